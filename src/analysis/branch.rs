@@ -7,10 +7,51 @@ pub enum ConditionContext {
     Storage,
     /// External context (msg.sender, params, etc.) - uses "when"
     External,
+    /// `a && b` or `a || b` whose sides differ, with the context of each side, so that
+    /// each side gets its own prefix
+    Mixed(Box<ConditionContext>, Box<ConditionContext>),
+}
+
+impl ConditionContext {
+    /// Context of the condition as a whole: storage if any part of it reads storage
+    pub fn overall(&self) -> ConditionContext {
+        match self {
+            ConditionContext::Mixed(left, right) => {
+                if left.overall() == ConditionContext::Storage
+                    || right.overall() == ConditionContext::Storage
+                {
+                    ConditionContext::Storage
+                } else {
+                    ConditionContext::External
+                }
+            }
+            context => context.clone(),
+        }
+    }
+}
+
+/// Kind of check a branch point comes from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CheckKind {
+    /// A guard that reverts (`require`, if-revert, external call)
+    Require,
+    /// An `assert`, which states an invariant rather than validating input
+    Assert,
+    /// A guard clause that stops without reverting (`if (cond) return;`)
+    EarlyReturn,
+    /// A loop guard that skips the current item (`if (cond) continue;`)
+    Continue,
+    /// A loop guard that leaves the loop (`if (cond) break;`)
+    Break,
+    /// A checked conversion (`amount.toUint128()`) that reverts when the value doesn't fit
+    Overflow,
+    /// `assert(false)` or `revert("unreachable")`, which fails whenever it's reached: code
+    /// that shouldn't run, like the last `else` of an exhaustive if/else chain
+    Unreachable,
 }
 
 /// A branch point in the control flow where a revert can occur
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct BranchPoint {
     /// The condition expression
     pub condition: ConditionExpr,
@@ -18,14 +59,60 @@ pub struct BranchPoint {
     pub context: ConditionContext,
     /// Whether this branch point is inside a loop (for "when any" labeling)
     pub is_loop: bool,
+    /// Whether this check is inside an `unchecked` block, where its arithmetic wraps
+    /// instead of reverting on overflow
+    pub is_unchecked: bool,
     /// Whether this is an external call (try/catch)
     pub is_external_call: bool,
     /// True if from if-revert pattern (TRUE causes revert), false if from require (FALSE causes revert)
     pub is_if_revert: bool,
+    /// Whether this check is a `require`-style guard or an `assert`
+    pub kind: CheckKind,
+    /// Original Solidity source of the check on a single line (e.g. `require(amount > 0)`)
+    pub source: Option<String>,
+    /// Line and column (both from 1) where the check starts, in the file defining it
+    pub span: Option<(usize, usize)>,
+    /// Name of the custom error raised when the check fails (e.g. `NotOwner`)
+    pub revert_reason: Option<String>,
+    /// String message given to `require`/`revert` (e.g. `amount must be positive`)
+    pub revert_message: Option<String>,
+    /// Set when this is an `if`/`else` whose arms don't revert outright: the tree forks
+    /// on `condition` and each side continues with its own arm's checks. On a guard that
+    /// stops early, `then_checks` are the checks in its body before it stops
+    pub arms: Option<Arms>,
+}
+
+impl BranchPoint {
+    /// Whether two checks are the same apart from where they're written, such as a
+    /// `require` repeated in each overload of a function
+    pub fn same_check(&self, other: &BranchPoint) -> bool {
+        self.without_span() == other.without_span()
+    }
+
+    fn without_span(&self) -> BranchPoint {
+        let strip = |checks: &[BranchPoint]| checks.iter().map(Self::without_span).collect();
+        BranchPoint {
+            span: None,
+            arms: self.arms.as_ref().map(|arms| Arms {
+                then_checks: strip(&arms.then_checks),
+                else_checks: strip(&arms.else_checks),
+            }),
+            ..self.clone()
+        }
+    }
+}
+
+/// Checks inside each arm of an `if`/`else`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Arms {
+    /// Checks when the condition holds
+    pub then_checks: Vec<BranchPoint>,
+    /// Checks when it doesn't (empty without an `else`)
+    pub else_checks: Vec<BranchPoint>,
 }
 
 /// Represents a condition expression for label generation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ConditionExpr {
     /// Binary comparison: a == b, a > b, etc.
     Binary {
@@ -45,7 +132,7 @@ pub enum ConditionExpr {
     ExternalCall(String),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum BinaryOp {
     Eq,
     NotEq,
@@ -55,6 +142,32 @@ pub enum BinaryOp {
     Lte,
 }
 
+impl BinaryOp {
+    /// The comparison that holds exactly when this one doesn't
+    pub fn negated(&self) -> BinaryOp {
+        match self {
+            BinaryOp::Eq => BinaryOp::NotEq,
+            BinaryOp::NotEq => BinaryOp::Eq,
+            BinaryOp::Gt => BinaryOp::Lte,
+            BinaryOp::Gte => BinaryOp::Lt,
+            BinaryOp::Lt => BinaryOp::Gte,
+            BinaryOp::Lte => BinaryOp::Gt,
+        }
+    }
+
+    /// The same comparison with its operands swapped: `a < b` is `b > a`
+    pub fn swapped(&self) -> BinaryOp {
+        match self {
+            BinaryOp::Eq => BinaryOp::Eq,
+            BinaryOp::NotEq => BinaryOp::NotEq,
+            BinaryOp::Gt => BinaryOp::Lt,
+            BinaryOp::Gte => BinaryOp::Lte,
+            BinaryOp::Lt => BinaryOp::Gt,
+            BinaryOp::Lte => BinaryOp::Gte,
+        }
+    }
+}
+
 impl std::fmt::Display for BinaryOp {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -68,6 +181,32 @@ impl std::fmt::Display for BinaryOp {
     }
 }
 
+impl ConditionExpr {
+    /// Fold negations into the condition: `!(a == b)` becomes `a != b`, `!(a > b)`
+    /// becomes `a <= b` and `!!a` becomes `a`. Negated `&&`/`||` keep their shape so
+    /// the labeler can still render them with or without De Morgan
+    pub fn normalize(self) -> ConditionExpr {
+        match self {
+            ConditionExpr::Not(inner) => match inner.normalize() {
+                ConditionExpr::Not(inner) => *inner,
+                ConditionExpr::Binary { left, op, right } => ConditionExpr::Binary {
+                    left,
+                    op: op.negated(),
+                    right,
+                },
+                inner => ConditionExpr::Not(Box::new(inner)),
+            },
+            ConditionExpr::And(left, right) => {
+                ConditionExpr::And(Box::new(left.normalize()), Box::new(right.normalize()))
+            }
+            ConditionExpr::Or(left, right) => {
+                ConditionExpr::Or(Box::new(left.normalize()), Box::new(right.normalize()))
+            }
+            other => other,
+        }
+    }
+}
+
 impl std::fmt::Display for ConditionExpr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -80,3 +219,90 @@ impl std::fmt::Display for ConditionExpr {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn binary(left: &str, op: BinaryOp, right: &str) -> ConditionExpr {
+        ConditionExpr::Binary {
+            left: left.to_string(),
+            op,
+            right: right.to_string(),
+        }
+    }
+
+    fn not(inner: ConditionExpr) -> ConditionExpr {
+        ConditionExpr::Not(Box::new(inner))
+    }
+
+    fn ident(name: &str) -> ConditionExpr {
+        ConditionExpr::Ident(name.to_string())
+    }
+
+    #[test]
+    fn test_normalize_folds_negated_comparisons() {
+        let cases = [
+            (BinaryOp::Eq, BinaryOp::NotEq),
+            (BinaryOp::NotEq, BinaryOp::Eq),
+            (BinaryOp::Gt, BinaryOp::Lte),
+            (BinaryOp::Gte, BinaryOp::Lt),
+            (BinaryOp::Lt, BinaryOp::Gte),
+            (BinaryOp::Lte, BinaryOp::Gt),
+        ];
+
+        for (op, negated) in cases {
+            assert_eq!(
+                not(binary("a", op, "b")).normalize(),
+                binary("a", negated, "b")
+            );
+        }
+    }
+
+    #[test]
+    fn test_normalize_strips_double_negation() {
+        assert_eq!(not(not(ident("paused"))).normalize(), ident("paused"));
+        assert_eq!(
+            not(not(not(ident("paused")))).normalize(),
+            not(ident("paused"))
+        );
+        assert_eq!(
+            not(not(not(binary("a", BinaryOp::Eq, "b")))).normalize(),
+            binary("a", BinaryOp::NotEq, "b")
+        );
+        assert_eq!(not(ident("paused")).normalize(), not(ident("paused")));
+    }
+
+    #[test]
+    fn test_normalize_recurses_into_logical_operators() {
+        let condition = ConditionExpr::And(
+            Box::new(not(binary("a", BinaryOp::Gt, "0"))),
+            Box::new(ConditionExpr::Or(
+                Box::new(not(not(ident("open")))),
+                Box::new(not(binary("b", BinaryOp::Lt, "c"))),
+            )),
+        );
+        let expected = ConditionExpr::And(
+            Box::new(binary("a", BinaryOp::Lte, "0")),
+            Box::new(ConditionExpr::Or(
+                Box::new(ident("open")),
+                Box::new(binary("b", BinaryOp::Gte, "c")),
+            )),
+        );
+        assert_eq!(condition.normalize(), expected);
+    }
+
+    #[test]
+    fn test_normalize_keeps_negated_logical_operators() {
+        // De Morgan is left to the labeler, which can render `not (a and b)` literally
+        let condition = not(ConditionExpr::And(
+            Box::new(not(not(ident("a")))),
+            Box::new(not(binary("b", BinaryOp::Eq, "c"))),
+        ));
+        let expected = not(ConditionExpr::And(
+            Box::new(ident("a")),
+            Box::new(binary("b", BinaryOp::NotEq, "c")),
+        ));
+        assert_eq!(condition.normalize(), expected);
+    }
+}
@@ -2,5 +2,7 @@
 
 mod branch;
 mod classifier;
+mod warning;
 
-pub use branch::{BinaryOp, BranchPoint, ConditionContext, ConditionExpr};
+pub use branch::{Arms, BinaryOp, BranchPoint, CheckKind, ConditionContext, ConditionExpr};
+pub use warning::{Warning, WarningKind};
@@ -0,0 +1,48 @@
+//! Warnings collected during analysis
+
+use std::fmt;
+use std::path::PathBuf;
+
+/// What the analysis had to skip or couldn't model
+#[derive(Debug, Clone, PartialEq)]
+pub enum WarningKind {
+    /// A file that failed to parse and was skipped
+    ParseSkipped { reason: String },
+    /// A modifier used by a function whose definition wasn't found
+    ModifierUnresolved { modifier: String, function: String },
+    /// A statement or expression the analysis doesn't understand
+    UnhandledConstruct { construct: String },
+}
+
+/// A warning with the location it applies to
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    pub kind: WarningKind,
+    pub file: PathBuf,
+    /// 1-based line, when the warning points at a specific construct
+    pub line: Option<usize>,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.file.display())?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+        }
+        write!(f, ": ")?;
+
+        match &self.kind {
+            WarningKind::ParseSkipped { reason } => {
+                write!(f, "skipped, failed to parse: {}", reason)
+            }
+            WarningKind::ModifierUnresolved { modifier, function } => write!(
+                f,
+                "modifier `{}` used by `{}` could not be resolved",
+                modifier, function
+            ),
+            WarningKind::UnhandledConstruct { construct } => {
+                write!(f, "unhandled construct: {}", construct)
+            }
+        }
+    }
+}
@@ -1,23 +1,45 @@
-use clap::{Parser, Subcommand};
-use std::collections::HashMap;
+use clap::{Parser, Subcommand, ValueEnum};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::io::Write;
-use std::path::Path;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 
-use crate::foundry::FoundryProject;
-use crate::output::render_to_string;
-use crate::parser::SolarParser;
-use crate::tree::TreeBuilder;
+use crate::analysis::{BranchPoint, Warning, WarningKind};
+use crate::config::{AcaciaConfig, LeafLabels};
+use crate::coverage::{check_coverage, CoverageReport};
+use crate::diff::{diff_trees, TreeDiff};
+use crate::foundry::{FoundryError, FoundryProject, SourceFilter};
+use crate::output::{
+    bulloak_root, colorize, parse_tree, parse_trees, render_audit_json, render_invariants,
+    render_scaffold, render_styled, render_to_mermaid, use_color, Connectors,
+};
+use crate::parser::{FunctionContext, ParserError, SolarParser};
+use crate::summary::{FunctionStats, Summary};
+use crate::tree::{LabelConfig, TreeBuilder, TreeNode, TreeOptions};
 
 /// Acacia - BTT Tree Generator for Solidity Smart Contracts
 #[derive(Parser)]
 #[command(name = "acacia")]
 #[command(version, about, long_about = None)]
 pub struct Cli {
+    /// foundry.toml profile to read `src`, `lib` and `remappings` from
+    /// (default: $FOUNDRY_PROFILE, else `default`)
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// Don't look for foundry.toml: use the current directory as the project root,
+    /// reading sources from `src/` or `contracts/` (for Hardhat or plain projects)
+    #[arg(long, global = true, conflicts_with = "profile")]
+    no_foundry: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
 
+// Parsed once per run, so the size of `Generate` doesn't matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand)]
 enum Commands {
     /// Generate a BTT-style test tree for a function
@@ -27,317 +49,3462 @@ enum Commands {
         #[arg(value_name = "TARGET", default_value = "")]
         target: String,
 
-        /// Output directory (default: test/trees/)
-        #[arg(short, long, default_value = "test/trees")]
-        output: String,
-    },
-}
+        /// Output directory (default: `output_dir` from .acacia.toml, else test/trees/)
+        #[arg(short, long)]
+        output: Option<String>,
 
-impl Cli {
-    pub fn run(self) -> Result<(), Box<dyn std::error::Error>> {
-        match self.command {
-            Commands::Generate { target, output } => generate_tree(&target, &output),
-        }
-    }
-}
+        /// Identifier prefix to strip from labels (repeatable, e.g. `--strip-prefix s_`)
+        #[arg(long = "strip-prefix", value_name = "PREFIX")]
+        strip_prefix: Vec<String>,
 
-/// Parsed target with optional contract and function names
-enum ParsedTarget {
-    /// No target - generate for all contracts in project
-    AllContracts,
-    /// Contract only - generate for all functions in this contract
-    Contract { contract_name: String },
-    /// Specific function
-    Function {
-        contract_name: String,
-        function_name: String,
-        signature: Option<String>,
-    },
-}
+        /// Render negated compound conditions literally (`not (a and b)`) instead of applying De Morgan
+        #[arg(long)]
+        no_demorgan: bool,
 
-fn generate_tree(target: &str, output_dir: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let parsed = parse_target(target);
+        /// Give each side of `require(a && b)` (and `if (a || b) revert`) its own branch level
+        #[arg(long)]
+        split_logical: bool,
 
-    // Discover Foundry project
-    let project = FoundryProject::discover()?;
-    println!("Found Foundry project at: {:?}", project.root());
+        /// Sort independent checks by condition instead of source order, for stable diffs
+        #[arg(long)]
+        stable_order: bool,
 
-    let parser = SolarParser::new(&project);
+        /// Label `block.timestamp`/`block.number` checks against deadline-like names as
+        /// "the deadline has (not) passed"
+        #[arg(long)]
+        time_labels: bool,
 
-    match parsed {
-        // Generate trees for ALL contracts in the project
-        ParsedTarget::AllContracts => {
-            println!("Generating BTT trees for all contracts in project");
+        /// Render at most N levels of branches, summarizing the remaining checks in one leaf
+        #[arg(long, value_name = "N")]
+        max_depth: Option<usize>,
 
-            let contract_files = project.find_all_contracts();
-            if contract_files.is_empty() {
-                println!("No Solidity files found in src directory");
-                return Ok(());
-            }
+        /// Whether the reverting branch of each check comes `first` (default) or `last`
+        #[arg(long, value_enum, default_value_t = RevertPosition::First)]
+        revert_position: RevertPosition,
 
-            println!("Found {} Solidity files", contract_files.len());
-            let mut total_trees = 0;
+        /// In combined overload files, render branches shared by every overload once
+        #[arg(long)]
+        dedup_across_overloads: bool,
 
-            for file_path in contract_files {
-                // Get all contracts in this file
-                let contracts = match parser.get_contract_names(&file_path) {
-                    Ok(c) => c,
-                    Err(e) => {
-                        eprintln!("Warning: Failed to parse {:?}: {}", file_path, e);
-                        continue;
-                    }
-                };
+        /// Write each overload to its own file named by its signature
+        /// (`transfer_address_uint256.tree`) instead of combining them
+        #[arg(long, conflicts_with = "dedup_across_overloads")]
+        split_overloads: bool,
 
-                for contract_name in contracts {
-                    total_trees +=
-                        process_contract(&parser, &file_path, &contract_name, output_dir)?;
-                }
-            }
+        /// Only generate trees for functions that don't have a tree file yet
+        #[arg(long)]
+        only_missing: bool,
 
-            println!("Generated {} trees total", total_trees);
-        }
+        /// Keep hand-edited leaves of existing tree files, regenerating only the branches
+        #[arg(long, conflicts_with = "only_missing")]
+        merge: bool,
 
-        // Generate trees for a specific contract
-        ParsedTarget::Contract { contract_name } => {
-            let contract_path = project.find_contract(&contract_name)?;
-            println!("Found contract at: {:?}", contract_path);
+        /// Skip functions without a single guard (getters, plain setters), whose tree
+        /// would only be `it should succeed`. Overloads are skipped only if all are
+        #[arg(long)]
+        only_reverting: bool,
 
-            println!(
-                "Generating BTT trees for all public/external functions in {}",
-                contract_name
-            );
+        /// Also generate trees for internal and private functions
+        #[arg(long)]
+        include_internal: bool,
 
-            let count = process_contract(&parser, &contract_path, &contract_name, output_dir)?;
+        /// Include the checks of internal and private functions where the target calls them
+        #[arg(long)]
+        inline_helpers: bool,
 
-            println!("Generated {} trees for {}", count, contract_name);
-        }
+        /// Branch on checked additions and multiplications that can overflow
+        #[arg(long)]
+        arithmetic_overflow: bool,
 
-        // Generate tree(s) for a specific function
-        ParsedTarget::Function {
-            contract_name,
-            function_name,
-            signature,
-        } => {
-            let contract_path = project.find_contract(&contract_name)?;
-            println!("Found contract at: {:?}", contract_path);
+        /// Label comparisons against constants with their values (`amount is at most 100`
+        /// for `uint256 constant MAX = 100`) instead of their names
+        #[arg(long)]
+        resolve_constants: bool,
 
-            let contract_snake = to_snake_case(&contract_name);
-            let contract_output_dir = Path::new(output_dir).join(contract_snake);
-            fs::create_dir_all(&contract_output_dir)?;
+        /// Skip source files matching a glob, relative to the project root
+        /// (repeatable, e.g. `--exclude '**/mocks/**'`)
+        #[arg(long, value_name = "GLOB")]
+        exclude: Vec<String>,
 
-            match signature {
-                Some(sig) => {
-                    // Specific signature provided
-                    println!(
-                        "Generating BTT tree for {}::{}({})",
-                        contract_name, function_name, sig
-                    );
+        /// Output format for generated trees (default: `format` from .acacia.toml, else tree)
+        #[arg(long, value_enum)]
+        format: Option<OutputFormat>,
 
-                    let function_ctx = parser.parse_function_by_signature(
-                        &contract_path,
-                        &contract_name,
-                        &function_name,
-                        &sig,
-                    )?;
+        /// End each condition of `tree` and `annotated` output with the source line it's
+        /// checked on (`// L42`)
+        #[arg(long)]
+        with_lines: bool,
 
-                    println!("Found {} branch points", function_ctx.branch_points.len());
+        /// Draw trees with ASCII connectors (`|--`, `` `-- ``) instead of box-drawing
+        /// characters, for terminals and CI logs that mangle them
+        #[arg(long)]
+        ascii: bool,
 
-                    let tree = TreeBuilder::build(&function_name, function_ctx.branch_points)?;
-                    let content = render_to_string(&tree);
+        /// Tree dialect: `acacia` (default) or `bulloak` (roots like `transfer_address_Test`
+        /// so files can be scaffolded with `bulloak scaffold`)
+        #[arg(long, value_enum, default_value_t = Dialect::Acacia)]
+        dialect: Dialect,
 
-                    // Note: We append only if it exists? Or wait, user wants overloads in same file.
-                    // But here we are targeting a specific signature.
-                    // If the user specifies a signature, they probably want just that tree.
-                    // However, to be consistent with the file naming convention "FunctionName.tree",
-                    // if we modify that file, we should probably append to it if it exists, or overwrite?
-                    // Given the user said "in case of function overloading ... keep file name the same as function name and add all trees in the same file",
-                    // implies if we run this command multiple times for different signatures, they might want them merged.
-                    // But for simplicity, if I run for a specific signature, I will write just that tree to "FunctionName.tree".
-                    // If they want all, they should run without signature.
+        /// Output layout: `flat` (default, `<output>/<contract>/`) or `mirror` (the source
+        /// file's directories under `src` repeated, `<output>/tokens/<contract>/`)
+        #[arg(long, value_enum, default_value_t = Layout::Flat)]
+        layout: Layout,
 
-                    let output_path = contract_output_dir.join(format!("{}.tree", function_name));
+        /// Don't write anything; fail if any tree file is missing or differs from what
+        /// would be generated
+        #[arg(long)]
+        check: bool,
 
-                    let mut file = fs::File::create(&output_path)?;
-                    file.write_all(content.as_bytes())?;
+        /// Print the trees instead of writing files, colored when stdout is a terminal
+        /// (unless `NO_COLOR` is set)
+        #[arg(long, conflicts_with_all = ["check", "merge", "watch"])]
+        stdout: bool,
 
-                    println!("Generated tree at: {:?}", output_path);
+        /// Write nothing; list the files a real run would write with their number of
+        /// branch points
+        #[arg(long, conflicts_with_all = ["check", "stdout", "watch"])]
+        dry_run: bool,
+
+        /// After generating for the whole project, write an `index.md` at the output root
+        /// linking every tree file, grouped by contract
+        #[arg(long, conflicts_with_all = ["check", "stdout", "dry_run"])]
+        index: bool,
+
+        /// Also write a Solidity test scaffold (`withdraw.t.sol`) next to each new tree file,
+        /// with an empty test per leaf group and a modifier per nested condition
+        #[arg(long, conflicts_with_all = ["check", "stdout", "dry_run"])]
+        scaffold: bool,
+
+        /// File listing one target per line (blank lines and `#` comments are ignored)
+        #[arg(long, value_name = "FILE", conflicts_with = "target")]
+        targets_file: Option<String>,
+
+        /// Keep running and regenerate the trees of contracts whose source file changes
+        #[arg(long, conflicts_with_all = ["check", "targets_file"])]
+        watch: bool,
+
+        /// Also write the end-of-run summary (contracts, functions, branch points) as JSON
+        #[arg(long, value_name = "PATH")]
+        json_summary: Option<String>,
+
+        /// Generate trees for every contract in one .sol file, even outside a project
+        #[arg(long, value_name = "PATH", conflicts_with_all = ["target", "targets_file"])]
+        single_file: Option<String>,
+
+        /// Exit non-zero if any source file fails to parse, instead of only warning
+        #[arg(long)]
+        strict: bool,
+
+        /// Don't warn about functions whose tree has no branches
+        #[arg(long)]
+        quiet: bool,
+
+        /// Leaf text for reverting paths (default: "it should revert")
+        #[arg(long, value_name = "TEXT")]
+        revert_label: Option<String>,
+
+        /// Leaf text for the happy path (default: "it should succeed")
+        #[arg(long, value_name = "TEXT")]
+        success_label: Option<String>,
+
+        /// Leaf text for guard clauses that return early (default: "it should return early")
+        #[arg(long, value_name = "TEXT")]
+        early_return_label: Option<String>,
+    },
+
+    /// List the functions a target would generate trees for, without writing anything
+    List {
+        /// Target (optional): ContractName or ContractName::functionName.
+        /// If omitted, lists every contract in the project
+        #[arg(value_name = "TARGET", default_value = "")]
+        target: String,
+    },
+
+    /// Report which tree leaves have no matching test function
+    Coverage {
+        /// Target: ContractName, ContractName::functionName, or ContractName::functionName(args)
+        #[arg(value_name = "TARGET")]
+        target: String,
+
+        /// Test file (`*.t.sol`) whose `test_`/`it_` functions are matched against the leaves
+        #[arg(long, value_name = "FILE")]
+        tests: String,
+    },
+
+    /// Show which branches a regenerated tree would add or remove from the tree files on disk
+    Diff {
+        /// Target: ContractName or ContractName::functionName
+        #[arg(value_name = "TARGET")]
+        target: String,
+
+        /// Directory of the existing trees (default: `output_dir` from .acacia.toml, else test/trees/)
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// Output layout the trees were generated with
+        #[arg(long, value_enum, default_value_t = Layout::Flat)]
+        layout: Layout,
+    },
+
+    /// Read Solidity source from stdin and print one function's tree, for editor integrations
+    Stdin {
+        /// Contract defining the function
+        #[arg(long)]
+        contract: String,
+
+        /// Function name, optionally with its signature (`transfer(address,uint256)`)
+        #[arg(long)]
+        function: String,
+
+        /// Where the source is saved, used to resolve its imports and base contracts
+        /// (default: `<stdin>.sol` in the current directory)
+        #[arg(long, value_name = "PATH")]
+        path: Option<String>,
+
+        /// Output format (default: `format` from .acacia.toml, else tree)
+        #[arg(long, value_enum)]
+        format: Option<OutputFormat>,
+    },
+}
+
+impl Cli {
+    pub fn run(self) -> Result<(), Box<dyn std::error::Error>> {
+        match self.command {
+            Commands::Generate {
+                target,
+                output,
+                strip_prefix,
+                no_demorgan,
+                split_logical,
+                stable_order,
+                time_labels,
+                max_depth,
+                revert_position,
+                dedup_across_overloads,
+                split_overloads,
+                only_missing,
+                merge,
+                only_reverting,
+                include_internal,
+                inline_helpers,
+                arithmetic_overflow,
+                resolve_constants,
+                exclude,
+                format,
+                with_lines,
+                ascii,
+                dialect,
+                layout,
+                check,
+                stdout,
+                dry_run,
+                index,
+                scaffold,
+                targets_file,
+                watch,
+                json_summary,
+                single_file,
+                strict,
+                quiet,
+                revert_label,
+                success_label,
+                early_return_label,
+            } => {
+                // Discover Foundry project. A single file doesn't need one: its
+                // directory stands in for the project root
+                let project = match (
+                    &single_file,
+                    discover_project(self.no_foundry, self.profile.as_deref()),
+                ) {
+                    (Some(path), Err(FoundryError::ProjectNotFound)) => {
+                        FoundryProject::plain(single_file_root(Path::new(path))?)
+                    }
+                    (_, project) => project?,
+                };
+                let target = single_file.unwrap_or(target);
+
+                // Flags win over .acacia.toml, which wins over the built-in defaults
+                let config = AcaciaConfig::load(project.root())?;
+                let output = output_dir(&project, &config, output);
+                let format = match (format, &config.format) {
+                    (Some(format), _) => format,
+                    (None, Some(name)) => OutputFormat::from_str(name, true)
+                        .map_err(|e| format!("Invalid format in .acacia.toml: {}", e))?,
+                    (None, None) => OutputFormat::default(),
+                };
+
+                let sources = source_filter(&config);
+                let options = GenerateOptions {
+                    tree: TreeOptions {
+                        strip_prefixes: strip_prefix,
+                        no_demorgan,
+                        split_logical,
+                        stable_order,
+                        time_labels,
+                        initialized_flags: config.initialized_flags.clone(),
+                        max_depth,
+                        revert_last: revert_position == RevertPosition::Last,
+                        labels: label_config(
+                            config.labels,
+                            revert_label,
+                            success_label,
+                            early_return_label,
+                        ),
+                    },
+                    format,
+                    with_lines,
+                    connectors: if ascii {
+                        Connectors::ASCII
+                    } else {
+                        Connectors::UNICODE
+                    },
+                    dialect,
+                    layout,
+                    dedup_across_overloads,
+                    split_overloads,
+                    only_missing,
+                    merge,
+                    only_reverting,
+                    include_internal: include_internal || config.include_internal.unwrap_or(false),
+                    inline_helpers,
+                    arithmetic_overflow,
+                    resolve_constants,
+                    overflow_calls: config.overflow_calls,
+                    exclude: build_excludes(&exclude)?,
+                    sources,
+                    check,
+                    stdout,
+                    dry_run,
+                    index,
+                    scaffold,
+                };
+                options.progress(format_args!("Found project at: {:?}", project.root()));
+
+                let mut report = match targets_file {
+                    Some(path) => {
+                        generate_from_targets_file(&project, Path::new(&path), &output, &options)?
+                    }
+                    None => generate_tree(&project, &target, &output, &options)?,
+                };
+
+                for warning in &report.warnings {
+                    eprintln!("Warning: {}", warning);
                 }
-                None => {
-                    // No signature - generate for all overloads of this function
-                    let function_contexts = parser.parse_all_functions(
-                        &contract_path,
-                        &contract_name,
-                        &function_name,
-                    )?;
 
-                    let num_overloads = function_contexts.len();
-                    println!(
-                        "Found {} overloads for {}::{}",
-                        num_overloads, contract_name, function_name
-                    );
+                if dry_run {
+                    print!("{}", format_planned(&report.planned));
+                }
 
-                    let mut combined_content = String::new();
-                    for (i, function_ctx) in function_contexts.iter().enumerate() {
-                        let root_name = if num_overloads > 1 {
-                            format!("{}({})", function_name, function_ctx.signature)
-                        } else {
-                            function_name.clone()
-                        };
+                let mut summary = Summary::new(&report.functions);
+                if quiet {
+                    summary.unguarded.clear();
+                } else if !summary.unguarded.is_empty() {
+                    eprintln!(
+                        "Warning: {} functions have no branches; their guards may be missing or unrecognized (--quiet to hide)",
+                        summary.unguarded.len()
+                    );
+                }
+                if stdout {
+                    eprint!("{}", summary);
+                } else {
+                    print!("{}", summary);
+                }
+                if let Some(path) = json_summary {
+                    fs::write(&path, serde_json::to_string_pretty(&summary)?)?;
+                }
 
-                        let tree =
-                            TreeBuilder::build(&root_name, function_ctx.branch_points.clone())?;
+                check_parse_failures(&report, strict)?;
+                check_tree_failures(&report)?;
 
-                        if i > 0 {
-                            combined_content.push_str("\n");
+                if check {
+                    report.stale.sort();
+                    if !report.stale.is_empty() {
+                        println!("Out-of-date trees:");
+                        for path in &report.stale {
+                            println!("  {}", path.display());
                         }
-                        combined_content.push_str(&render_to_string(&tree));
+                        return Err(
+                            format!("{} tree files are out of date", report.stale.len()).into()
+                        );
+                    }
+                    println!("All trees are up to date");
+                }
+
+                if watch {
+                    watch_sources(&project, &target, &output, &options);
+                }
+                Ok(())
+            }
+
+            Commands::List { target } => {
+                let project = discover_project(self.no_foundry, self.profile.as_deref())?;
+                println!("Found project at: {:?}", project.root());
+
+                let listings = list_target(&project, &target)?;
+                print!("{}", format_listings(&listings));
+                Ok(())
+            }
+
+            Commands::Coverage { target, tests } => {
+                let project = discover_project(self.no_foundry, self.profile.as_deref())?;
+                println!("Found project at: {:?}", project.root());
+
+                let report = check_target_coverage(&project, &target, Path::new(&tests))?;
+
+                println!(
+                    "Covered {}/{} leaves ({:.1}%)",
+                    report.covered(),
+                    report.total,
+                    report.percentage()
+                );
+                if !report.gaps.is_empty() {
+                    println!("Leaves without a test:");
+                    for gap in &report.gaps {
+                        println!("  - {}", gap);
                     }
+                }
+                Ok(())
+            }
 
-                    let output_path = contract_output_dir.join(format!("{}.tree", function_name));
-                    let mut file = fs::File::create(&output_path)?;
-                    file.write_all(combined_content.as_bytes())?;
+            Commands::Diff {
+                target,
+                output,
+                layout,
+            } => {
+                let project = discover_project(self.no_foundry, self.profile.as_deref())?;
+                println!("Found project at: {:?}", project.root());
+
+                // Regenerate the way `generate` would with the same .acacia.toml
+                let config = AcaciaConfig::load(project.root())?;
+                let output = output_dir(&project, &config, output);
+                let options = GenerateOptions {
+                    tree: TreeOptions {
+                        initialized_flags: config.initialized_flags.clone(),
+                        labels: label_config(config.labels, None, None, None),
+                        ..TreeOptions::default()
+                    },
+                    include_internal: config.include_internal.unwrap_or(false),
+                    overflow_calls: config.overflow_calls,
+                    layout,
+                    ..GenerateOptions::default()
+                };
 
-                    println!("Generated combined tree at: {:?}", output_path);
+                let diffs = diff_target(&project, &target, &output, &options)?;
+                if diffs.is_empty() {
+                    println!("No changes");
                 }
+                for (path, diff) in &diffs {
+                    println!("{}", path.display());
+                    print!("{}", diff);
+                }
+                Ok(())
+            }
+
+            Commands::Stdin {
+                contract,
+                function,
+                path,
+                format,
+            } => {
+                // Nothing but the tree goes to stdout, so the output can be used as is
+                let project = match discover_project(self.no_foundry, self.profile.as_deref()) {
+                    Err(FoundryError::ProjectNotFound) => {
+                        FoundryProject::plain(std::env::current_dir()?)
+                    }
+                    project => project?,
+                };
+                let config = AcaciaConfig::load(project.root())?;
+                let format = match (format, &config.format) {
+                    (Some(format), _) => format,
+                    (None, Some(name)) => OutputFormat::from_str(name, true)
+                        .map_err(|e| format!("Invalid format in .acacia.toml: {}", e))?,
+                    (None, None) => OutputFormat::default(),
+                };
+                let options = GenerateOptions {
+                    tree: TreeOptions {
+                        initialized_flags: config.initialized_flags.clone(),
+                        labels: label_config(config.labels, None, None, None),
+                        ..TreeOptions::default()
+                    },
+                    format,
+                    overflow_calls: config.overflow_calls,
+                    ..GenerateOptions::default()
+                };
+
+                let mut source = String::new();
+                std::io::stdin().read_to_string(&mut source)?;
+                let path = std::env::current_dir()?.join(path.as_deref().unwrap_or(STDIN_FILE));
+
+                let tree =
+                    tree_from_source(&project, &path, &source, &contract, &function, &options)?;
+                print!("{}", tree);
+                Ok(())
             }
         }
     }
-
-    Ok(())
 }
 
-fn process_contract(
-    parser: &SolarParser,
-    file_path: &Path,
-    contract_name: &str,
-    output_dir: &str,
-) -> Result<usize, Box<dyn std::error::Error>> {
-    let function_contexts = parser.parse_all_public_functions(file_path, contract_name)?;
+/// How generated trees are written out
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+enum OutputFormat {
+    /// Plain BTT tree
+    #[default]
+    Tree,
+    /// BTT tree with each condition's Solidity source as a trailing comment
+    Annotated,
+    /// List of `assert`ed conditions as candidate invariant properties
+    Invariants,
+    /// Mermaid flowchart
+    Mermaid,
+    /// Every revert condition with its line, as JSON for audit tooling
+    AuditJson,
+}
 
-    if function_contexts.is_empty() {
-        return Ok(0);
+impl OutputFormat {
+    /// File extension for generated files
+    fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Tree | OutputFormat::Annotated => "tree",
+            OutputFormat::Invariants => "invariants",
+            OutputFormat::Mermaid => "mmd",
+            OutputFormat::AuditJson => "json",
+        }
     }
 
-    // Group functions by name
-    let mut func_groups: HashMap<String, Vec<&crate::parser::FunctionContext>> = HashMap::new();
-    for ctx in &function_contexts {
-        func_groups
-            .entry(ctx.function_name.clone())
-            .or_default()
-            .push(ctx);
+    /// Line comment marker, used for headers in combined overload files
+    fn comment(self) -> &'static str {
+        match self {
+            OutputFormat::Mermaid => "%%",
+            _ => "//",
+        }
     }
+}
 
-    let contract_snake = to_snake_case(contract_name);
-    let contract_output_dir = Path::new(output_dir).join(contract_snake);
-    fs::create_dir_all(&contract_output_dir)?;
+/// Conventions the generated trees follow
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+enum Dialect {
+    /// Function name (and signature for overloads) as the root
+    #[default]
+    Acacia,
+    /// Identifier roots with a `_Test` suffix and no comment headers, as bulloak expects
+    Bulloak,
+}
 
-    let mut generated_count = 0;
+/// Order of the two branches of each check
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+enum RevertPosition {
+    /// The reverting branch, then the one that continues
+    #[default]
+    First,
+    /// The branch that continues, then the reverting one
+    Last,
+}
 
-    for (func_name, contexts) in func_groups {
-        let mut combined_content = String::new();
-        let is_overloaded = contexts.len() > 1;
+/// Where each contract's trees go under the output directory
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+enum Layout {
+    /// One directory per contract: `<output>/<contract>/<function>.tree`
+    #[default]
+    Flat,
+    /// The source file's directories under `src` in front of the contract directory:
+    /// `src/tokens/Vault.sol` goes to `<output>/tokens/vault/<function>.tree`
+    Mirror,
+}
 
-        // Use the order from contexts, which is deterministic based on parser output (order of definition)
-        for (i, ctx) in contexts.iter().enumerate() {
-            let root_name = if is_overloaded {
-                format!("{}({})", func_name, ctx.signature)
-            } else {
-                func_name.clone()
-            };
+/// Settings shared by every tree generated in one run
+#[derive(Debug, Clone, Default)]
+struct GenerateOptions {
+    tree: TreeOptions,
+    format: OutputFormat,
+    /// Add the source line of each condition as a trailing comment
+    with_lines: bool,
+    /// Characters drawing the trees
+    connectors: Connectors,
+    dialect: Dialect,
+    layout: Layout,
+    /// Factor branches common to all overloads out of combined files
+    dedup_across_overloads: bool,
+    /// One file per overload instead of a combined file
+    split_overloads: bool,
+    /// Leave functions that already have an output file untouched
+    only_missing: bool,
+    /// Keep the leaves of existing tree files where their branches still match
+    merge: bool,
+    /// Write no file for functions whose trees have no branches
+    only_reverting: bool,
+    /// Generate internal and private functions too
+    include_internal: bool,
+    /// Inline the checks of internal helpers at their call sites
+    inline_helpers: bool,
+    /// Branch on checked arithmetic that can overflow
+    arithmetic_overflow: bool,
+    /// Compare against constants' values instead of their names
+    resolve_constants: bool,
+    /// Methods that revert on overflow, instead of the `SafeCast` conversions
+    overflow_calls: Option<Vec<String>>,
+    /// Source files to skip when generating for the whole project
+    exclude: GlobSet,
+    /// Directories and `.gitignore`d paths left out when searching for sources
+    sources: SourceFilter,
+    /// Compare against the files on disk instead of writing them
+    check: bool,
+    /// Print to stdout instead of writing files
+    stdout: bool,
+    /// Only record the files that would be written
+    dry_run: bool,
+    /// Write an index of the tree files after a whole-project run
+    index: bool,
+    /// Write a Solidity test scaffold next to each tree file that doesn't have one
+    scaffold: bool,
+}
 
-            let tree = TreeBuilder::build(&root_name, ctx.branch_points.clone())?;
-            if i > 0 {
-                combined_content.push_str("\n");
+impl GenerateOptions {
+    /// Render a function's branch points in the selected format
+    fn render(
+        &self,
+        name: &str,
+        branch_points: Vec<BranchPoint>,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        match self.format {
+            OutputFormat::Invariants => {
+                return Ok(render_invariants(name, &branch_points, self.connectors))
             }
-            combined_content.push_str(&render_to_string(&tree));
+            OutputFormat::AuditJson => {
+                return Ok(render_audit_json(&[(name.to_string(), &branch_points)]))
+            }
+            _ => {}
         }
 
-        let output_path = contract_output_dir.join(format!("{}.tree", func_name));
-        let mut file = fs::File::create(&output_path)?;
-        file.write_all(combined_content.as_bytes())?;
+        let root_name = match self.dialect {
+            Dialect::Acacia => name.to_string(),
+            Dialect::Bulloak => bulloak_root(name),
+        };
 
-        println!("  -> {:?}", output_path);
-        generated_count += 1;
+        let tree = TreeBuilder::build_with_options(&root_name, branch_points, &self.tree)?;
+        Ok(match self.format {
+            OutputFormat::Mermaid => render_to_mermaid(&tree, &self.tree.labels),
+            format => self.render_tree(&tree, format == OutputFormat::Annotated),
+        })
     }
 
-    Ok(generated_count)
-}
-
-fn parse_target(target: &str) -> ParsedTarget {
-    // Empty target = all contracts
-    if target.is_empty() {
-        return ParsedTarget::AllContracts;
+    /// Render a tree as `tree` or `annotated` (with `sources`) output
+    fn render_tree(&self, tree: &TreeNode, sources: bool) -> String {
+        render_styled(tree, self.connectors, sources, self.with_lines)
     }
 
-    // Check if it contains :: (has function name)
-    if let Some(separator_pos) = target.find("::") {
-        let contract_name = target[..separator_pos].to_string();
-        let function_part = &target[separator_pos + 2..];
+    /// Parser configured for these options
+    fn parser<'a>(&self, project: &'a FoundryProject) -> SolarParser<'a> {
+        let parser = SolarParser::new(project)
+            .with_inline_helpers(self.inline_helpers)
+            .with_arithmetic_overflow(self.arithmetic_overflow)
+            .with_resolved_constants(self.resolve_constants);
+        match &self.overflow_calls {
+            Some(calls) => parser.with_overflow_calls(calls.clone()),
+            None => parser,
+        }
+    }
 
-        // Check if signature is provided: functionName(args)
-        if let Some(open_paren) = function_part.find('(') {
-            if function_part.ends_with(')') {
-                let function_name = function_part[..open_paren].to_string();
-                let signature = function_part[open_paren + 1..function_part.len() - 1].to_string();
-                return ParsedTarget::Function {
-                    contract_name,
-                    function_name,
-                    signature: Some(signature),
-                };
+    /// Directory for the trees of a contract defined in `file`
+    fn contract_dir(
+        &self,
+        project: &FoundryProject,
+        output_dir: &str,
+        file: &Path,
+        contract_name: &str,
+    ) -> PathBuf {
+        let mut dir = PathBuf::from(output_dir);
+        if self.layout == Layout::Mirror {
+            if let Some(parent) = file
+                .strip_prefix(project.src_dir())
+                .ok()
+                .and_then(Path::parent)
+            {
+                dir.push(parent);
             }
         }
+        dir.push(to_snake_case(contract_name));
+        dir
+    }
 
-        // No signature - just function name
-        ParsedTarget::Function {
-            contract_name,
-            function_name: function_part.to_string(),
-            signature: None,
+    /// Whether `--exclude` skips a source file
+    fn excludes(&self, project: &FoundryProject, path: &Path) -> bool {
+        self.exclude
+            .is_match(path.strip_prefix(project.root()).unwrap_or(path))
+    }
+
+    /// Functions of a contract to generate trees for
+    fn functions(
+        &self,
+        parser: &SolarParser,
+        file_path: &Path,
+        contract_name: &str,
+    ) -> Result<Vec<FunctionContext>, ParserError> {
+        if self.include_internal {
+            parser.parse_all_functions_filtered(file_path, contract_name, |_| true)
+        } else {
+            parser.parse_all_public_functions(file_path, contract_name)
         }
-    } else {
-        // Contract name only
-        ParsedTarget::Contract {
-            contract_name: target.to_string(),
+    }
+
+    /// Print a progress message. With `--stdout` it goes to stderr, leaving stdout to
+    /// the trees
+    fn progress(&self, message: std::fmt::Arguments<'_>) {
+        if self.stdout {
+            eprintln!("{}", message);
+        } else {
+            println!("{}", message);
         }
     }
-}
 
-/// Convert CamelCase to snake_case
-/// strictness: new word on first uppercase or uppercase preceded by lowercase
-fn to_snake_case(s: &str) -> String {
-    let mut result = String::new();
-    let chars: Vec<char> = s.chars().collect();
+    /// Put a function's `@custom:btt` hints above its tree as comments. bulloak and JSON
+    /// don't accept comments, so they're left out there
+    fn with_hints(&self, hints: &[String], content: String) -> String {
+        if self.dialect == Dialect::Bulloak || self.format == OutputFormat::AuditJson {
+            return content;
+        }
 
-    for (i, &c) in chars.iter().enumerate() {
-        if c.is_uppercase() {
-            // Add underscore if:
-            // 1. It's not the first character
-            // 2. The previous character is lowercase
-            if i > 0 {
-                let prev = chars[i - 1];
-                if prev.is_lowercase() {
-                    result.push('_');
-                }
-            }
-            result.push(c.to_ascii_lowercase());
-        } else {
-            result.push(c);
+        let mut output = String::new();
+        for hint in hints {
+            output.push_str(&format!("{} {}\n", self.format.comment(), hint));
         }
+        output + &content
     }
 
-    result
+    /// Prefix a section of a combined file with a comment header. bulloak doesn't
+    /// accept comments, and its roots already carry the signature
+    fn section(&self, header: &str, content: String) -> String {
+        match self.dialect {
+            Dialect::Acacia => format!("{} {}\n{}", self.format.comment(), header, content),
+            Dialect::Bulloak => content,
+        }
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Outcome of a generation run
+#[derive(Debug, Default)]
+struct GenerateReport {
+    /// Number of tree files written
+    trees: usize,
+    /// Everything that was skipped or only partially analyzed along the way
+    warnings: Vec<Warning>,
+    /// With `--check`, files that are missing or differ from the generated content
+    stale: Vec<PathBuf>,
+    /// Branch point counts of every function analyzed, for the summary
+    functions: Vec<FunctionStats>,
+    /// Source files that couldn't be parsed and were skipped
+    parse_failures: Vec<ParseFailure>,
+    /// With `--dry-run`, files that would be written with their number of branch points
+    planned: Vec<(PathBuf, usize)>,
+    /// Tree files on disk after the run, with the function (or overload) each holds
+    written: Vec<(String, PathBuf)>,
+    /// Trees that couldn't be built or written
+    failures: Vec<TreeFailure>,
+}
 
-    #[test]
-    fn test_to_snake_case() {
-        assert_eq!(to_snake_case("Vault"), "vault");
-        assert_eq!(to_snake_case("VaultABC"), "vault_abc");
-        assert_eq!(to_snake_case("HiHello"), "hi_hello");
-        assert_eq!(to_snake_case("myFunction"), "my_function"); // Note: standard conversion, but contract names are usually CapWords
-        assert_eq!(to_snake_case("ABC"), "abc");
-        assert_eq!(to_snake_case("A"), "a");
+impl GenerateReport {
+    fn merge(&mut self, other: GenerateReport) {
+        self.trees += other.trees;
+        self.warnings.extend(other.warnings);
+        self.stale.extend(other.stale);
+        self.functions.extend(other.functions);
+        self.parse_failures.extend(other.parse_failures);
+        self.planned.extend(other.planned);
+        self.written.extend(other.written);
+        self.failures.extend(other.failures);
+    }
+}
+
+/// A source file skipped because it failed to parse
+#[derive(Debug)]
+struct ParseFailure {
+    file: PathBuf,
+    error: ParserError,
+}
+
+impl std::fmt::Display for ParseFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.file.display(), self.error)
+    }
+}
+
+/// A tree file that couldn't be built or written. The other trees of its contract
+/// are generated regardless
+#[derive(Debug)]
+struct TreeFailure {
+    path: PathBuf,
+    error: String,
+}
+
+impl std::fmt::Display for TreeFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.error)
+    }
+}
+
+/// List the trees that failed and fail the run if there are any, once every other
+/// tree has been written
+fn check_tree_failures(report: &GenerateReport) -> Result<(), String> {
+    if report.failures.is_empty() {
+        return Ok(());
+    }
+
+    eprintln!("Trees that failed to generate:");
+    for failure in &report.failures {
+        eprintln!("  {}", failure);
+    }
+    Err(format!(
+        "{} trees failed to generate",
+        report.failures.len()
+    ))
+}
+
+/// With `--strict`, list the files that failed to parse and fail the run if there are any
+fn check_parse_failures(report: &GenerateReport, strict: bool) -> Result<(), String> {
+    if !strict || report.parse_failures.is_empty() {
+        return Ok(());
+    }
+
+    eprintln!("Files that failed to parse:");
+    for failure in &report.parse_failures {
+        eprintln!("  {}", failure);
+    }
+    Err(format!(
+        "{} files failed to parse",
+        report.parse_failures.len()
+    ))
+}
+
+/// Branch point counts of a contract's functions
+fn function_stats<'a>(
+    contract_name: &str,
+    contexts: impl IntoIterator<Item = &'a FunctionContext>,
+) -> Vec<FunctionStats> {
+    contexts
+        .into_iter()
+        .map(|ctx| FunctionStats {
+            contract: contract_name.to_string(),
+            function: function_key(ctx),
+            branch_points: ctx.branch_points.len(),
+            unguarded: is_unguarded(ctx),
+        })
+        .collect()
+}
+
+/// Whether a function's tree is a lone leaf, without a single branch
+fn is_unguarded(ctx: &FunctionContext) -> bool {
+    TreeBuilder::build(&ctx.function_name, ctx.branch_points.clone())
+        .is_ok_and(|tree| !tree.has_branches())
+}
+
+/// Leaf wording from the `--*-label` flags, else `[labels]` in .acacia.toml, else the
+/// defaults
+fn label_config(
+    config: LeafLabels,
+    revert: Option<String>,
+    success: Option<String>,
+    early_return: Option<String>,
+) -> LabelConfig {
+    let defaults = LabelConfig::default();
+    LabelConfig {
+        revert: revert.or(config.revert).unwrap_or(defaults.revert),
+        success: success.or(config.success).unwrap_or(defaults.success),
+        early_return: early_return
+            .or(config.early_return)
+            .unwrap_or(defaults.early_return),
+    }
+}
+
+/// Directories to skip from `ignore_dirs` and `gitignore` in .acacia.toml, else the defaults
+fn source_filter(config: &AcaciaConfig) -> SourceFilter {
+    let defaults = SourceFilter::default();
+    SourceFilter {
+        ignored_dirs: config.ignore_dirs.clone().unwrap_or(defaults.ignored_dirs),
+        gitignore: config.gitignore.unwrap_or(defaults.gitignore),
+    }
+}
+
+/// Compile `--exclude` patterns into one matcher
+fn build_excludes(patterns: &[String]) -> Result<GlobSet, globset::Error> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern)?);
+    }
+    builder.build()
+}
+
+/// The Foundry project around the current directory, or with `--no-foundry` the current
+/// directory as a plain project
+fn discover_project(
+    no_foundry: bool,
+    profile: Option<&str>,
+) -> Result<FoundryProject, FoundryError> {
+    if no_foundry {
+        return Ok(FoundryProject::plain(std::env::current_dir()?));
+    }
+    FoundryProject::discover(profile)
+}
+
+/// Directory containing a `--single-file` path, used as the root when there's no project
+fn single_file_root(path: &Path) -> Result<PathBuf, std::io::Error> {
+    match path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        Some(dir) => Ok(dir.to_path_buf()),
+        None => std::env::current_dir(),
+    }
+}
+
+/// Output directory from `--output`, else `output_dir` from .acacia.toml (relative to the
+/// project root), else `test/trees`
+fn output_dir(project: &FoundryProject, config: &AcaciaConfig, output: Option<String>) -> String {
+    match (output, &config.output_dir) {
+        (Some(output), _) => output,
+        (None, Some(dir)) => project.root().join(dir).display().to_string(),
+        (None, None) => "test/trees".to_string(),
+    }
+}
+
+/// What `write_output` did with a generated file
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum WriteOutcome {
+    /// Written to disk, or to stdout with `--stdout`
+    Written,
+    /// The file on disk already has the generated content
+    Unchanged,
+    /// With `--dry-run`, nothing is written
+    Planned,
+    /// With `--check`, the file on disk is missing or differs from the generated content
+    Stale,
+}
+
+/// Write a generated file, or with `--check` compare it against the file on disk. A
+/// file that already has the generated content is left alone
+fn write_output(
+    path: &Path,
+    content: &str,
+    options: &GenerateOptions,
+) -> Result<WriteOutcome, std::io::Error> {
+    let merged = options
+        .merge
+        .then(|| merge_existing(path, content, options))
+        .flatten();
+    let content = merged.as_deref().unwrap_or(content);
+    let unchanged = || fs::read_to_string(path).is_ok_and(|existing| existing == content);
+
+    if options.check {
+        return Ok(if unchanged() {
+            WriteOutcome::Unchanged
+        } else {
+            WriteOutcome::Stale
+        });
+    }
+
+    if options.dry_run {
+        return Ok(WriteOutcome::Planned);
+    }
+
+    if options.stdout {
+        // Colors only make sense for the box-drawing formats
+        let color =
+            matches!(options.format, OutputFormat::Tree | OutputFormat::Annotated) && use_color();
+        println!("{}", colorize(content, color, &options.tree.labels));
+        return Ok(WriteOutcome::Written);
+    }
+
+    let outcome = if unchanged() {
+        WriteOutcome::Unchanged
+    } else {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = fs::File::create(path)?;
+        file.write_all(content.as_bytes())?;
+        WriteOutcome::Written
+    };
+
+    if options.scaffold {
+        write_scaffold(path, content, options)?;
+    }
+
+    Ok(outcome)
+}
+
+/// Write the `.t.sol` scaffold of the trees in `content` next to their file. An existing
+/// scaffold is left alone, since it's meant to be filled in by hand
+fn write_scaffold(
+    tree_path: &Path,
+    content: &str,
+    options: &GenerateOptions,
+) -> Result<(), std::io::Error> {
+    let path = tree_path.with_extension("t.sol");
+    if path.exists() || !matches!(options.format, OutputFormat::Tree | OutputFormat::Annotated) {
+        return Ok(());
+    }
+
+    match parse_trees(content) {
+        Ok(trees) => fs::write(path, render_scaffold(&trees, &options.tree.labels)),
+        Err(_) => Ok(()),
+    }
+}
+
+/// `content` with the leaves of the trees already at `path` carried over, or `None` when
+/// there is nothing to merge: no existing file, a format other than `tree`/`annotated`,
+/// or a file that doesn't read back as trees. Each tree of `content`, such as one
+/// overload of a combined file, is merged with the existing tree of the same name
+fn merge_existing(path: &Path, content: &str, options: &GenerateOptions) -> Option<String> {
+    let sources = match options.format {
+        OutputFormat::Tree => false,
+        OutputFormat::Annotated => true,
+        _ => return None,
+    };
+
+    let existing = parse_trees(&fs::read_to_string(path).ok()?).ok()?;
+    let mut sections = Vec::new();
+    for section in content.split("\n\n") {
+        // Comment lines above a tree (its overload header and `@custom:btt` hints) stay
+        // as generated
+        let header_len: usize = section
+            .lines()
+            .take_while(|line| line.starts_with(options.format.comment()))
+            .map(|line| line.len() + 1)
+            .sum();
+        let (header, text) = section.split_at(header_len.min(section.len()));
+
+        let mut tree = parse_tree(text).ok()?;
+        let previous = existing.iter().find(|previous| match (previous, &tree) {
+            (TreeNode::Root { name, .. }, TreeNode::Root { name: current, .. }) => name == current,
+            _ => false,
+        });
+        if let Some(previous) = previous {
+            tree.keep_leaves_from(previous);
+        }
+        sections.push(format!("{}{}", header, options.render_tree(&tree, sources)));
+    }
+    Some(sections.join("\n"))
+}
+
+/// Warnings collected while parsing the given functions
+fn collect_warnings<'a>(contexts: impl IntoIterator<Item = &'a FunctionContext>) -> Vec<Warning> {
+    contexts
+        .into_iter()
+        .flat_map(|ctx| ctx.warnings.iter().cloned())
+        .collect()
+}
+
+/// Parsed target with optional contract and function names
+enum ParsedTarget {
+    /// No target - generate for all contracts in project
+    AllContracts,
+    /// Contract only - generate for all functions in this contract
+    Contract {
+        contract_name: String,
+        /// File given as `path:Contract`, relative to the project root
+        file: Option<PathBuf>,
+    },
+    /// Specific function
+    Function {
+        contract_name: String,
+        file: Option<PathBuf>,
+        function_name: String,
+        signature: Option<String>,
+    },
+    /// Path to a .sol file - generate for every contract it defines
+    File { path: PathBuf },
+}
+
+/// Read a targets file: one target per line, ignoring blank lines and `#` comments
+fn read_targets_file(path: &Path) -> Result<Vec<String>, std::io::Error> {
+    let content = fs::read_to_string(path)?;
+
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Generate trees for every target listed in a file
+fn generate_from_targets_file(
+    project: &FoundryProject,
+    targets_path: &Path,
+    output_dir: &str,
+    options: &GenerateOptions,
+) -> Result<GenerateReport, Box<dyn std::error::Error>> {
+    let targets = read_targets_file(targets_path)?;
+    options.progress(format_args!(
+        "Processing {} targets from {:?}",
+        targets.len(),
+        targets_path
+    ));
+
+    let mut report = GenerateReport::default();
+    for target in &targets {
+        report.merge(generate_tree(project, target, output_dir, options)?);
+    }
+
+    options.progress(format_args!(
+        "Processed {} targets, generated {} trees total",
+        targets.len(),
+        report.trees
+    ));
+
+    Ok(report)
+}
+
+/// Generate tree files for a target
+fn generate_tree(
+    project: &FoundryProject,
+    target: &str,
+    output_dir: &str,
+    options: &GenerateOptions,
+) -> Result<GenerateReport, Box<dyn std::error::Error>> {
+    let parsed = parse_target(target);
+
+    let parser = options.parser(project);
+
+    let report = match parsed {
+        // Generate trees for every contract in one file, without looking it up
+        ParsedTarget::File { path } => {
+            if !path.is_file() {
+                return Err(format!("No such Solidity file: {}", path.display()).into());
+            }
+            options.progress(format_args!(
+                "Generating BTT trees for all contracts in {:?}",
+                path
+            ));
+
+            let report = generate_file(project, &parser, &path, output_dir, options)?;
+
+            options.progress(format_args!(
+                "Generated {} trees for {:?}",
+                report.trees, path
+            ));
+            report
+        }
+
+        // Generate trees for ALL contracts in the project
+        ParsedTarget::AllContracts => {
+            options.progress(format_args!(
+                "Generating BTT trees for all contracts in project"
+            ));
+
+            let contract_files: Vec<PathBuf> = project
+                .find_contracts(&options.sources)
+                .into_iter()
+                .filter(|path| !options.excludes(project, path))
+                .collect();
+            if contract_files.is_empty() {
+                options.progress(format_args!("No Solidity files found in src directory"));
+                return Ok(GenerateReport::default());
+            }
+
+            options.progress(format_args!(
+                "Found {} Solidity files",
+                contract_files.len()
+            ));
+
+            // Parse every contract up front so a function overridden in a derived
+            // contract is only generated under the most-derived one
+            let mut contracts = Vec::new();
+            // Functions declared outside any contract, as (label, file, functions)
+            let mut free_functions = Vec::new();
+            let mut warnings = Vec::new();
+            let mut parse_failures = Vec::new();
+            for file_path in contract_files {
+                let bases = match parser.get_contract_bases(&file_path) {
+                    Ok(c) => c,
+                    Err(e) => {
+                        warnings.push(Warning {
+                            kind: WarningKind::ParseSkipped {
+                                reason: e.to_string(),
+                            },
+                            file: file_path.clone(),
+                            line: None,
+                        });
+                        parse_failures.push(ParseFailure {
+                            file: file_path,
+                            error: e,
+                        });
+                        continue;
+                    }
+                };
+
+                for (name, parents) in bases {
+                    let functions = options.functions(&parser, &file_path, &name)?;
+                    warnings.extend(collect_warnings(&functions));
+                    contracts.push(ProjectContract {
+                        name,
+                        file: file_path.clone(),
+                        parents,
+                        functions,
+                    });
+                }
+
+                let functions = parser.parse_free_functions(&file_path)?;
+                if !functions.is_empty() {
+                    free_functions.push((free_functions_label(&file_path), file_path, functions));
+                }
+            }
+
+            let overridden = overridden_functions(&contracts);
+            let duplicated = duplicated_names(&contracts);
+            let mut report = GenerateReport {
+                warnings,
+                parse_failures,
+                ..GenerateReport::default()
+            };
+            let mut index = Vec::new();
+
+            for contract in &contracts {
+                let skip = overridden.get(&contract.name);
+                let mut functions = Vec::new();
+                for ctx in &contract.functions {
+                    if skip.is_some_and(|skip| skip.contains(&function_key(ctx))) {
+                        options.progress(format_args!(
+                            "  -- {}::{} (overridden in a derived contract)",
+                            contract.name,
+                            function_key(ctx)
+                        ));
+                    } else {
+                        functions.push(ctx);
+                    }
+                }
+
+                // Same-named contracts from different files get a directory per file
+                // instead of overwriting each other's trees. The mirror layout already
+                // keeps them apart
+                let contract_dir = if duplicated.contains(contract.name.as_str())
+                    && options.layout == Layout::Flat
+                {
+                    let dir = Path::new(output_dir).join(file_segment(project, &contract.file));
+                    options.progress(format_args!(
+                        "  {} is defined in several files; trees from {:?} go to {:?}",
+                        contract.name, contract.file, dir
+                    ));
+                    dir.join(to_snake_case(&contract.name))
+                } else {
+                    options.contract_dir(project, output_dir, &contract.file, &contract.name)
+                };
+                report
+                    .functions
+                    .extend(function_stats(&contract.name, functions.iter().copied()));
+
+                let contract_report = write_contract_trees(&contract_dir, &functions, options)?;
+                if !contract_report.written.is_empty() {
+                    index.push((contract.name.as_str(), contract_report.written.clone()));
+                }
+                report.merge(contract_report);
+            }
+
+            for (label, file, functions) in &free_functions {
+                let free_report =
+                    process_free_functions(project, file, functions, output_dir, options)?;
+                if !free_report.written.is_empty() {
+                    index.push((label.as_str(), free_report.written.clone()));
+                }
+                report.merge(free_report);
+            }
+
+            if options.index {
+                let path = Path::new(output_dir).join(INDEX_FILE);
+                fs::create_dir_all(output_dir)?;
+                fs::write(&path, format_index(Path::new(output_dir), &index))?;
+                options.progress(format_args!("  -> {:?}", path));
+            }
+
+            options.progress(format_args!("Generated {} trees total", report.trees));
+            report
+        }
+
+        // Generate trees for a specific contract
+        ParsedTarget::Contract {
+            contract_name,
+            file,
+        } => {
+            let contract_path =
+                project.locate_contract(file.as_deref(), &contract_name, &options.sources)?;
+            options.progress(format_args!("Found contract at: {:?}", contract_path));
+
+            options.progress(format_args!(
+                "Generating BTT trees for all public/external functions in {}",
+                contract_name
+            ));
+
+            let report = process_contract(
+                project,
+                &parser,
+                &contract_path,
+                &contract_name,
+                output_dir,
+                options,
+            )?;
+
+            options.progress(format_args!(
+                "Generated {} trees for {}",
+                report.trees, contract_name
+            ));
+            report
+        }
+
+        // Generate tree(s) for a specific function
+        ParsedTarget::Function {
+            contract_name,
+            file,
+            function_name,
+            signature,
+        } => {
+            let contract_path =
+                project.locate_contract(file.as_deref(), &contract_name, &options.sources)?;
+            options.progress(format_args!("Found contract at: {:?}", contract_path));
+
+            let contract_output_dir =
+                options.contract_dir(project, output_dir, &contract_path, &contract_name);
+
+            // Overloads split into one file each are written like a contract's functions
+            if options.split_overloads && signature.is_none() {
+                let function_contexts =
+                    parser.parse_all_functions(&contract_path, &contract_name, &function_name)?;
+                let contexts: Vec<&FunctionContext> = function_contexts.iter().collect();
+
+                return Ok(GenerateReport {
+                    warnings: collect_warnings(&function_contexts),
+                    functions: function_stats(&contract_name, &function_contexts),
+                    ..write_contract_trees(&contract_output_dir, &contexts, options)?
+                });
+            }
+
+            let output_path = contract_output_dir.join(format!(
+                "{}.{}",
+                function_name,
+                options.format.extension()
+            ));
+            if options.only_missing && output_path.exists() {
+                options.progress(format_args!("Skipping {:?} (already exists)", output_path));
+                return Ok(GenerateReport::default());
+            }
+
+            let (content, warnings, functions) = match signature {
+                Some(sig) => {
+                    // Specific signature provided
+                    options.progress(format_args!(
+                        "Generating BTT tree for {}::{}({})",
+                        contract_name, function_name, sig
+                    ));
+
+                    let function_ctx = parser.parse_function_by_signature(
+                        &contract_path,
+                        &contract_name,
+                        &function_name,
+                        &sig,
+                    )?;
+
+                    options.progress(format_args!(
+                        "Found {} branch points",
+                        function_ctx.branch_points.len()
+                    ));
+
+                    let functions = function_stats(&contract_name, [&function_ctx]);
+                    let warnings = function_ctx.warnings;
+                    let content = options.with_hints(
+                        &function_ctx.hints,
+                        options.render(&function_name, function_ctx.branch_points)?,
+                    );
+
+                    // Note: We append only if it exists? Or wait, user wants overloads in same file.
+                    // But here we are targeting a specific signature.
+                    // If the user specifies a signature, they probably want just that tree.
+                    // However, to be consistent with the file naming convention "FunctionName.tree",
+                    // if we modify that file, we should probably append to it if it exists, or overwrite?
+                    // Given the user said "in case of function overloading ... keep file name the same as function name and add all trees in the same file",
+                    // implies if we run this command multiple times for different signatures, they might want them merged.
+                    // But for simplicity, if I run for a specific signature, I will write just that tree to "FunctionName.tree".
+                    // If they want all, they should run without signature.
+
+                    (content, warnings, functions)
+                }
+                None => {
+                    // No signature - generate for all overloads of this function
+                    let function_contexts = parser.parse_all_functions(
+                        &contract_path,
+                        &contract_name,
+                        &function_name,
+                    )?;
+
+                    let num_overloads = function_contexts.len();
+                    options.progress(format_args!(
+                        "Found {} overloads for {}::{}",
+                        num_overloads, contract_name, function_name
+                    ));
+
+                    let contexts: Vec<&FunctionContext> = function_contexts.iter().collect();
+                    let combined_content = render_overloads(&function_name, &contexts, options)?;
+
+                    (
+                        combined_content,
+                        collect_warnings(&function_contexts),
+                        function_stats(&contract_name, &function_contexts),
+                    )
+                }
+            };
+
+            // Only a tree that was actually written counts
+            let outcome = write_output(&output_path, &content, options)?;
+            let mut report = GenerateReport {
+                trees: usize::from(outcome == WriteOutcome::Written),
+                warnings,
+                ..GenerateReport::default()
+            };
+            match outcome {
+                WriteOutcome::Stale => report.stale.push(output_path),
+                WriteOutcome::Planned => {
+                    let branch_points = functions.iter().map(|f| f.branch_points).sum();
+                    report.planned.push((output_path, branch_points));
+                }
+                _ if !options.check && !options.stdout => {
+                    println!("Generated tree at: {:?}", output_path);
+                }
+                _ => {}
+            }
+            report.functions = functions;
+            report
+        }
+    };
+
+    Ok(report)
+}
+
+/// Generate trees for every contract declared in `file`
+fn generate_file(
+    project: &FoundryProject,
+    parser: &SolarParser,
+    file: &Path,
+    output_dir: &str,
+    options: &GenerateOptions,
+) -> Result<GenerateReport, Box<dyn std::error::Error>> {
+    let mut report = GenerateReport::default();
+    for (name, _) in parser.get_contract_bases(file)? {
+        report.merge(process_contract(
+            project, parser, file, &name, output_dir, options,
+        )?);
+    }
+    let free_functions = parser.parse_free_functions(file)?;
+    report.merge(process_free_functions(
+        project,
+        file,
+        &free_functions,
+        output_dir,
+        options,
+    )?);
+    Ok(report)
+}
+
+/// Directory under the output root for the trees of functions declared outside any
+/// contract, with a subdirectory per source file
+const FREE_FUNCTIONS_DIR: &str = "_free";
+
+/// Write the trees of the free functions declared in `file`
+fn process_free_functions(
+    project: &FoundryProject,
+    file: &Path,
+    function_contexts: &[FunctionContext],
+    output_dir: &str,
+    options: &GenerateOptions,
+) -> Result<GenerateReport, Box<dyn std::error::Error>> {
+    let functions: Vec<&FunctionContext> = function_contexts.iter().collect();
+    let dir = Path::new(output_dir)
+        .join(FREE_FUNCTIONS_DIR)
+        .join(file_segment(project, file));
+
+    Ok(GenerateReport {
+        warnings: collect_warnings(function_contexts),
+        functions: function_stats(&free_functions_label(file), function_contexts),
+        ..write_contract_trees(&dir, &functions, options)?
+    })
+}
+
+/// Name standing in for the contract of the free functions declared in `file`, e.g.
+/// in the summary and the index: the file name
+fn free_functions_label(file: &Path) -> String {
+    file.file_name().map_or_else(
+        || file.display().to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    )
+}
+
+fn process_contract(
+    project: &FoundryProject,
+    parser: &SolarParser,
+    file_path: &Path,
+    contract_name: &str,
+    output_dir: &str,
+    options: &GenerateOptions,
+) -> Result<GenerateReport, Box<dyn std::error::Error>> {
+    let function_contexts = options.functions(parser, file_path, contract_name)?;
+    let functions: Vec<&FunctionContext> = function_contexts.iter().collect();
+
+    Ok(GenerateReport {
+        warnings: collect_warnings(&function_contexts),
+        functions: function_stats(contract_name, &function_contexts),
+        ..write_contract_trees(
+            &options.contract_dir(project, output_dir, file_path, contract_name),
+            &functions,
+            options,
+        )?
+    })
+}
+
+/// How often `--watch` checks the source files for changes
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How long the sources must stay unchanged before `--watch` regenerates, so an
+/// editor writing several files at once triggers a single cycle
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Modification times of a project's Solidity sources
+#[derive(Debug, Default, PartialEq)]
+struct SourceSnapshot(HashMap<PathBuf, SystemTime>);
+
+impl SourceSnapshot {
+    fn take(project: &FoundryProject, filter: &SourceFilter) -> Self {
+        Self(
+            project
+                .find_contracts(filter)
+                .into_iter()
+                .filter_map(|path| {
+                    let modified = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+                    Some((path, modified))
+                })
+                .collect(),
+        )
+    }
+
+    /// Files that are new or modified in `newer`
+    fn changed_in(&self, newer: &SourceSnapshot) -> Vec<PathBuf> {
+        let mut changed: Vec<PathBuf> = newer
+            .0
+            .iter()
+            .filter(|(path, modified)| self.0.get(*path) != Some(*modified))
+            .map(|(path, _)| path.clone())
+            .collect();
+        changed.sort();
+        changed
+    }
+}
+
+/// Files changed since `snapshot`, once they have stopped changing. Updates `snapshot`
+fn poll_changes(
+    project: &FoundryProject,
+    filter: &SourceFilter,
+    snapshot: &mut SourceSnapshot,
+) -> Vec<PathBuf> {
+    let mut current = SourceSnapshot::take(project, filter);
+    let mut changed = snapshot.changed_in(&current);
+    if changed.is_empty() {
+        return changed;
+    }
+
+    loop {
+        std::thread::sleep(WATCH_DEBOUNCE);
+        let settled = SourceSnapshot::take(project, filter);
+        let more = current.changed_in(&settled);
+        current = settled;
+        if more.is_empty() {
+            break;
+        }
+        changed.extend(more);
+    }
+
+    changed.sort();
+    changed.dedup();
+    *snapshot = current;
+    changed
+}
+
+/// Whether two paths name the same file, however they are spelled
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Regenerate the trees of the contracts declared in `file`, limited to the contract
+/// named by `target` when there is one
+fn regenerate_file(
+    project: &FoundryProject,
+    file: &Path,
+    target: &str,
+    output_dir: &str,
+    options: &GenerateOptions,
+) -> Result<GenerateReport, Box<dyn std::error::Error>> {
+    let parser = options.parser(project);
+
+    match parse_target(target) {
+        ParsedTarget::AllContracts if options.excludes(project, file) => {
+            Ok(GenerateReport::default())
+        }
+        ParsedTarget::AllContracts => generate_file(project, &parser, file, output_dir, options),
+        ParsedTarget::File { path } => {
+            if same_file(&path, file) {
+                generate_file(project, &parser, file, output_dir, options)
+            } else {
+                Ok(GenerateReport::default())
+            }
+        }
+        ParsedTarget::Contract {
+            contract_name,
+            file: source,
+        }
+        | ParsedTarget::Function {
+            contract_name,
+            file: source,
+            ..
+        } => {
+            let declared_in =
+                project.locate_contract(source.as_deref(), &contract_name, &options.sources)?;
+            if same_file(&declared_in, file) {
+                generate_tree(project, target, output_dir, options)
+            } else {
+                Ok(GenerateReport::default())
+            }
+        }
+    }
+}
+
+/// Regenerate trees as source files change, until the process is interrupted
+fn watch_sources(
+    project: &FoundryProject,
+    target: &str,
+    output_dir: &str,
+    options: &GenerateOptions,
+) {
+    println!(
+        "Watching {} for changes (Ctrl-C to stop)",
+        project.src_dir().display()
+    );
+
+    let mut snapshot = SourceSnapshot::take(project, &options.sources);
+    loop {
+        std::thread::sleep(WATCH_INTERVAL);
+
+        for file in poll_changes(project, &options.sources, &mut snapshot) {
+            // A file that doesn't parse mid-edit shouldn't stop the watcher
+            match regenerate_file(project, &file, target, output_dir, options) {
+                Ok(report) if report.trees > 0 => {
+                    println!("Regenerated {} ({} trees)", file.display(), report.trees);
+                    for warning in &report.warnings {
+                        eprintln!("Warning: {}", warning);
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("Error regenerating {}: {}", file.display(), e),
+            }
+        }
+    }
+}
+
+/// A contract found during whole-project generation
+struct ProjectContract {
+    name: String,
+    /// File the contract is defined in
+    file: PathBuf,
+    /// Names of the direct base contracts
+    parents: Vec<String>,
+    functions: Vec<FunctionContext>,
+}
+
+/// Contract names defined in more than one file
+fn duplicated_names(contracts: &[ProjectContract]) -> HashSet<&str> {
+    let mut files: HashMap<&str, HashSet<&Path>> = HashMap::new();
+    for contract in contracts {
+        files
+            .entry(contract.name.as_str())
+            .or_default()
+            .insert(contract.file.as_path());
+    }
+
+    files
+        .into_iter()
+        .filter(|(_, files)| files.len() > 1)
+        .map(|(name, _)| name)
+        .collect()
+}
+
+/// Directory name derived from a source file's path under `src`, e.g.
+/// `src/v2/Vault.sol` becomes `v2_vault`
+fn file_segment(project: &FoundryProject, file: &Path) -> String {
+    let relative = file.strip_prefix(project.src_dir()).unwrap_or(file);
+    relative
+        .with_extension("")
+        .components()
+        .filter_map(|component| match component {
+            std::path::Component::Normal(part) => part.to_str().map(to_snake_case),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// Key identifying a function overload, e.g. `withdraw(uint256)`
+fn function_key(ctx: &FunctionContext) -> String {
+    format!("{}({})", ctx.function_name, ctx.signature)
+}
+
+/// File name (without extension) of an overload written to its own file:
+/// `transfer(address,uint256)` becomes `transfer_address_uint256`
+fn overload_file_stem(ctx: &FunctionContext) -> String {
+    let words: Vec<String> = std::iter::once(ctx.function_name.clone())
+        .chain(ctx.signature.split(',').map(|ty| {
+            ty.replace("[]", "_array")
+                .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .filter(|word| !word.is_empty())
+                .collect::<Vec<_>>()
+                .join("_")
+        }))
+        .filter(|word| !word.is_empty())
+        .collect();
+    words.join("_")
+}
+
+/// For each contract, the functions it defines that some derived contract overrides
+fn overridden_functions(contracts: &[ProjectContract]) -> HashMap<String, HashSet<String>> {
+    let parents: HashMap<&str, &[String]> = contracts
+        .iter()
+        .map(|c| (c.name.as_str(), c.parents.as_slice()))
+        .collect();
+
+    let mut overridden: HashMap<String, HashSet<String>> = HashMap::new();
+
+    for contract in contracts {
+        // Walk all ancestors, guarding against inheritance cycles
+        let mut ancestors = HashSet::new();
+        let mut pending: Vec<&str> = contract.parents.iter().map(String::as_str).collect();
+        while let Some(ancestor) = pending.pop() {
+            if ancestor == contract.name || !ancestors.insert(ancestor) {
+                continue;
+            }
+            if let Some(grandparents) = parents.get(ancestor) {
+                pending.extend(grandparents.iter().map(String::as_str));
+            }
+        }
+
+        for ancestor in ancestors {
+            overridden
+                .entry(ancestor.to_string())
+                .or_default()
+                .extend(contract.functions.iter().map(function_key));
+        }
+    }
+
+    overridden
+}
+
+/// Write one tree file per function name (overloads combined)
+fn write_contract_trees(
+    contract_output_dir: &Path,
+    function_contexts: &[&FunctionContext],
+    options: &GenerateOptions,
+) -> Result<GenerateReport, Box<dyn std::error::Error>> {
+    let mut report = GenerateReport::default();
+    if function_contexts.is_empty() {
+        return Ok(report);
+    }
+
+    // Group functions by name
+    let mut func_groups: HashMap<String, Vec<&FunctionContext>> = HashMap::new();
+    for ctx in function_contexts.iter().copied() {
+        func_groups
+            .entry(ctx.function_name.clone())
+            .or_default()
+            .push(ctx);
+    }
+
+    for (func_name, contexts) in func_groups {
+        if options.only_reverting && contexts.iter().all(|ctx| is_unguarded(ctx)) {
+            options.progress(format_args!("  -- {} (no guards)", func_name));
+            continue;
+        }
+
+        // Each file as (file stem, root name, overloads it holds)
+        let files: Vec<(String, String, Vec<&FunctionContext>)> =
+            if options.split_overloads && contexts.len() > 1 {
+                contexts
+                    .iter()
+                    .map(|ctx| (overload_file_stem(ctx), function_key(ctx), vec![*ctx]))
+                    .collect()
+            } else {
+                vec![(func_name.clone(), func_name, contexts)]
+            };
+
+        for (stem, root_name, contexts) in files {
+            let output_path =
+                contract_output_dir.join(format!("{}.{}", stem, options.format.extension()));
+            if options.only_missing && output_path.exists() {
+                options.progress(format_args!("  -- {:?} (already exists)", output_path));
+                report.written.push((root_name, output_path));
+                continue;
+            }
+
+            // Overloads keep the order from the parser (order of definition). A tree
+            // that fails is reported without losing the other functions' trees
+            let outcome = render_overloads(&root_name, &contexts, options)
+                .and_then(|content| Ok(write_output(&output_path, &content, options)?));
+            let outcome = match outcome {
+                Ok(outcome) => outcome,
+                Err(e) => {
+                    report.failures.push(TreeFailure {
+                        path: output_path,
+                        error: e.to_string(),
+                    });
+                    continue;
+                }
+            };
+
+            match outcome {
+                WriteOutcome::Stale => report.stale.push(output_path),
+                WriteOutcome::Planned => {
+                    let branch_points = contexts.iter().map(|ctx| ctx.branch_points.len()).sum();
+                    report.planned.push((output_path, branch_points));
+                }
+                _ if !options.check && !options.stdout => {
+                    println!("  -> {:?}", output_path);
+                    report.written.push((root_name, output_path));
+                }
+                _ => {}
+            }
+            report.trees += 1;
+        }
+    }
+
+    Ok(report)
+}
+
+/// A contract's public/external functions, for the `list` subcommand
+struct ContractListing {
+    name: String,
+    path: PathBuf,
+    /// Function signatures (e.g. `withdraw(uint256)`) with their number of branch points
+    functions: Vec<(String, usize)>,
+}
+
+impl ContractListing {
+    fn new(name: String, path: &Path, functions: &[FunctionContext]) -> Self {
+        Self {
+            name,
+            path: path.to_path_buf(),
+            functions: functions
+                .iter()
+                .map(|ctx| (function_key(ctx), ctx.branch_points.len()))
+                .collect(),
+        }
+    }
+}
+
+/// Collect the functions of a target, grouped by contract. Files are visited in path
+/// order and contracts and functions in definition order
+fn list_target(
+    project: &FoundryProject,
+    target: &str,
+) -> Result<Vec<ContractListing>, Box<dyn std::error::Error>> {
+    let parser = SolarParser::new(project);
+
+    match parse_target(target) {
+        ParsedTarget::AllContracts => {
+            let mut contract_files = project.find_all_contracts();
+            contract_files.sort();
+
+            let mut listings = Vec::new();
+            for file_path in contract_files {
+                for (name, _) in parser.get_contract_bases(&file_path)? {
+                    let functions = parser.parse_all_public_functions(&file_path, &name)?;
+                    listings.push(ContractListing::new(name, &file_path, &functions));
+                }
+            }
+            Ok(listings)
+        }
+        ParsedTarget::File { path } => {
+            let mut listings = Vec::new();
+            for (name, _) in parser.get_contract_bases(&path)? {
+                let functions = parser.parse_all_public_functions(&path, &name)?;
+                listings.push(ContractListing::new(name, &path, &functions));
+            }
+            Ok(listings)
+        }
+        ParsedTarget::Contract {
+            contract_name,
+            file,
+        } => {
+            let contract_path = project.locate_contract(
+                file.as_deref(),
+                &contract_name,
+                &SourceFilter::default(),
+            )?;
+            let functions = parser.parse_all_public_functions(&contract_path, &contract_name)?;
+            Ok(vec![ContractListing::new(
+                contract_name,
+                &contract_path,
+                &functions,
+            )])
+        }
+        ParsedTarget::Function {
+            contract_name,
+            file,
+            function_name,
+            ..
+        } => {
+            let contract_path = project.locate_contract(
+                file.as_deref(),
+                &contract_name,
+                &SourceFilter::default(),
+            )?;
+            let functions =
+                parser.parse_all_functions(&contract_path, &contract_name, &function_name)?;
+            Ok(vec![ContractListing::new(
+                contract_name,
+                &contract_path,
+                &functions,
+            )])
+        }
+    }
+}
+
+/// List the files a `--dry-run` would write, in path order
+fn format_planned(planned: &[(PathBuf, usize)]) -> String {
+    let mut planned: Vec<&(PathBuf, usize)> = planned.iter().collect();
+    planned.sort();
+
+    let mut output = format!("Would write {} files:\n", planned.len());
+    for (path, count) in planned {
+        let noun = if *count == 1 {
+            "branch point"
+        } else {
+            "branch points"
+        };
+        output.push_str(&format!("  {} ({} {})\n", path.display(), count, noun));
+    }
+    output
+}
+
+/// Name of the `--index` file at the output root
+const INDEX_FILE: &str = "index.md";
+
+/// Markdown index of tree files, one section per contract and one link per file,
+/// relative to the output root. Both are sorted by name
+fn format_index(output_dir: &Path, contracts: &[(&str, Vec<(String, PathBuf)>)]) -> String {
+    let mut contracts: Vec<&(&str, Vec<(String, PathBuf)>)> = contracts.iter().collect();
+    contracts.sort_by_key(|(name, _)| *name);
+
+    let mut output = String::from("# Branching trees\n");
+    for (name, files) in contracts {
+        let mut files: Vec<&(String, PathBuf)> = files.iter().collect();
+        files.sort();
+
+        output.push_str(&format!("\n## {}\n\n", name));
+        for (function, path) in files {
+            let link = path.strip_prefix(output_dir).unwrap_or(path);
+            output.push_str(&format!("- [{}]({})\n", function, link.display()));
+        }
+    }
+    output
+}
+
+/// Render listings as one block per contract with aligned branch point counts
+fn format_listings(listings: &[ContractListing]) -> String {
+    let mut output = String::new();
+
+    for listing in listings {
+        output.push_str(&format!("{} ({})\n", listing.name, listing.path.display()));
+        if listing.functions.is_empty() {
+            output.push_str("  (no public or external functions)\n");
+            continue;
+        }
+
+        let width = listing
+            .functions
+            .iter()
+            .map(|(signature, _)| signature.len())
+            .max()
+            .unwrap_or(0);
+        for (signature, count) in &listing.functions {
+            let noun = if *count == 1 {
+                "branch point"
+            } else {
+                "branch points"
+            };
+            output.push_str(&format!(
+                "  {:width$}  {} {}\n",
+                signature,
+                count,
+                noun,
+                width = width
+            ));
+        }
+    }
+
+    output
+}
+
+/// Match the leaves of a target's trees against the test functions of a test file
+fn check_target_coverage(
+    project: &FoundryProject,
+    target: &str,
+    tests_path: &Path,
+) -> Result<CoverageReport, Box<dyn std::error::Error>> {
+    let parser = SolarParser::new(project);
+
+    let function_contexts = match parse_target(target) {
+        ParsedTarget::AllContracts => {
+            return Err("coverage needs a contract or function target".into());
+        }
+        ParsedTarget::File { path } => {
+            let mut contexts = Vec::new();
+            for (name, _) in parser.get_contract_bases(&path)? {
+                contexts.extend(parser.parse_all_public_functions(&path, &name)?);
+            }
+            contexts
+        }
+        ParsedTarget::Contract {
+            contract_name,
+            file,
+        } => {
+            let contract_path = project.locate_contract(
+                file.as_deref(),
+                &contract_name,
+                &SourceFilter::default(),
+            )?;
+            parser.parse_all_public_functions(&contract_path, &contract_name)?
+        }
+        ParsedTarget::Function {
+            contract_name,
+            file,
+            function_name,
+            signature: Some(sig),
+        } => {
+            let contract_path = project.locate_contract(
+                file.as_deref(),
+                &contract_name,
+                &SourceFilter::default(),
+            )?;
+            vec![parser.parse_function_by_signature(
+                &contract_path,
+                &contract_name,
+                &function_name,
+                &sig,
+            )?]
+        }
+        ParsedTarget::Function {
+            contract_name,
+            file,
+            function_name,
+            signature: None,
+        } => {
+            let contract_path = project.locate_contract(
+                file.as_deref(),
+                &contract_name,
+                &SourceFilter::default(),
+            )?;
+            parser.parse_all_functions(&contract_path, &contract_name, &function_name)?
+        }
+    };
+
+    let mut trees = Vec::new();
+    for ctx in function_contexts {
+        trees.push(TreeBuilder::build(&ctx.function_name, ctx.branch_points)?);
+    }
+
+    let test_names = parser.get_function_names(tests_path)?;
+
+    Ok(check_coverage(&trees, &test_names))
+}
+
+/// Compare the trees of a contract or function against their files in `output_dir`.
+/// Returns the files with changes; a missing file counts as all branches added
+fn diff_target(
+    project: &FoundryProject,
+    target: &str,
+    output_dir: &str,
+    options: &GenerateOptions,
+) -> Result<Vec<(PathBuf, TreeDiff)>, Box<dyn std::error::Error>> {
+    let parser = options.parser(project);
+
+    let (contract_name, contract_path, function_contexts) = match parse_target(target) {
+        ParsedTarget::Contract {
+            contract_name,
+            file,
+        } => {
+            let contract_path =
+                project.locate_contract(file.as_deref(), &contract_name, &options.sources)?;
+            let functions = options.functions(&parser, &contract_path, &contract_name)?;
+            (contract_name, contract_path, functions)
+        }
+        // Overloads share a file, so a signature still compares all of them
+        ParsedTarget::Function {
+            contract_name,
+            file,
+            function_name,
+            ..
+        } => {
+            let contract_path =
+                project.locate_contract(file.as_deref(), &contract_name, &options.sources)?;
+            let functions =
+                parser.parse_all_functions(&contract_path, &contract_name, &function_name)?;
+            (contract_name, contract_path, functions)
+        }
+        ParsedTarget::AllContracts | ParsedTarget::File { .. } => {
+            return Err("diff needs a contract or function target".into());
+        }
+    };
+
+    // Group overloads by name, in order of first definition
+    let mut func_groups: Vec<(&str, Vec<&FunctionContext>)> = Vec::new();
+    for ctx in &function_contexts {
+        match func_groups
+            .iter_mut()
+            .find(|(name, _)| *name == ctx.function_name)
+        {
+            Some((_, contexts)) => contexts.push(ctx),
+            None => func_groups.push((&ctx.function_name, vec![ctx])),
+        }
+    }
+
+    let contract_dir = options.contract_dir(project, output_dir, &contract_path, &contract_name);
+    let mut diffs = Vec::new();
+    for (func_name, contexts) in func_groups {
+        let path = contract_dir.join(format!("{}.{}", func_name, options.format.extension()));
+        let existing = match fs::read_to_string(&path) {
+            Ok(content) => parse_trees(&content)?,
+            Err(_) => Vec::new(),
+        };
+        let fresh = parse_trees(&render_overloads(func_name, &contexts, options)?)?;
+
+        let diff = diff_trees(&existing, &fresh);
+        if !diff.is_empty() {
+            diffs.push((path, diff));
+        }
+    }
+
+    Ok(diffs)
+}
+
+/// Stand-in file name for source read from stdin without a `--path`
+const STDIN_FILE: &str = "<stdin>.sol";
+
+/// Tree of `function` (a name, or a name with its signature) in `source`, parsed as
+/// if it were saved at `path`
+fn tree_from_source(
+    project: &FoundryProject,
+    path: &Path,
+    source: &str,
+    contract_name: &str,
+    function: &str,
+    options: &GenerateOptions,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let parser = options.parser(project).with_source(path, source);
+
+    let (function_name, function_contexts) = match function.split_once('(') {
+        Some((name, args)) => {
+            let signature = args.strip_suffix(')').unwrap_or(args);
+            let ctx = parser.parse_function_by_signature(path, contract_name, name, signature)?;
+            (name, vec![ctx])
+        }
+        None => (
+            function,
+            parser.parse_all_functions(path, contract_name, function)?,
+        ),
+    };
+
+    let contexts: Vec<&FunctionContext> = function_contexts.iter().collect();
+    render_overloads(function_name, &contexts, options)
+}
+
+/// Render every overload of a function into one file, each headed by its signature
+fn render_overloads(
+    function_name: &str,
+    contexts: &[&FunctionContext],
+    options: &GenerateOptions,
+) -> Result<String, Box<dyn std::error::Error>> {
+    // A single JSON array, with each overload named by its signature
+    if options.format == OutputFormat::AuditJson {
+        let functions: Vec<(String, &[BranchPoint])> = contexts
+            .iter()
+            .map(|ctx| {
+                let name = match contexts {
+                    [_] => function_name.to_string(),
+                    _ => format!("{}({})", function_name, ctx.signature),
+                };
+                (name, ctx.branch_points.as_slice())
+            })
+            .collect();
+        return Ok(render_audit_json(&functions));
+    }
+
+    if let [ctx] = contexts {
+        let content = options.render(function_name, ctx.branch_points.clone())?;
+        return Ok(options.with_hints(&ctx.hints, content));
+    }
+
+    let shared = if options.dedup_across_overloads {
+        shared_prefix_len(contexts)
+    } else {
+        0
+    };
+
+    let mut sections = Vec::new();
+
+    // Branch points every overload starts with (e.g. a common modifier) are rendered once
+    if shared > 0 {
+        let content =
+            options.render(function_name, contexts[0].branch_points[..shared].to_vec())?;
+        sections.push(options.section("shared by all overloads", content));
+    }
+
+    for ctx in contexts {
+        let root_name = format!("{}({})", function_name, ctx.signature);
+        let content = options.render(&root_name, ctx.branch_points[shared..].to_vec())?;
+        let content = options.with_hints(&ctx.hints, content);
+        sections.push(options.section(&root_name, content));
+    }
+
+    Ok(sections.join("\n"))
+}
+
+/// Number of leading branch points that are identical in every overload
+fn shared_prefix_len(contexts: &[&FunctionContext]) -> usize {
+    let Some(first) = contexts.first() else {
+        return 0;
+    };
+
+    first
+        .branch_points
+        .iter()
+        .enumerate()
+        .take_while(|(i, bp)| {
+            contexts.iter().all(|ctx| {
+                ctx.branch_points
+                    .get(*i)
+                    .is_some_and(|other| other.same_check(bp))
+            })
+        })
+        .count()
+}
+
+fn parse_target(target: &str) -> ParsedTarget {
+    // Empty target = all contracts
+    if target.is_empty() {
+        return ParsedTarget::AllContracts;
+    }
+
+    // A path to a Solidity file rather than a contract name
+    if target.ends_with(".sol") {
+        return ParsedTarget::File {
+            path: PathBuf::from(target),
+        };
+    }
+
+    // Check if it contains :: (has function name)
+    let contract_part = target.find("::").map_or(target, |pos| &target[..pos]);
+
+    // Foundry-style `src/Vault.sol:Vault` names the file defining the contract
+    let (file, contract_name) = match contract_part.rsplit_once(':') {
+        Some((path, name)) => (Some(PathBuf::from(path)), name.to_string()),
+        None => (None, contract_part.to_string()),
+    };
+
+    if let Some(separator_pos) = target.find("::") {
+        let function_part = &target[separator_pos + 2..];
+
+        // Check if signature is provided: functionName(args)
+        if let Some(open_paren) = function_part.find('(') {
+            if function_part.ends_with(')') {
+                let function_name = function_part[..open_paren].to_string();
+                let signature = function_part[open_paren + 1..function_part.len() - 1].to_string();
+                return ParsedTarget::Function {
+                    contract_name,
+                    file,
+                    function_name,
+                    signature: Some(signature),
+                };
+            }
+        }
+
+        // No signature - just function name
+        ParsedTarget::Function {
+            contract_name,
+            file,
+            function_name: function_part.to_string(),
+            signature: None,
+        }
+    } else {
+        // Contract name only
+        ParsedTarget::Contract {
+            contract_name,
+            file,
+        }
+    }
+}
+
+/// Convert CamelCase to snake_case
+/// strictness: new word on first uppercase or uppercase preceded by lowercase
+fn to_snake_case(s: &str) -> String {
+    let mut result = String::new();
+    let chars: Vec<char> = s.chars().collect();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            // Add underscore if:
+            // 1. It's not the first character
+            // 2. The previous character is lowercase
+            if i > 0 {
+                let prev = chars[i - 1];
+                if prev.is_lowercase() {
+                    result.push('_');
+                }
+            }
+            result.push(c.to_ascii_lowercase());
+        } else {
+            result.push(c);
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::{BinaryOp, CheckKind, ConditionContext, ConditionExpr};
+
+    #[test]
+    fn test_to_snake_case() {
+        assert_eq!(to_snake_case("Vault"), "vault");
+        assert_eq!(to_snake_case("VaultABC"), "vault_abc");
+        assert_eq!(to_snake_case("HiHello"), "hi_hello");
+        assert_eq!(to_snake_case("myFunction"), "my_function"); // Note: standard conversion, but contract names are usually CapWords
+        assert_eq!(to_snake_case("ABC"), "abc");
+        assert_eq!(to_snake_case("A"), "a");
+    }
+
+    /// Create a throwaway project with the given `src/` files
+    fn scratch_project(name: &str, files: &[(&str, &str)]) -> FoundryProject {
+        let root = std::env::temp_dir().join(format!("acacia-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let src_dir = root.join("src");
+        fs::create_dir_all(&src_dir).unwrap();
+
+        for (path, content) in files {
+            let file_path = src_dir.join(path);
+            fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+            fs::write(file_path, content).unwrap();
+        }
+
+        FoundryProject {
+            root,
+            src_dir,
+            lib_dirs: vec![],
+            remappings: vec![],
+        }
+    }
+
+    #[test]
+    fn test_build_and_dependency_dirs_are_not_searched() {
+        let project = scratch_project(
+            "ignored-dirs",
+            &[
+                ("Vault.sol", VAULT),
+                ("out/Vault.sol/Vault.sol", VAULT),
+                ("node_modules/dep/Token.sol", VAULT),
+                ("generated/Flat.sol", VAULT),
+                ("tokens/Token.sol", VAULT),
+            ],
+        );
+        fs::write(project.root.join(".gitignore"), "# build\nsrc/generated/\n").unwrap();
+
+        let mut found = project.find_all_contracts();
+        found.sort();
+        assert_eq!(
+            found,
+            vec![
+                project.src_dir.join("Vault.sol"),
+                project.src_dir.join("tokens/Token.sol"),
+            ]
+        );
+
+        // Both can be turned off
+        let everything = SourceFilter {
+            ignored_dirs: vec![],
+            gitignore: false,
+        };
+        assert_eq!(project.find_contracts(&everything).len(), 5);
+    }
+
+    #[test]
+    fn test_contract_lookup_and_watch_use_the_configured_filter() {
+        let project = scratch_project(
+            "lookup-filter",
+            &[("Vault.sol", VAULT), ("legacy/Vault.sol", VAULT)],
+        );
+        let sources = SourceFilter {
+            ignored_dirs: vec!["legacy".to_string()],
+            ..SourceFilter::default()
+        };
+        let vault = project.src_dir.join("Vault.sol");
+
+        assert!(project
+            .find_contract("Vault", &SourceFilter::default())
+            .is_err());
+        assert_eq!(project.find_contract("Vault", &sources).unwrap(), vault);
+        assert_eq!(
+            SourceSnapshot::take(&project, &sources)
+                .0
+                .into_keys()
+                .collect::<Vec<_>>(),
+            vec![vault]
+        );
+    }
+
+    const VAULT: &str = r#"
+        contract Vault {
+            function deposit(uint256 amount) external {
+                require(amount > 0, "zero");
+            }
+            function withdraw(uint256 amount) external {
+                require(amount > 0, "zero");
+            }
+        }
+    "#;
+
+    const TOKEN: &str = r#"
+        contract Token {
+            function mint(uint256 amount) external {
+                require(amount > 0, "zero");
+            }
+            function burn(uint256 amount) external {
+                require(amount > 0, "zero");
+            }
+        }
+    "#;
+
+    #[test]
+    fn test_read_targets_file_skips_comments_and_blanks() {
+        let project = scratch_project("targets-read", &[]);
+        let path = project.root.join("targets.txt");
+        fs::write(
+            &path,
+            "# contracts\nVault\n\n  Token::mint  \n# Token::burn\n",
+        )
+        .unwrap();
+
+        let targets = read_targets_file(&path).unwrap();
+
+        assert_eq!(targets, vec!["Vault", "Token::mint"]);
+    }
+
+    #[test]
+    fn test_generate_from_targets_file() {
+        let project = scratch_project("targets-gen", &[("Vault.sol", VAULT), ("Token.sol", TOKEN)]);
+        let targets_path = project.root.join("targets.txt");
+        fs::write(&targets_path, "# batch\nVault\n\nToken::mint\n").unwrap();
+        let output = project.root.join("trees");
+
+        let report = generate_from_targets_file(
+            &project,
+            &targets_path,
+            output.to_str().unwrap(),
+            &GenerateOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(report.trees, 3);
+        assert!(output.join("vault/deposit.tree").exists());
+        assert!(output.join("vault/withdraw.tree").exists());
+        assert!(output.join("token/mint.tree").exists());
+        assert!(!output.join("token/burn.tree").exists());
+    }
+
+    #[test]
+    fn test_config_output_dir_used_without_output_flag() {
+        let project = scratch_project("config-output", &[("Vault.sol", VAULT)]);
+        fs::write(
+            project.root.join(".acacia.toml"),
+            "output_dir = \"test/btt\"\n",
+        )
+        .unwrap();
+        let config = AcaciaConfig::load(project.root()).unwrap();
+
+        let output = output_dir(&project, &config, None);
+        generate_tree(&project, "Vault", &output, &GenerateOptions::default()).unwrap();
+
+        assert!(project.root.join("test/btt/vault/deposit.tree").exists());
+        assert_eq!(
+            output_dir(&project, &config, Some("trees".to_string())),
+            "trees"
+        );
+    }
+
+    #[test]
+    fn test_mirror_layout_keeps_source_directories() {
+        let project = scratch_project("mirror-layout", &[("tokens/Vault.sol", VAULT)]);
+        let output = project.root.join("trees");
+        let output_dir = output.to_str().unwrap();
+        let options = GenerateOptions {
+            layout: Layout::Mirror,
+            ..GenerateOptions::default()
+        };
+
+        generate_tree(&project, "Vault", output_dir, &options).unwrap();
+
+        assert!(output.join("tokens/vault/deposit.tree").exists());
+        assert!(!output.join("vault").exists());
+    }
+
+    #[test]
+    fn test_check_reports_only_modified_tree() {
+        let project = scratch_project("check", &[("Vault.sol", VAULT)]);
+        let output = project.root.join("trees");
+        let output_dir = output.to_str().unwrap();
+        generate_tree(&project, "Vault", output_dir, &GenerateOptions::default()).unwrap();
+
+        let withdraw = output.join("vault/withdraw.tree");
+        fs::write(&withdraw, "withdraw\n└── it should succeed\n").unwrap();
+        let options = GenerateOptions {
+            check: true,
+            ..GenerateOptions::default()
+        };
+
+        let report = generate_tree(&project, "Vault", output_dir, &options).unwrap();
+
+        assert_eq!(report.stale, vec![withdraw.clone()]);
+        // Nothing is rewritten in check mode
+        assert_eq!(
+            fs::read_to_string(&withdraw).unwrap(),
+            "withdraw\n└── it should succeed\n"
+        );
+    }
+
+    #[test]
+    fn test_function_target_counts_only_written_trees() {
+        let project = scratch_project("function-count", &[("Vault.sol", VAULT)]);
+        let output = project.root.join("trees");
+        let output_dir = output.to_str().unwrap();
+        let generate = |options: &GenerateOptions| {
+            generate_tree(&project, "Vault::deposit", output_dir, options)
+                .unwrap()
+                .trees
+        };
+        let dry_run = GenerateOptions {
+            dry_run: true,
+            ..GenerateOptions::default()
+        };
+        let check = GenerateOptions {
+            check: true,
+            ..GenerateOptions::default()
+        };
+
+        assert_eq!(generate(&dry_run), 0);
+        assert_eq!(generate(&GenerateOptions::default()), 1);
+        // The second run finds the same tree on disk
+        assert_eq!(generate(&GenerateOptions::default()), 0);
+        assert_eq!(generate(&check), 0);
+    }
+
+    #[test]
+    fn test_only_missing_keeps_existing_trees() {
+        let project = scratch_project("only-missing", &[("Vault.sol", VAULT)]);
+        let output = project.root.join("trees");
+        fs::create_dir_all(output.join("vault")).unwrap();
+        fs::write(
+            output.join("vault/deposit.tree"),
+            "deposit\n└── hand-written\n",
+        )
+        .unwrap();
+        let options = GenerateOptions {
+            only_missing: true,
+            ..GenerateOptions::default()
+        };
+
+        let report = generate_tree(&project, "Vault", output.to_str().unwrap(), &options).unwrap();
+
+        assert_eq!(report.trees, 1);
+        assert_eq!(
+            fs::read_to_string(output.join("vault/deposit.tree")).unwrap(),
+            "deposit\n└── hand-written\n"
+        );
+        assert!(output.join("vault/withdraw.tree").exists());
+    }
+
+    #[test]
+    fn test_only_reverting_skips_functions_without_guards() {
+        const POOL: &str = r#"
+            contract Pool {
+                uint256 public fee;
+                function setFee(uint256 newFee) external {
+                    fee = newFee;
+                }
+                function deposit(uint256 amount) external {
+                    require(amount > 0, "zero");
+                }
+                function deposit(uint256 amount, address to) external {
+                    fee = amount;
+                }
+                function getFee() external view returns (uint256) {
+                    return fee;
+                }
+            }
+        "#;
+        let project = scratch_project("only-reverting", &[("Pool.sol", POOL)]);
+        let output = project.root.join("trees");
+        let options = GenerateOptions {
+            only_reverting: true,
+            ..GenerateOptions::default()
+        };
+
+        let report = generate_tree(&project, "Pool", output.to_str().unwrap(), &options).unwrap();
+
+        // One guarded overload keeps the combined file
+        assert_eq!(report.trees, 1);
+        assert!(output.join("pool/deposit.tree").exists());
+        assert!(!output.join("pool/setFee.tree").exists());
+        assert!(!output.join("pool/getFee.tree").exists());
+    }
+
+    #[test]
+    fn test_merge_keeps_edited_leaves_and_adds_new_branches() {
+        let project = scratch_project("merge", &[("Vault.sol", VAULT)]);
+        let output = project.root.join("trees");
+        let deposit = output.join("vault/deposit.tree");
+        let options = GenerateOptions {
+            merge: true,
+            ..GenerateOptions::default()
+        };
+
+        generate_tree(
+            &project,
+            "Vault::deposit",
+            output.to_str().unwrap(),
+            &options,
+        )
+        .unwrap();
+        let edited = fs::read_to_string(&deposit).unwrap().replace(
+            r#"it should revert with "zero""#,
+            r#"it should revert with "zero" and leave balances unchanged"#,
+        );
+        fs::write(&deposit, &edited).unwrap();
+
+        // A new check adds a branch on the success path
+        fs::write(
+            project.src_dir.join("Vault.sol"),
+            VAULT.replacen(
+                r#"require(amount > 0, "zero");"#,
+                r#"require(amount > 0, "zero");
+                require(amount < 100, "too much");"#,
+                1,
+            ),
+        )
+        .unwrap();
+        generate_tree(
+            &project,
+            "Vault::deposit",
+            output.to_str().unwrap(),
+            &options,
+        )
+        .unwrap();
+
+        let expected = r#"deposit
+├── when amount is at most zero
+│   └── it should revert with "zero" and leave balances unchanged
+└── when amount is greater than zero
+    ├── when amount is at least 100
+    │   └── it should revert with "too much"
+    └── when amount is less than 100
+        └── it should succeed
+"#;
+        assert_eq!(fs::read_to_string(&deposit).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_exclude_skips_matching_files() {
+        let project = scratch_project(
+            "exclude",
+            &[("Vault.sol", VAULT), ("mocks/Token.sol", TOKEN)],
+        );
+        let options = GenerateOptions {
+            exclude: build_excludes(&["**/mocks/**".to_string()]).unwrap(),
+            ..GenerateOptions::default()
+        };
+
+        assert!(!options.excludes(&project, &project.src_dir.join("Vault.sol")));
+        assert!(options.excludes(&project, &project.src_dir.join("mocks/Token.sol")));
+
+        let output = project.root.join("trees");
+        generate_tree(&project, "", output.to_str().unwrap(), &options).unwrap();
+        assert!(output.join("vault/deposit.tree").exists());
+        assert!(!output.join("token").exists());
+    }
+
+    #[test]
+    fn test_strict_fails_on_unparseable_files() {
+        let project = scratch_project(
+            "strict",
+            &[("Vault.sol", VAULT), ("Broken.sol", "contract Broken {")],
+        );
+        let output = project.root.join("trees");
+
+        let report = generate_tree(
+            &project,
+            "",
+            output.to_str().unwrap(),
+            &GenerateOptions::default(),
+        )
+        .unwrap();
+
+        // The broken file is skipped with a warning, the rest is still generated
+        assert!(output.join("vault/deposit.tree").exists());
+        assert_eq!(report.parse_failures.len(), 1);
+        assert!(report.parse_failures[0].file.ends_with("Broken.sol"));
+        assert!(report.warnings.iter().any(|w| {
+            matches!(w.kind, WarningKind::ParseSkipped { .. }) && w.file.ends_with("Broken.sol")
+        }));
+
+        assert!(check_parse_failures(&report, false).is_ok());
+        assert_eq!(
+            check_parse_failures(&report, true),
+            Err("1 files failed to parse".to_string())
+        );
+    }
+
+    #[test]
+    fn test_report_counts_branch_points_per_function() {
+        const LEDGER: &str = r#"
+            contract Ledger {
+                address public owner;
+                function record(uint256 amount) external {
+                    require(msg.sender == owner, "owner");
+                    require(amount > 0, "zero");
+                }
+                function reset() external {
+                    require(msg.sender == owner, "owner");
+                }
+                function total() external view returns (uint256) {
+                    return 0;
+                }
+            }
+        "#;
+        let project = scratch_project("summary", &[("Ledger.sol", LEDGER)]);
+        let output = project.root.join("trees");
+
+        let report = generate_tree(
+            &project,
+            "Ledger",
+            output.to_str().unwrap(),
+            &GenerateOptions::default(),
+        )
+        .unwrap();
+        let summary = Summary::new(&report.functions);
+
+        assert_eq!(summary.contracts, 1);
+        assert_eq!(summary.functions, 3);
+        assert_eq!(summary.branch_points, 3);
+        assert_eq!(summary.most_branches[0].function, "record(uint256)");
+        assert_eq!(summary.most_branches[0].branch_points, 2);
+    }
+
+    #[test]
+    fn test_include_internal_adds_internal_functions() {
+        const LEDGER: &str = r#"
+            contract Ledger {
+                function record(uint256 amount) external {
+                    _check(amount);
+                }
+                function _check(uint256 amount) internal pure {
+                    require(amount > 0, "zero");
+                }
+            }
+        "#;
+        let project = scratch_project("include-internal", &[("Ledger.sol", LEDGER)]);
+        let output = project.root.join("trees");
+
+        generate_tree(
+            &project,
+            "Ledger",
+            output.to_str().unwrap(),
+            &GenerateOptions::default(),
+        )
+        .unwrap();
+        assert!(output.join("ledger/record.tree").exists());
+        assert!(!output.join("ledger/_check.tree").exists());
+
+        let options = GenerateOptions {
+            include_internal: true,
+            ..GenerateOptions::default()
+        };
+        generate_tree(&project, "Ledger", output.to_str().unwrap(), &options).unwrap();
+        assert!(output.join("ledger/_check.tree").exists());
+    }
+
+    #[test]
+    fn test_qualified_target_uses_explicit_file() {
+        let project = scratch_project(
+            "qualified-target",
+            &[("Vault.sol", VAULT), ("v2/VaultV2.sol", VAULT)],
+        );
+        let locate = |target: &str| match parse_target(target) {
+            ParsedTarget::Function {
+                contract_name,
+                file,
+                function_name,
+                ..
+            } => {
+                assert_eq!(function_name, "deposit");
+                project.locate_contract(file.as_deref(), &contract_name, &SourceFilter::default())
+            }
+            _ => panic!("{} is not a function target", target),
+        };
+
+        assert_eq!(
+            locate("src/v2/VaultV2.sol:Vault::deposit").unwrap(),
+            project.root.join("src/v2/VaultV2.sol")
+        );
+        // Without a path, the file named after the contract wins
+        assert_eq!(
+            locate("Vault::deposit").unwrap(),
+            project.src_dir.join("Vault.sol")
+        );
+        assert!(matches!(
+            locate("src/Token.sol:Vault::deposit"),
+            Err(FoundryError::ContractNotInFile { .. })
+        ));
+
+        assert!(matches!(
+            parse_target("src/v2/VaultV2.sol:Vault"),
+            ParsedTarget::Contract { file: Some(file), .. } if file == Path::new("src/v2/VaultV2.sol")
+        ));
+    }
+
+    #[test]
+    fn test_tree_from_source_parses_text_not_on_disk() {
+        let project = scratch_project("stdin-source", &[]);
+        let path = project.root.join(STDIN_FILE);
+        let options = GenerateOptions::default();
+
+        let tree = tree_from_source(&project, &path, VAULT, "Vault", "withdraw", &options).unwrap();
+        assert_eq!(
+            tree,
+            r#"withdraw
+├── when amount is at most zero
+│   └── it should revert with "zero"
+└── when amount is greater than zero
+    └── it should succeed
+"#
+        );
+        assert!(!path.exists());
+
+        let tree = tree_from_source(
+            &project,
+            &path,
+            VAULT,
+            "Vault",
+            "deposit(uint256)",
+            &options,
+        )
+        .unwrap();
+        assert!(tree.starts_with("deposit\n"));
+
+        // Base contracts and the modifiers they define come from the piped text too
+        let source = r#"
+            contract Base {
+                address public owner;
+                modifier onlyOwner() {
+                    require(msg.sender == owner, "not owner");
+                    _;
+                }
+            }
+            contract Vault is Base {
+                function sweep(uint256 amount) external onlyOwner {
+                    require(amount > 0, "zero");
+                }
+            }
+        "#;
+        let tree = tree_from_source(&project, &path, source, "Vault", "sweep", &options).unwrap();
+        assert_eq!(
+            tree,
+            r#"sweep
+├── given msg.sender is not owner
+│   └── it should revert with "not owner"
+└── given msg.sender is owner
+    ├── when amount is at most zero
+    │   └── it should revert with "zero"
+    └── when amount is greater than zero
+        └── it should succeed
+"#
+        );
+    }
+
+    #[test]
+    fn test_single_file_generates_every_contract_in_it() {
+        assert!(matches!(
+            parse_target("src/Vaults.sol"),
+            ParsedTarget::File { .. }
+        ));
+
+        let project = scratch_project(
+            "single-file",
+            &[("Vaults.sol", VAULTS), ("Token.sol", TOKEN)],
+        );
+        let output = project.root.join("trees");
+        let file = project.src_dir.join("Vaults.sol");
+
+        let report = generate_tree(
+            &project,
+            file.to_str().unwrap(),
+            output.to_str().unwrap(),
+            &GenerateOptions::default(),
+        )
+        .unwrap();
+
+        assert!(report.trees > 0);
+        assert!(output.join("base_vault").is_dir());
+        assert!(output.join("vault").is_dir());
+        // Contracts in other files aren't touched
+        assert!(!output.join("token").exists());
+    }
+
+    #[test]
+    fn test_watch_reports_only_the_changed_file() {
+        let project = scratch_project("watch", &[("Vault.sol", VAULT), ("Token.sol", TOKEN)]);
+        let sources = SourceFilter::default();
+        let mut snapshot = SourceSnapshot::take(&project, &sources);
+        assert!(poll_changes(&project, &sources, &mut snapshot).is_empty());
+
+        // Simulate an edit by moving the file's modification time forward
+        let token = project.src_dir.join("Token.sol");
+        let edited = SystemTime::now() + Duration::from_secs(60);
+        fs::File::options()
+            .write(true)
+            .open(&token)
+            .unwrap()
+            .set_modified(edited)
+            .unwrap();
+
+        assert_eq!(poll_changes(&project, &sources, &mut snapshot), vec![token]);
+        assert!(poll_changes(&project, &sources, &mut snapshot).is_empty());
+    }
+
+    const VAULTS: &str = r#"
+        abstract contract BaseVault {
+            function deposit(uint256 amount) external virtual {
+                require(amount > 0, "zero");
+            }
+            function withdraw(uint256 amount) external {
+                require(amount > 0, "zero");
+            }
+        }
+        contract Vault is BaseVault {
+            function deposit(uint256 amount) external override {
+                require(amount > 1, "dust");
+            }
+        }
+    "#;
+
+    const SHARES: &str = r#"
+        function ceilDiv(uint256 a, uint256 b) pure returns (uint256) {
+            require(b > 0, "zero");
+            return (a + b - 1) / b;
+        }
+        contract SharePool {
+            function sharesFor(uint256 assets) external pure returns (uint256) {
+                return ceilDiv(assets, 3);
+            }
+        }
+    "#;
+
+    #[test]
+    fn test_free_functions_are_written_under_their_file() {
+        let project = scratch_project("free-functions", &[("math/Shares.sol", SHARES)]);
+        let output = project.root.join("trees");
+
+        let report = generate_tree(
+            &project,
+            "",
+            output.to_str().unwrap(),
+            &GenerateOptions::default(),
+        )
+        .unwrap();
+
+        assert!(output.join("_free/math_shares/ceilDiv.tree").is_file());
+        assert!(output.join("share_pool").is_dir());
+        assert!(report
+            .functions
+            .iter()
+            .any(|f| f.contract == "Shares.sol" && f.function == "ceilDiv(uint256,uint256)"));
+    }
+
+    #[test]
+    fn test_file_segment_names_the_source_path() {
+        let project = scratch_project("file-segment", &[]);
+
+        assert_eq!(
+            file_segment(&project, &project.src_dir.join("v2/Vault.sol")),
+            "v2_vault"
+        );
+        assert_eq!(
+            file_segment(&project, &project.src_dir.join("legacy/TokenVault.sol")),
+            "legacy_token_vault"
+        );
+    }
+
+    #[test]
+    fn test_same_named_contracts_get_separate_directories() {
+        let v2 = VAULT.replace(r#""zero""#, r#""empty""#);
+        let project = scratch_project(
+            "duplicate-names",
+            &[("v1/Vault.sol", VAULT), ("v2/Vault.sol", &v2)],
+        );
+        let output = project.root.join("trees");
+
+        generate_tree(
+            &project,
+            "",
+            output.to_str().unwrap(),
+            &GenerateOptions::default(),
+        )
+        .unwrap();
+
+        let v1 = fs::read_to_string(output.join("v1_vault/vault/deposit.tree")).unwrap();
+        let v2 = fs::read_to_string(output.join("v2_vault/vault/deposit.tree")).unwrap();
+        assert!(v1.contains(r#"with "zero""#));
+        assert!(v2.contains(r#"with "empty""#));
+        assert!(!output.join("vault").exists());
+    }
+
+    #[test]
+    fn test_index_links_every_tree_by_contract() {
+        let project = scratch_project(
+            "index",
+            &[("Vault.sol", VAULT), ("tokens/Token.sol", TOKEN)],
+        );
+        let output = project.root.join("trees");
+        let options = GenerateOptions {
+            index: true,
+            ..GenerateOptions::default()
+        };
+
+        generate_tree(&project, "", output.to_str().unwrap(), &options).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(output.join(INDEX_FILE)).unwrap(),
+            "\
+# Branching trees
+
+## Token
+
+- [burn](token/burn.tree)
+- [mint](token/mint.tree)
+
+## Vault
+
+- [deposit](vault/deposit.tree)
+- [withdraw](vault/withdraw.tree)
+"
+        );
+    }
+
+    #[test]
+    fn test_scaffold_is_written_once_next_to_tree() {
+        let project = scratch_project("scaffold", &[("Vault.sol", VAULT)]);
+        let output = project.root.join("trees");
+        let options = GenerateOptions {
+            scaffold: true,
+            ..GenerateOptions::default()
+        };
+
+        generate_tree(&project, "Vault", output.to_str().unwrap(), &options).unwrap();
+
+        let scaffold = output.join("vault/withdraw.t.sol");
+        let content = fs::read_to_string(&scaffold).unwrap();
+        assert!(content.contains("contract withdraw_Test {"));
+        assert!(content.contains("function test_RevertWhen_AmountIsAtMostZero() external {"));
+
+        // Filled-in tests survive regenerating
+        fs::write(&scaffold, "// my tests").unwrap();
+        generate_tree(&project, "Vault", output.to_str().unwrap(), &options).unwrap();
+        assert_eq!(fs::read_to_string(&scaffold).unwrap(), "// my tests");
+    }
+
+    #[test]
+    fn test_overridden_functions_follow_ancestors() {
+        let contract = |name: &str, parents: &[&str], functions: &[&str]| ProjectContract {
+            name: name.to_string(),
+            file: PathBuf::from(format!("{}.sol", name)),
+            parents: parents.iter().map(|p| p.to_string()).collect(),
+            functions: functions
+                .iter()
+                .map(|f| overload_named(f, "uint256", vec![]))
+                .collect(),
+        };
+        let contracts = vec![
+            contract("Base", &[], &["deposit", "withdraw"]),
+            contract("Middle", &["Base"], &["withdraw"]),
+            contract("Leaf", &["Middle"], &["deposit"]),
+        ];
+
+        let overridden = overridden_functions(&contracts);
+
+        let base = &overridden["Base"];
+        assert!(base.contains("deposit(uint256)"));
+        assert!(base.contains("withdraw(uint256)"));
+        assert!(overridden["Middle"].contains("deposit(uint256)"));
+        assert!(!overridden.contains_key("Leaf"));
+    }
+
+    #[test]
+    fn test_all_contracts_attributes_overrides_to_derived_contract() {
+        let project = scratch_project("attribution", &[("Vaults.sol", VAULTS)]);
+        let output = project.root.join("trees");
+
+        let report = generate_tree(
+            &project,
+            "",
+            output.to_str().unwrap(),
+            &GenerateOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(report.trees, 2);
+        assert!(output.join("base_vault/withdraw.tree").exists());
+        assert!(!output.join("base_vault/deposit.tree").exists());
+        assert!(output.join("vault/deposit.tree").exists());
+    }
+
+    fn overload_named(
+        name: &str,
+        signature: &str,
+        branch_points: Vec<BranchPoint>,
+    ) -> FunctionContext {
+        FunctionContext {
+            function_name: name.to_string(),
+            signature: signature.to_string(),
+            branch_points,
+            parameters: vec![],
+            state_variables: vec![],
+            warnings: vec![],
+            hints: vec![],
+        }
+    }
+
+    fn overload(signature: &str, branch_points: Vec<BranchPoint>) -> FunctionContext {
+        overload_named("transfer", signature, branch_points)
+    }
+
+    fn require_bp(left: &str, op: BinaryOp, right: &str, context: ConditionContext) -> BranchPoint {
+        BranchPoint {
+            condition: ConditionExpr::Binary {
+                left: left.to_string(),
+                op,
+                right: right.to_string(),
+            },
+            context,
+            is_loop: false,
+            is_unchecked: false,
+            is_external_call: false,
+            is_if_revert: false,
+            kind: CheckKind::Require,
+            source: None,
+            span: None,
+            revert_reason: None,
+            revert_message: None,
+            arms: None,
+        }
+    }
+
+    fn transfer_overloads() -> Vec<FunctionContext> {
+        let only_owner = || {
+            require_bp(
+                "msg.sender",
+                BinaryOp::Eq,
+                "owner",
+                ConditionContext::Storage,
+            )
+        };
+        let positive = require_bp("amount", BinaryOp::Gt, "0", ConditionContext::External);
+
+        vec![
+            overload("address,uint256", vec![only_owner(), positive.clone()]),
+            overload("address,uint256,bytes", vec![only_owner(), positive]),
+        ]
+    }
+
+    #[test]
+    fn test_combined_overloads_have_signature_headers() {
+        let overloads = transfer_overloads();
+        let contexts: Vec<&FunctionContext> = overloads.iter().collect();
+
+        let content = render_overloads("transfer", &contexts, &GenerateOptions::default()).unwrap();
+
+        assert!(content.starts_with("// transfer(address,uint256)\ntransfer(address,uint256)\n"));
+        assert!(content
+            .contains("\n\n// transfer(address,uint256,bytes)\ntransfer(address,uint256,bytes)\n"));
+        assert!(!content.contains("shared by all overloads"));
+    }
+
+    #[test]
+    fn test_dedup_across_overloads_factors_out_shared_branches() {
+        let overloads = transfer_overloads();
+        let contexts: Vec<&FunctionContext> = overloads.iter().collect();
+        let options = GenerateOptions {
+            dedup_across_overloads: true,
+            ..GenerateOptions::default()
+        };
+
+        let content = render_overloads("transfer", &contexts, &options).unwrap();
+
+        let expected = "\
+// shared by all overloads
+transfer
+├── given msg.sender is not owner
+│   └── it should revert
+└── given msg.sender is owner
+    ├── when amount is at most zero
+    │   └── it should revert
+    └── when amount is greater than zero
+        └── it should succeed
+
+// transfer(address,uint256)
+transfer(address,uint256)
+└── it should succeed
+
+// transfer(address,uint256,bytes)
+transfer(address,uint256,bytes)
+└── it should succeed
+";
+        assert_eq!(content, expected);
+    }
+
+    #[test]
+    fn test_merge_keeps_edited_leaves_of_each_overload() {
+        let overloads = transfer_overloads();
+        let contexts: Vec<&FunctionContext> = overloads.iter().collect();
+        let options = GenerateOptions {
+            merge: true,
+            ..GenerateOptions::default()
+        };
+        let content = render_overloads("transfer", &contexts, &options).unwrap();
+
+        let path = std::env::temp_dir().join(format!(
+            "acacia-merge-overloads-{}.tree",
+            std::process::id()
+        ));
+        let edited = content.replace("it should succeed", "it should succeed and emit Transfer");
+        fs::write(&path, &edited).unwrap();
+
+        let merged = merge_existing(&path, &content, &options);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(merged.as_deref(), Some(edited.as_str()));
+    }
+
+    #[test]
+    fn test_btt_hints_are_written_above_the_tree() {
+        let mut ctx = overload_named("deposit", "uint256", vec![]);
+        ctx.hints = vec!["given the vault is empty".to_string()];
+
+        let content = render_overloads("deposit", &[&ctx], &GenerateOptions::default()).unwrap();
+        assert_eq!(
+            content,
+            "// given the vault is empty\ndeposit\n└── it should succeed\n"
+        );
+
+        // bulloak files can't hold comments
+        let options = GenerateOptions {
+            dialect: Dialect::Bulloak,
+            ..GenerateOptions::default()
+        };
+        let content = render_overloads("deposit", &[&ctx], &options).unwrap();
+        assert!(!content.contains("vault is empty"));
+    }
+
+    #[test]
+    fn test_merge_keeps_btt_hints() {
+        let mut ctx = overload_named("deposit", "uint256", vec![]);
+        ctx.hints = vec!["given the vault is empty".to_string()];
+        let options = GenerateOptions {
+            merge: true,
+            ..GenerateOptions::default()
+        };
+        let content = render_overloads("deposit", &[&ctx], &options).unwrap();
+
+        let path =
+            std::env::temp_dir().join(format!("acacia-merge-hints-{}.tree", std::process::id()));
+        fs::write(&path, "deposit\n└── it should succeed and emit Deposit\n").unwrap();
+
+        let merged = merge_existing(&path, &content, &options);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            merged.as_deref(),
+            Some("// given the vault is empty\ndeposit\n└── it should succeed and emit Deposit\n")
+        );
+    }
+
+    #[test]
+    fn test_split_overloads_writes_a_file_per_overload() {
+        let mut overloads = transfer_overloads();
+        overloads.push(overload("address[],uint256", vec![]));
+        let contexts: Vec<&FunctionContext> = overloads.iter().collect();
+        let output = std::env::temp_dir().join(format!("acacia-split-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&output);
+        let options = GenerateOptions {
+            split_overloads: true,
+            ..GenerateOptions::default()
+        };
+
+        let report = write_contract_trees(&output, &contexts, &options).unwrap();
+
+        assert_eq!(report.trees, 3);
+        assert!(!output.join("transfer.tree").exists());
+        for stem in [
+            "transfer_address_uint256",
+            "transfer_address_uint256_bytes",
+            "transfer_address_array_uint256",
+        ] {
+            assert!(output.join(format!("{}.tree", stem)).exists(), "{}", stem);
+        }
+
+        let single = fs::read_to_string(output.join("transfer_address_uint256.tree")).unwrap();
+        assert!(single.starts_with("transfer(address,uint256)\n├── given msg.sender"));
+    }
+
+    #[test]
+    fn test_dry_run_lists_files_without_writing() {
+        let mut overloads = transfer_overloads();
+        overloads.push(overload_named("approve", "address,uint256", vec![]));
+        let contexts: Vec<&FunctionContext> = overloads.iter().collect();
+        let output = std::env::temp_dir().join(format!("acacia-dry-run-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&output);
+        let options = GenerateOptions {
+            dry_run: true,
+            ..GenerateOptions::default()
+        };
+
+        let report = write_contract_trees(&output, &contexts, &options).unwrap();
+
+        assert!(!output.exists());
+        assert_eq!(report.trees, 2);
+        // Both overloads go into one combined file, as in a real run
+        assert_eq!(
+            format_planned(&report.planned),
+            format!(
+                "Would write 2 files:\n  {} (0 branch points)\n  {} (4 branch points)\n",
+                output.join("approve.tree").display(),
+                output.join("transfer.tree").display()
+            )
+        );
+    }
+
+    #[test]
+    fn test_function_without_branches_is_reported() {
+        let mut overloads = transfer_overloads();
+        overloads.push(overload_named("approve", "address,uint256", vec![]));
+
+        let summary = Summary::new(&function_stats("Vault", &overloads));
+
+        assert_eq!(summary.unguarded, vec!["Vault::approve(address,uint256)"]);
+    }
+
+    #[test]
+    fn test_failed_tree_keeps_other_functions() {
+        let mut overloads = transfer_overloads();
+        overloads.push(overload_named("approve", "address,uint256", vec![]));
+        let contexts: Vec<&FunctionContext> = overloads.iter().collect();
+        let output = std::env::temp_dir().join(format!("acacia-partial-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&output);
+        // A directory where `approve.tree` should go can't be written
+        fs::create_dir_all(output.join("approve.tree")).unwrap();
+
+        let report = write_contract_trees(&output, &contexts, &GenerateOptions::default()).unwrap();
+
+        assert_eq!(report.trees, 1);
+        assert!(output.join("transfer.tree").is_file());
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(report.failures[0].path, output.join("approve.tree"));
+        assert!(check_tree_failures(&report).is_err());
+    }
+
+    #[test]
+    fn test_format_listings_groups_functions_by_contract() {
+        let listings = vec![
+            ContractListing {
+                name: "Vault".to_string(),
+                path: PathBuf::from("src/Vault.sol"),
+                functions: vec![
+                    ("deposit(uint256)".to_string(), 1),
+                    ("withdraw(uint256,address)".to_string(), 3),
+                ],
+            },
+            ContractListing {
+                name: "Empty".to_string(),
+                path: PathBuf::from("src/Empty.sol"),
+                functions: vec![],
+            },
+        ];
+
+        let expected = "\
+Vault (src/Vault.sol)
+  deposit(uint256)           1 branch point
+  withdraw(uint256,address)  3 branch points
+Empty (src/Empty.sol)
+  (no public or external functions)
+";
+        assert_eq!(format_listings(&listings), expected);
+    }
+
+    /// Check a file against bulloak's tree grammar: identifier roots, `when`/`given`
+    /// branches and `it` leaves, with trees separated by blank lines
+    fn assert_bulloak_grammar(content: &str) {
+        for tree in content.split("\n\n") {
+            let lines: Vec<&str> = tree.lines().collect();
+            let root = lines[0];
+            assert!(
+                root.ends_with("_Test")
+                    && root.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'),
+                "invalid root {:?}",
+                root
+            );
+
+            let depth = |line: &str| line.chars().take_while(|c| !c.is_alphanumeric()).count();
+            for (i, line) in lines.iter().enumerate().skip(1) {
+                let label = line.trim_start_matches(|c: char| !c.is_alphanumeric());
+                let has_children = lines
+                    .get(i + 1)
+                    .is_some_and(|next| depth(next) > depth(line));
+                if has_children {
+                    assert!(
+                        label.starts_with("when ") || label.starts_with("given "),
+                        "invalid branch {:?}",
+                        label
+                    );
+                } else {
+                    assert!(label.starts_with("it "), "invalid leaf {:?}", label);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_bulloak_dialect_uses_identifier_roots() {
+        let overloads = transfer_overloads();
+        let contexts: Vec<&FunctionContext> = overloads.iter().collect();
+        let options = GenerateOptions {
+            dialect: Dialect::Bulloak,
+            ..GenerateOptions::default()
+        };
+
+        let content = render_overloads("transfer", &contexts, &options).unwrap();
+
+        assert!(content.starts_with("transfer_address_uint256_Test\n"));
+        assert!(content.contains("\n\ntransfer_address_uint256_bytes_Test\n"));
+        assert!(!content.contains("//"));
+        assert_bulloak_grammar(&content);
+
+        let positive = require_bp("amount", BinaryOp::Gt, "0", ConditionContext::External);
+        let single = options.render("withdraw", vec![positive]).unwrap();
+        assert!(single.starts_with("withdraw_Test\n"));
+        assert_bulloak_grammar(&single);
     }
 }
@@ -0,0 +1,99 @@
+//! Project-level settings read from `.acacia.toml`
+
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+use thiserror::Error;
+
+/// Name of the config file, looked up in the project root next to `foundry.toml`
+pub const CONFIG_FILE: &str = ".acacia.toml";
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Failed to read .acacia.toml: {0}")]
+    ReadError(#[from] std::io::Error),
+
+    #[error("Failed to parse .acacia.toml: {0}")]
+    ParseError(#[from] toml::de::Error),
+}
+
+/// Settings from `.acacia.toml`. Every key is optional and command-line flags take
+/// precedence over the file
+#[derive(Debug, Deserialize, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct AcaciaConfig {
+    /// Output directory, relative to the project root
+    pub output_dir: Option<String>,
+    /// Output format, spelled like the `--format` values (`tree`, `annotated`, ...)
+    pub format: Option<String>,
+    /// Also generate trees for internal and private functions
+    pub include_internal: Option<bool>,
+    /// Methods that revert on overflow, replacing the default `SafeCast` conversions
+    pub overflow_calls: Option<Vec<String>>,
+    /// Flags read as "the contract is already initialized", replacing `initialized`
+    pub initialized_flags: Option<Vec<String>>,
+    /// Directory names skipped when searching for sources, replacing the default
+    /// `out`, `cache`, `broadcast`, `node_modules` and `lib`
+    pub ignore_dirs: Option<Vec<String>>,
+    /// Skip the sources `.gitignore` lists (default: true)
+    pub gitignore: Option<bool>,
+    /// Wording of the tree leaves
+    #[serde(default)]
+    pub labels: LeafLabels,
+}
+
+/// Leaf text overrides, e.g. `revert = "it reverts"`
+#[derive(Debug, Deserialize, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct LeafLabels {
+    pub revert: Option<String>,
+    pub success: Option<String>,
+    pub early_return: Option<String>,
+}
+
+impl AcaciaConfig {
+    /// Load `.acacia.toml` from a project root, or the defaults if there is none
+    pub fn load(root: &Path) -> Result<Self, ConfigError> {
+        let path = root.join(CONFIG_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)?;
+        Ok(toml::from_str(&content)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config: AcaciaConfig = toml::from_str(
+            r#"
+            output_dir = "test/btt"
+            format = "annotated"
+            include_internal = true
+
+            [labels]
+            revert = "it reverts"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.output_dir.as_deref(), Some("test/btt"));
+        assert_eq!(config.format.as_deref(), Some("annotated"));
+        assert_eq!(config.include_internal, Some(true));
+        assert_eq!(config.labels.revert.as_deref(), Some("it reverts"));
+        assert_eq!(config.labels.success, None);
+    }
+
+    #[test]
+    fn test_missing_config_is_default() {
+        let root = std::env::temp_dir().join(format!("acacia-no-config-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+
+        assert_eq!(AcaciaConfig::load(&root).unwrap(), AcaciaConfig::default());
+    }
+}
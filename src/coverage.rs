@@ -0,0 +1,181 @@
+//! Coverage of tree leaves by the functions of a test contract
+//!
+//! A leaf counts as covered when a test function's name mentions the condition
+//! right above it. Names and labels are compared as sets of lowercase words
+//! (split on `_` and camelCase), ignoring filler words like `when`, `given`
+//! or `is`. A covered leaf additionally needs:
+//! - every word of the condition to appear in the test name
+//! - `not` in both or in neither
+//! - `revert` in the test name exactly when the leaf is `it should revert`
+
+use crate::tree::TreeNode;
+
+/// Words that carry no meaning when matching test names against labels
+const FILLER_WORDS: &[&str] = &[
+    "a", "an", "the", "when", "given", "is", "are", "it", "should", "test", "fuzz", "fork",
+];
+
+/// Leaves of a set of trees with and without a matching test
+#[derive(Debug, Default)]
+pub struct CoverageReport {
+    /// Number of leaves in all trees
+    pub total: usize,
+    /// Leaves without a test, as `function: condition, ..., outcome`
+    pub gaps: Vec<String>,
+}
+
+impl CoverageReport {
+    /// Number of leaves with a matching test
+    pub fn covered(&self) -> usize {
+        self.total - self.gaps.len()
+    }
+
+    /// Share of covered leaves, from 0 to 100
+    pub fn percentage(&self) -> f64 {
+        if self.total == 0 {
+            return 100.0;
+        }
+        self.covered() as f64 * 100.0 / self.total as f64
+    }
+}
+
+/// Whether a function is a Foundry test (`test_...`, `testFuzz_...`) or BTT-style `it_...` test
+pub fn is_test_function(name: &str) -> bool {
+    name.starts_with("test") || name.starts_with("it_")
+}
+
+/// Check which leaves of the given trees have a test among `test_names`
+pub fn check_coverage(trees: &[TreeNode], test_names: &[String]) -> CoverageReport {
+    let tests: Vec<Vec<String>> = test_names
+        .iter()
+        .filter(|name| is_test_function(name))
+        .map(|name| words(name))
+        .collect();
+
+    let mut report = CoverageReport::default();
+
+    for tree in trees {
+        let function_name = match tree {
+            TreeNode::Root { name, .. } => name.as_str(),
+            _ => "",
+        };
+
+        for path in tree.leaf_paths() {
+            report.total += 1;
+            if !tests.iter().any(|test| leaf_matches(&path, test)) {
+                report
+                    .gaps
+                    .push(format!("{}: {}", function_name, path.join(", ")));
+            }
+        }
+    }
+
+    report
+}
+
+/// Whether a test name (as words) covers the leaf at the end of `path`
+fn leaf_matches(path: &[&str], test: &[String]) -> bool {
+    let Some((outcome, conditions)) = path.split_last() else {
+        return false;
+    };
+    let reverts = outcome.contains("revert");
+    let test_reverts = test.iter().any(|w| w.starts_with("revert"));
+    if reverts != test_reverts {
+        return false;
+    }
+
+    // A tree without branch points has a single leaf: any matching-outcome test covers it
+    let Some(condition) = conditions.last() else {
+        return true;
+    };
+    let condition = words(condition);
+
+    let negated = |words: &[String]| words.iter().any(|w| w == "not");
+    negated(&condition) == negated(test) && condition.iter().all(|w| test.contains(w))
+}
+
+/// Split a label or test name into lowercase words, dropping filler words
+fn words(text: &str) -> Vec<String> {
+    let text = text.replace("msg.sender", "caller");
+    let mut words = Vec::new();
+
+    for part in text.split(|c: char| !c.is_ascii_alphanumeric()) {
+        let mut word = String::new();
+        let mut prev_lower = false;
+        for c in part.chars() {
+            // camelCase boundary
+            if c.is_ascii_uppercase() && prev_lower {
+                words.push(std::mem::take(&mut word));
+            }
+            prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+            word.push(c.to_ascii_lowercase());
+        }
+        words.push(word);
+    }
+
+    words.retain(|w| !w.is_empty() && !FILLER_WORDS.contains(&w.as_str()));
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaf(label: &str) -> TreeNode {
+        TreeNode::Leaf {
+            label: label.to_string(),
+        }
+    }
+
+    fn branch(label: &str, children: Vec<TreeNode>) -> TreeNode {
+        TreeNode::Branch {
+            label: label.to_string(),
+            source: None,
+            line: None,
+            children,
+        }
+    }
+
+    #[test]
+    fn test_words_split_test_names_and_labels() {
+        assert_eq!(
+            words("test_RevertWhen_CallerIsNotOwner"),
+            vec!["revert", "caller", "not", "owner"]
+        );
+        assert_eq!(
+            words("when msg.sender is not owner"),
+            vec!["caller", "not", "owner"]
+        );
+    }
+
+    #[test]
+    fn test_check_coverage_reports_gaps() {
+        let tree = TreeNode::Root {
+            name: "withdraw".to_string(),
+            children: vec![
+                branch("when amount is zero", vec![leaf("it should revert")]),
+                branch(
+                    "when amount is not zero",
+                    vec![
+                        branch("given paused is true", vec![leaf("it should revert")]),
+                        branch("given paused is false", vec![leaf("it should succeed")]),
+                    ],
+                ),
+            ],
+        };
+        let tests = vec![
+            "setUp".to_string(),
+            "test_RevertWhen_AmountIsZero".to_string(),
+            "test_GivenPausedIsFalse".to_string(),
+        ];
+
+        let report = check_coverage(&[tree], &tests);
+
+        assert_eq!(report.total, 3);
+        assert_eq!(report.covered(), 2);
+        assert_eq!(
+            report.gaps,
+            vec!["withdraw: when amount is not zero, given paused is true, it should revert"]
+        );
+    }
+}
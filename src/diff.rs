@@ -0,0 +1,161 @@
+//! Tree-aware comparison of a regenerated tree against the one on disk
+//!
+//! Children are matched by label rather than by position, so reordered branches
+//! compare equal. A node without a same-labeled sibling on the other side is
+//! reported once, as a whole, instead of once per leaf below it.
+
+use std::fmt;
+
+use crate::tree::TreeNode;
+
+/// Nodes only found in the old or the new trees, as label paths from the root name
+#[derive(Debug, Default, PartialEq)]
+pub struct TreeDiff {
+    pub removed: Vec<Vec<String>>,
+    pub added: Vec<Vec<String>>,
+}
+
+impl TreeDiff {
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.added.is_empty()
+    }
+}
+
+impl fmt::Display for TreeDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for path in &self.removed {
+            writeln!(f, "- {}", path.join(" > "))?;
+        }
+        for path in &self.added {
+            writeln!(f, "+ {}", path.join(" > "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Compare two sets of trees, such as the overload sections of a file. Trees are
+/// paired by root name
+pub fn diff_trees(old: &[TreeNode], new: &[TreeNode]) -> TreeDiff {
+    let mut diff = TreeDiff::default();
+    diff_children(old, new, &[], &mut diff);
+    diff
+}
+
+fn diff_children(old: &[TreeNode], new: &[TreeNode], path: &[String], diff: &mut TreeDiff) {
+    for node in old {
+        if !new.iter().any(|other| label(other) == label(node)) {
+            diff.removed.push(child_path(path, node));
+        }
+    }
+
+    for node in new {
+        let child = child_path(path, node);
+        match old.iter().find(|other| label(other) == label(node)) {
+            Some(previous) => diff_children(children(previous), children(node), &child, diff),
+            None => diff.added.push(child),
+        }
+    }
+}
+
+fn child_path(path: &[String], node: &TreeNode) -> Vec<String> {
+    let mut child = path.to_vec();
+    child.push(label(node).to_string());
+    child
+}
+
+fn label(node: &TreeNode) -> &str {
+    match node {
+        TreeNode::Root { name, .. } => name,
+        TreeNode::Branch { label, .. } | TreeNode::Leaf { label } => label,
+    }
+}
+
+fn children(node: &TreeNode) -> &[TreeNode] {
+    match node {
+        TreeNode::Root { children, .. } | TreeNode::Branch { children, .. } => children,
+        TreeNode::Leaf { .. } => &[],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::parse_trees;
+
+    fn diff(old: &str, new: &str) -> TreeDiff {
+        diff_trees(&parse_trees(old).unwrap(), &parse_trees(new).unwrap())
+    }
+
+    fn path(labels: &[&str]) -> Vec<String> {
+        labels.iter().map(|label| label.to_string()).collect()
+    }
+
+    #[test]
+    fn test_diff_names_changed_threshold_branches() {
+        let old = "\
+withdraw
+├── when amount is greater than 100
+│   └── it should revert
+└── when amount is at most 100
+    ├── given balance is zero
+    │   └── it should revert
+    └── given balance is not zero
+        └── it should succeed
+";
+        let new = "\
+withdraw
+├── when amount is greater than 200
+│   └── it should revert
+└── when amount is at most 200
+    ├── given balance is zero
+    │   └── it should revert
+    └── given balance is not zero
+        └── it should succeed
+";
+
+        let diff = diff(old, new);
+        assert_eq!(
+            diff.removed,
+            vec![
+                path(&["withdraw", "when amount is greater than 100"]),
+                path(&["withdraw", "when amount is at most 100"]),
+            ]
+        );
+        assert_eq!(
+            diff.added,
+            vec![
+                path(&["withdraw", "when amount is greater than 200"]),
+                path(&["withdraw", "when amount is at most 200"]),
+            ]
+        );
+        assert_eq!(
+            diff.to_string(),
+            "\
+- withdraw > when amount is greater than 100
+- withdraw > when amount is at most 100
++ withdraw > when amount is greater than 200
++ withdraw > when amount is at most 200
+"
+        );
+    }
+
+    #[test]
+    fn test_diff_ignores_reordered_branches() {
+        let old = "\
+pause
+├── when caller is not owner
+│   └── it should revert
+└── when caller is owner
+    └── it should succeed
+";
+        let new = "\
+pause
+├── when caller is owner
+│   └── it should succeed
+└── when caller is not owner
+    └── it should revert
+";
+
+        assert!(diff(old, new).is_empty());
+    }
+}
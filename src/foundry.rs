@@ -2,15 +2,23 @@
 
 #![allow(dead_code)]
 
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use thiserror::Error;
 use walkdir::WalkDir;
 
+/// Directories skipped by default when looking for sources: build output, broadcast
+/// logs and dependencies, which hold copies of contracts rather than the project's own
+pub const DEFAULT_IGNORED_DIRS: &[&str] = &["out", "cache", "broadcast", "node_modules", "lib"];
+
 #[derive(Error, Debug)]
 pub enum FoundryError {
-    #[error("Could not find foundry.toml in current directory or any parent")]
+    #[error(
+        "Could not find foundry.toml in current directory or any parent (use --no-foundry for other projects)"
+    )]
     ProjectNotFound,
 
     #[error("Failed to read foundry.toml: {0}")]
@@ -21,25 +29,60 @@ pub enum FoundryError {
 
     #[error("Contract '{0}' not found in project")]
     ContractNotFound(String),
+
+    #[error("Contract '{name}' not found in {}", .file.display())]
+    ContractNotInFile { name: String, file: PathBuf },
+
+    #[error(
+        "Contract '{name}' is defined in multiple files: {}. Use `path:{name}` to pick one",
+        list_paths(.candidates)
+    )]
+    AmbiguousContract {
+        name: String,
+        candidates: Vec<PathBuf>,
+    },
 }
 
-#[derive(Debug, Deserialize, Default)]
-struct FoundryConfig {
-    profile: Option<ProfileConfig>,
+fn list_paths(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|p| p.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Split a `prefix=target` remapping
+fn parse_remapping(remapping: &str) -> Option<(String, String)> {
+    let (prefix, target) = remapping.split_once('=')?;
+    Some((prefix.to_string(), target.to_string()))
 }
 
 #[derive(Debug, Deserialize, Default)]
-struct ProfileConfig {
-    default: Option<DefaultProfile>,
+struct FoundryConfig {
+    /// `[profile.<name>]` tables by name
+    #[serde(default)]
+    profile: HashMap<String, Profile>,
 }
 
 #[derive(Debug, Deserialize, Default)]
-struct DefaultProfile {
+struct Profile {
     src: Option<String>,
     lib: Option<Vec<String>>,
     remappings: Option<Vec<String>>,
 }
 
+impl Profile {
+    /// Fill settings this profile leaves unset from `base`, the way Foundry profiles
+    /// inherit from `default`
+    fn or(self, base: Profile) -> Profile {
+        Profile {
+            src: self.src.or(base.src),
+            lib: self.lib.or(base.lib),
+            remappings: self.remappings.or(base.remappings),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct FoundryProject {
     pub root: PathBuf,
@@ -49,18 +92,60 @@ pub struct FoundryProject {
 }
 
 impl FoundryProject {
-    /// Discover a Foundry project by searching for foundry.toml
-    pub fn discover() -> Result<Self, FoundryError> {
+    /// Project with explicit directories, for setups that aren't described by a
+    /// foundry.toml
+    pub fn new(
+        root: PathBuf,
+        src_dir: PathBuf,
+        lib_dirs: Vec<PathBuf>,
+        remappings: Vec<(String, String)>,
+    ) -> Self {
+        Self {
+            root,
+            src_dir,
+            lib_dirs,
+            remappings,
+        }
+    }
+
+    /// Project rooted at `root` without a foundry.toml, e.g. a Hardhat repo or a loose
+    /// directory of contracts. Sources are read from `src/` or `contracts/` when one
+    /// exists, else from `root` itself, and imports from `lib/` and `node_modules/`
+    pub fn plain(root: PathBuf) -> Self {
+        let src_dir = ["src", "contracts"]
+            .iter()
+            .map(|dir| root.join(dir))
+            .find(|dir| dir.is_dir())
+            .unwrap_or_else(|| root.clone());
+        let lib_dirs = vec![root.join("lib"), root.join("node_modules")];
+
+        Self::new(root, src_dir, lib_dirs, Vec::new())
+    }
+
+    /// Discover a Foundry project by searching for foundry.toml.
+    /// Settings come from `profile` (else `$FOUNDRY_PROFILE`, else `default`)
+    pub fn discover(profile: Option<&str>) -> Result<Self, FoundryError> {
         let current_dir = std::env::current_dir()?;
         let root = Self::find_project_root(&current_dir)?;
 
+        let env_profile = std::env::var("FOUNDRY_PROFILE").ok();
+        Self::load(root, profile.or(env_profile.as_deref()))
+    }
+
+    /// Load the project whose foundry.toml is in `root`, using the settings of `profile`
+    /// (falling back to `default` for anything it doesn't set)
+    pub fn load(root: PathBuf, profile: Option<&str>) -> Result<Self, FoundryError> {
         // Parse foundry.toml
         let config_path = root.join("foundry.toml");
         let config_content = fs::read_to_string(&config_path)?;
-        let config: FoundryConfig = toml::from_str(&config_content)?;
+        let mut config: FoundryConfig = toml::from_str(&config_content)?;
 
         // Extract configuration with defaults
-        let default_profile = config.profile.and_then(|p| p.default).unwrap_or_default();
+        let default_profile = config.profile.remove("default").unwrap_or_default();
+        let default_profile = match profile.and_then(|name| config.profile.remove(name)) {
+            Some(selected) => selected.or(default_profile),
+            None => default_profile,
+        };
 
         let src_dir = root.join(default_profile.src.unwrap_or_else(|| "src".to_string()));
 
@@ -71,26 +156,30 @@ impl FoundryProject {
             .map(|l| root.join(l))
             .collect();
 
-        let remappings = default_profile
+        let mut remappings: Vec<(String, String)> = default_profile
             .remappings
             .unwrap_or_default()
-            .into_iter()
-            .filter_map(|r| {
-                let parts: Vec<&str> = r.splitn(2, '=').collect();
-                if parts.len() == 2 {
-                    Some((parts[0].to_string(), parts[1].to_string()))
-                } else {
-                    None
-                }
-            })
+            .iter()
+            .filter_map(|r| parse_remapping(r))
             .collect();
 
-        Ok(Self {
-            root,
-            src_dir,
-            lib_dirs,
-            remappings,
-        })
+        // remappings.txt entries come after foundry.toml's, which win for the same prefix
+        let remappings_path = root.join("remappings.txt");
+        if remappings_path.exists() {
+            for line in fs::read_to_string(&remappings_path)?.lines() {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                if let Some((prefix, target)) = parse_remapping(line) {
+                    if !remappings.iter().any(|(existing, _)| *existing == prefix) {
+                        remappings.push((prefix, target));
+                    }
+                }
+            }
+        }
+
+        Ok(Self::new(root, src_dir, lib_dirs, remappings))
     }
 
     fn find_project_root(start: &Path) -> Result<PathBuf, FoundryError> {
@@ -119,46 +208,78 @@ impl FoundryProject {
         &self.remappings
     }
 
-    /// Find a contract file by contract name
-    pub fn find_contract(&self, contract_name: &str) -> Result<PathBuf, FoundryError> {
-        // First, look for a file with the exact contract name
-        let expected_filename = format!("{}.sol", contract_name);
-
-        // Search in src directory
-        for entry in WalkDir::new(&self.src_dir)
-            .into_iter()
-            .filter_map(|e| e.ok())
+    /// Find the file of a contract named by a target. An explicit file from a
+    /// `src/Vault.sol:Vault` target is taken relative to the project root, as in
+    /// Foundry; without one the contract is looked up by name among the files `filter`
+    /// keeps
+    pub fn locate_contract(
+        &self,
+        file: Option<&Path>,
+        contract_name: &str,
+        filter: &SourceFilter,
+    ) -> Result<PathBuf, FoundryError> {
+        let Some(file) = file else {
+            return self.find_contract(contract_name, filter);
+        };
+
+        let path = self.root.join(file);
+        if fs::read_to_string(&path).is_ok_and(|content| defines_contract(&content, contract_name))
         {
-            if entry.file_type().is_file() {
-                if let Some(name) = entry.file_name().to_str() {
-                    if name == expected_filename {
-                        return Ok(entry.path().to_path_buf());
-                    }
-                }
-            }
+            Ok(path)
+        } else {
+            Err(FoundryError::ContractNotInFile {
+                name: contract_name.to_string(),
+                file: path,
+            })
         }
+    }
+
+    /// Find a contract file by contract name, among the files `filter` keeps.
+    ///
+    /// A file named `<Contract>.sol` wins over other files that define the contract.
+    /// If several files remain, the lookup fails listing all of them, sorted by path,
+    /// instead of depending on directory traversal order.
+    pub fn find_contract(
+        &self,
+        contract_name: &str,
+        filter: &SourceFilter,
+    ) -> Result<PathBuf, FoundryError> {
+        let expected_filename = format!("{}.sol", contract_name);
+
+        let mut sol_files = self.find_contracts(filter);
+        sol_files.sort();
+
+        // First, look for files with the exact contract name
+        let by_filename: Vec<PathBuf> = sol_files
+            .iter()
+            .filter(|path| path.file_name().and_then(|n| n.to_str()) == Some(&expected_filename))
+            .cloned()
+            .collect();
 
         // If not found by filename, search file contents for contract definition
-        for entry in WalkDir::new(&self.src_dir)
-            .into_iter()
-            .filter_map(|e| e.ok())
-        {
-            if entry.file_type().is_file() {
-                if let Some(ext) = entry.path().extension() {
-                    if ext == "sol" {
-                        if let Ok(content) = fs::read_to_string(entry.path()) {
-                            // Simple pattern match for contract definition
-                            let pattern = format!("contract {}", contract_name);
-                            if content.contains(&pattern) {
-                                return Ok(entry.path().to_path_buf());
-                            }
-                        }
-                    }
-                }
-            }
+        let candidates = if by_filename.is_empty() {
+            sol_files
+                .into_iter()
+                .filter(|path| {
+                    fs::read_to_string(path)
+                        .is_ok_and(|content| defines_contract(&content, contract_name))
+                })
+                .collect()
+        } else {
+            by_filename
+        };
+
+        if candidates.len() > 1 {
+            return Err(FoundryError::AmbiguousContract {
+                name: contract_name.to_string(),
+                candidates,
+            });
         }
 
-        Err(FoundryError::ContractNotFound(contract_name.to_string()))
+        candidates
+            .into_iter()
+            .next()
+            .ok_or_else(|| FoundryError::ContractNotFound(contract_name.to_string()))
     }
 
     /// Resolve an import path using remappings
@@ -195,12 +316,38 @@ impl FoundryProject {
         None
     }
 
-    /// Find all Solidity contract files in the src directory
+    /// Find all Solidity contract files in the src directory, skipping the
+    /// [`DEFAULT_IGNORED_DIRS`] and whatever `.gitignore` lists
     pub fn find_all_contracts(&self) -> Vec<PathBuf> {
+        self.find_contracts(&SourceFilter::default())
+    }
+
+    /// Find the Solidity contract files in the src directory that `filter` keeps
+    pub fn find_contracts(&self, filter: &SourceFilter) -> Vec<PathBuf> {
+        let gitignore = if filter.gitignore {
+            read_gitignore(&self.root)
+        } else {
+            GlobSet::empty()
+        };
         let mut contracts = Vec::new();
 
         for entry in WalkDir::new(&self.src_dir)
             .into_iter()
+            .filter_entry(|entry| {
+                // Never skip the src directory itself, even when it's named `lib`
+                entry.depth() == 0
+                    || !(entry.file_type().is_dir()
+                        && filter
+                            .ignored_dirs
+                            .iter()
+                            .any(|dir| entry.file_name() == dir.as_str())
+                        || gitignore.is_match(
+                            entry
+                                .path()
+                                .strip_prefix(&self.root)
+                                .unwrap_or(entry.path()),
+                        ))
+            })
             .filter_map(|e| e.ok())
         {
             if entry.file_type().is_file() {
@@ -215,3 +362,62 @@ impl FoundryProject {
         contracts
     }
 }
+
+/// Which files below the src directory are searched for contracts
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceFilter {
+    /// Directory names skipped wherever they appear
+    pub ignored_dirs: Vec<String>,
+    /// Also skip the paths listed in the project's `.gitignore`
+    pub gitignore: bool,
+}
+
+impl Default for SourceFilter {
+    fn default() -> Self {
+        Self {
+            ignored_dirs: DEFAULT_IGNORED_DIRS.iter().map(|d| d.to_string()).collect(),
+            gitignore: true,
+        }
+    }
+}
+
+/// Patterns of the `.gitignore` in `root`, matched against paths relative to it.
+/// Negated (`!`) patterns aren't supported and are skipped
+fn read_gitignore(root: &Path) -> GlobSet {
+    let content = fs::read_to_string(root.join(".gitignore")).unwrap_or_default();
+    let mut builder = GlobSetBuilder::new();
+
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with('!') {
+            continue;
+        }
+
+        // A pattern with a slash is relative to the root, any other matches at any depth
+        let pattern = line.trim_end_matches('/');
+        let globs = match pattern.strip_prefix('/') {
+            Some(anchored) => [anchored.to_string(), format!("{}/**", anchored)],
+            None if pattern.contains('/') => [pattern.to_string(), format!("{}/**", pattern)],
+            None => [format!("**/{}", pattern), format!("**/{}/**", pattern)],
+        };
+        for glob in globs {
+            if let Ok(glob) = GlobBuilder::new(&glob).literal_separator(true).build() {
+                builder.add(glob);
+            }
+        }
+    }
+
+    builder.build().unwrap_or_else(|_| GlobSet::empty())
+}
+
+/// Simple pattern match for a contract definition, e.g. `contract Vault` but not
+/// `contract VaultFactory`
+fn defines_contract(content: &str, contract_name: &str) -> bool {
+    let pattern = format!("contract {}", contract_name);
+
+    content.match_indices(&pattern).any(|(start, _)| {
+        content[start + pattern.len()..]
+            .chars()
+            .next()
+            .is_none_or(|c| !(c.is_alphanumeric() || c == '_' || c == '$'))
+    })
+}
@@ -1,6 +1,113 @@
+//! Acacia generates Branching Tree Technique (BTT) test trees for Solidity functions.
+//!
+//! Besides the `acacia` binary, the crate can be used as a library. The main types
+//! are re-exported at the top level:
+//!
+//! - [`FoundryProject`] locates sources and resolves imports
+//! - [`SolarParser`] extracts the [`BranchPoint`]s of a contract's functions
+//! - [`TreeBuilder`] turns branch points into a [`TreeNode`] tree
+//! - [`render_to_string`] prints a tree in the `.tree` format
+//!
+//! [`generate_trees_for_contract`] runs the whole pipeline for one contract.
+
 pub mod analysis;
 pub mod cli;
+pub mod config;
+pub mod coverage;
+pub mod diff;
 pub mod foundry;
 pub mod output;
 pub mod parser;
+pub mod summary;
 pub mod tree;
+
+use std::collections::HashMap;
+use thiserror::Error;
+
+pub use analysis::{
+    BinaryOp, BranchPoint, CheckKind, ConditionContext, ConditionExpr, Warning, WarningKind,
+};
+use foundry::SourceFilter;
+pub use foundry::{FoundryError, FoundryProject};
+pub use output::{parse_tree, render_to_string};
+pub use parser::{FunctionContext, ParserError, SolarParser};
+pub use tree::{LabelConfig, TreeBuilder, TreeError, TreeNode, TreeOptions};
+
+/// Errors from the library entry points
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Foundry(#[from] FoundryError),
+
+    #[error(transparent)]
+    Parser(#[from] ParserError),
+
+    #[error(transparent)]
+    Tree(#[from] TreeError),
+}
+
+/// Trees of a contract's functions, with the warnings raised while parsing them
+#[derive(Debug)]
+pub struct ContractTrees {
+    /// Each function's name, or `name(signature)` when overloaded, with its tree
+    pub trees: Vec<(String, TreeNode)>,
+    /// Constructs that were skipped, such as modifiers that couldn't be resolved
+    pub warnings: Vec<Warning>,
+}
+
+/// Build the trees of every public and external function of a contract, in declaration
+/// order. Each tree is paired with its function's name, or `name(signature)` when the
+/// function is overloaded.
+///
+/// ```
+/// use acacia::{generate_trees_for_contract, render_to_string, FoundryProject};
+///
+/// # let testdata = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata");
+/// # let project = FoundryProject {
+/// #     root: testdata.clone(),
+/// #     src_dir: testdata,
+/// #     lib_dirs: vec![],
+/// #     remappings: vec![],
+/// # };
+/// // `project` would usually come from `FoundryProject::discover(None)?`
+/// let contract = generate_trees_for_contract(&project, "AllFunctions")?;
+///
+/// let names: Vec<&str> = contract.trees.iter().map(|(name, _)| name.as_str()).collect();
+/// assert_eq!(names, ["externalFunc", "publicFunc", "pausableFunc"]);
+/// assert!(render_to_string(&contract.trees[0].1).starts_with("externalFunc\n"));
+/// assert!(contract.warnings.is_empty());
+/// # Ok::<(), acacia::Error>(())
+/// ```
+pub fn generate_trees_for_contract(
+    project: &FoundryProject,
+    contract: &str,
+) -> Result<ContractTrees, Error> {
+    let file = project.find_contract(contract, &SourceFilter::default())?;
+    let functions = SolarParser::new(project).parse_all_public_functions(&file, contract)?;
+
+    let mut overloads: HashMap<&str, usize> = HashMap::new();
+    for function in &functions {
+        *overloads
+            .entry(function.function_name.as_str())
+            .or_default() += 1;
+    }
+
+    let trees = functions
+        .iter()
+        .map(|function| {
+            let name = if overloads[function.function_name.as_str()] > 1 {
+                format!("{}({})", function.function_name, function.signature)
+            } else {
+                function.function_name.clone()
+            };
+            let tree = TreeBuilder::build(&name, function.branch_points.clone())?;
+            Ok((name, tree))
+        })
+        .collect::<Result<_, Error>>()?;
+    let warnings = functions
+        .into_iter()
+        .flat_map(|function| function.warnings)
+        .collect();
+
+    Ok(ContractTrees { trees, warnings })
+}
@@ -1,5 +1,5 @@
-use clap::Parser;
 use acacia::cli::Cli;
+use clap::Parser;
 
 fn main() {
     let cli = Cli::parse();
@@ -9,4 +9,3 @@ fn main() {
         std::process::exit(1);
     }
 }
-
@@ -1,16 +1,21 @@
 //! BTT tree output rendering
 
+use serde::Serialize;
 use std::fs;
-use std::io::Write;
+use std::io::{IsTerminal, Write};
 use std::path::Path;
 use thiserror::Error;
 
-use crate::tree::TreeNode;
+use crate::analysis::{BranchPoint, CheckKind, ConditionContext};
+use crate::tree::{LabelConfig, TreeNode};
 
 #[derive(Error, Debug)]
 pub enum OutputError {
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
+
+    #[error("Malformed tree at line {line}: {reason}")]
+    ParseError { line: usize, reason: String },
 }
 
 /// Render a tree to BTT format and write to file
@@ -28,46 +33,716 @@ pub fn render_tree(tree: &TreeNode, output_path: &Path) -> Result<(), OutputErro
     Ok(())
 }
 
+/// Characters drawing the structure of a rendered tree
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Connectors {
+    /// Before a node with siblings after it
+    pub fork: &'static str,
+    /// Before the last of its siblings
+    pub last: &'static str,
+    /// Below a node with siblings after it, leading down to them
+    pub pipe: &'static str,
+    /// Below the last of its siblings
+    pub blank: &'static str,
+}
+
+impl Connectors {
+    /// Box-drawing characters, as bulloak writes them
+    pub const UNICODE: Connectors = Connectors {
+        fork: "├── ",
+        last: "└── ",
+        pipe: "│   ",
+        blank: "    ",
+    };
+
+    /// Plain ASCII, for terminals and CI logs that mangle box-drawing characters
+    pub const ASCII: Connectors = Connectors {
+        fork: "|-- ",
+        last: "`-- ",
+        pipe: "|   ",
+        blank: "    ",
+    };
+
+    fn connector(&self, is_last: bool) -> &'static str {
+        if is_last {
+            self.last
+        } else {
+            self.fork
+        }
+    }
+}
+
+impl Default for Connectors {
+    fn default() -> Self {
+        Connectors::UNICODE
+    }
+}
+
 /// Render a tree to a string in BTT format
 pub fn render_to_string(tree: &TreeNode) -> String {
+    render_styled(tree, Connectors::UNICODE, false, false)
+}
+
+/// Render a tree with each condition's Solidity source as an aligned trailing comment
+pub fn render_annotated_to_string(tree: &TreeNode) -> String {
+    render_styled(tree, Connectors::UNICODE, true, false)
+}
+
+/// Render a tree with the line each condition is checked on as an aligned trailing
+/// comment (`// L42`), followed by the condition's Solidity source when `sources` is set
+/// (`// L42: require(amount > 0)`)
+pub fn render_with_lines(tree: &TreeNode, sources: bool) -> String {
+    render_styled(tree, Connectors::UNICODE, sources, true)
+}
+
+/// Render a tree drawn with `connectors`, with the Solidity source (`sources`) and the
+/// line (`lines`) of each condition as an aligned trailing comment
+pub fn render_styled(
+    tree: &TreeNode,
+    connectors: Connectors,
+    sources: bool,
+    lines: bool,
+) -> String {
+    let comment = |notes: Notes| {
+        let line = notes
+            .line
+            .filter(|_| lines)
+            .map(|line| format!("L{}", line));
+        match (line, notes.source.filter(|_| sources)) {
+            (Some(line), Some(source)) => Some(format!("{}: {}", line, source)),
+            (line, source) => line.or(source.map(str::to_string)),
+        }
+    };
+
+    let mut nodes = Vec::new();
+    render_node(tree, &mut nodes, "", true, &connectors);
+
+    let lines: Vec<(String, Option<String>)> = nodes
+        .into_iter()
+        .map(|(line, notes)| (line, notes.and_then(comment)))
+        .collect();
+
+    let width = lines
+        .iter()
+        .filter(|(_, comment)| comment.is_some())
+        .map(|(line, _)| line.chars().count())
+        .max()
+        .unwrap_or(0);
+
     let mut output = String::new();
-    render_node(tree, &mut output, "", true);
+    for (line, comment) in lines {
+        output.push_str(&line);
+        if let Some(comment) = comment {
+            let padding = width - line.chars().count() + 2;
+            output.push_str(&" ".repeat(padding));
+            output.push_str("// ");
+            output.push_str(&comment);
+        }
+        output.push('\n');
+    }
     output
 }
 
-fn render_node(node: &TreeNode, output: &mut String, prefix: &str, is_last: bool) {
-    match node {
+/// Root name accepted by bulloak: the function name (and overload signature) as an
+/// identifier with a `_Test` suffix, e.g. `transfer(address,uint256)` becomes
+/// `transfer_address_uint256_Test`
+pub fn bulloak_root(name: &str) -> String {
+    let words: Vec<&str> = name
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .filter(|word| !word.is_empty())
+        .collect();
+
+    format!("{}_Test", words.join("_"))
+}
+
+/// Render a tree as a Mermaid flowchart, with revert and success leaves styled apart.
+/// `labels` tells reverting leaves from the others
+pub fn render_to_mermaid(tree: &TreeNode, labels: &LabelConfig) -> String {
+    let mut nodes = Vec::new();
+    let mut edges = Vec::new();
+    mermaid_node(tree, None, labels, &mut nodes, &mut edges);
+
+    let mut output = String::from("flowchart TD\n");
+    for line in nodes.iter().chain(edges.iter()) {
+        output.push_str("    ");
+        output.push_str(line);
+        output.push('\n');
+    }
+    output.push_str("    classDef revert fill:#fdd,stroke:#c00\n");
+    output.push_str("    classDef success fill:#dfd,stroke:#080\n");
+    output
+}
+
+/// Declare a node (and its edge from `parent`), then recurse into its children
+fn mermaid_node(
+    node: &TreeNode,
+    parent: Option<&str>,
+    labels: &LabelConfig,
+    nodes: &mut Vec<String>,
+    edges: &mut Vec<String>,
+) {
+    let id = format!("n{}", nodes.len());
+
+    let children: &[TreeNode] = match node {
         TreeNode::Root { name, children } => {
-            output.push_str(name);
+            nodes.push(format!("{}[\"{}\"]", id, mermaid_escape(name)));
+            children
+        }
+        TreeNode::Branch {
+            label, children, ..
+        } => {
+            nodes.push(format!("{}(\"{}\")", id, mermaid_escape(label)));
+            children
+        }
+        TreeNode::Leaf { label } => {
+            let class = if labels.is_revert(label) {
+                "revert"
+            } else {
+                "success"
+            };
+            nodes.push(format!(
+                "{}([\"{}\"]):::{}",
+                id,
+                mermaid_escape(label),
+                class
+            ));
+            &[]
+        }
+    };
+
+    if let Some(parent) = parent {
+        edges.push(format!("{} --> {}", parent, id));
+    }
+
+    for child in children {
+        mermaid_node(child, Some(&id), labels, nodes, edges);
+    }
+}
+
+/// Whether trees printed to stdout should be colored: stdout is a terminal and
+/// `NO_COLOR` isn't set
+pub fn use_color() -> bool {
+    std::io::stdout().is_terminal()
+        && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+}
+
+/// Color rendered trees for a terminal: reverting leaves red, other leaves green and
+/// branch labels dimmed. Only ANSI codes are wrapped around the labels, so the text
+/// stays the same, and with `color` off the content is returned as is
+pub fn colorize(content: &str, color: bool, labels: &LabelConfig) -> String {
+    if !color {
+        return content.to_string();
+    }
+
+    // Each line with its ending, so that the output ends its lines like the input
+    let lines: Vec<(&str, &str)> = content
+        .split_inclusive('\n')
+        .map(|raw| {
+            let line = raw.trim_end_matches(['\n', '\r']);
+            (line, &raw[line.len()..])
+        })
+        .collect();
+    let mut output = String::new();
+    for (i, (line, ending)) in lines.iter().enumerate() {
+        let node = parse_tree_line(line, i + 1);
+        let start = ["── ", "-- "]
+            .iter()
+            .find_map(|arrow| line.find(arrow).map(|index| index + arrow.len()));
+
+        match (node, start) {
+            (Some(node), Some(start)) => {
+                let is_leaf = lines
+                    .get(i + 1)
+                    .and_then(|(next, _)| parse_tree_line(next, i + 2))
+                    .is_none_or(|next| next.depth <= node.depth);
+                let style = match (is_leaf, labels.is_revert(&node.label)) {
+                    (true, true) => "\x1b[31m",
+                    (true, false) => "\x1b[32m",
+                    (false, _) => "\x1b[2m",
+                };
+                let end = start + node.label.len();
+                output.push_str(&line[..start]);
+                output.push_str(style);
+                output.push_str(&line[start..end]);
+                output.push_str("\x1b[0m");
+                output.push_str(&line[end..]);
+            }
+            _ => output.push_str(line),
+        }
+        output.push_str(ending);
+    }
+    output
+}
+
+/// Escape a label for use inside a quoted Mermaid node text
+fn mermaid_escape(label: &str) -> String {
+    label
+        .replace('"', "#quot;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;")
+}
+
+/// Render the `assert`ed conditions of a function as candidate invariant properties
+pub fn render_invariants(
+    name: &str,
+    branch_points: &[BranchPoint],
+    connectors: Connectors,
+) -> String {
+    let invariants: Vec<&BranchPoint> = branch_points
+        .iter()
+        .filter(|bp| bp.kind == CheckKind::Assert)
+        .collect();
+
+    let mut output = String::new();
+    output.push_str(name);
+    output.push('\n');
+
+    for (i, bp) in invariants.iter().enumerate() {
+        output.push_str(connectors.connector(i == invariants.len() - 1));
+        output.push_str(&format!("invariant: {}", bp.condition));
+        output.push('\n');
+    }
+    output
+}
+
+/// A revert condition as listed by `--format audit-json`
+#[derive(Debug, Serialize)]
+struct AuditCheck {
+    condition: String,
+    /// `storage` or `external`
+    context: &'static str,
+    is_if_revert: bool,
+    is_external_call: bool,
+    /// Line the check starts on, when known
+    line: Option<usize>,
+}
+
+/// The revert conditions of one function
+#[derive(Debug, Serialize)]
+struct AuditFunction<'a> {
+    function: &'a str,
+    checks: Vec<AuditCheck>,
+}
+
+/// Render the revert conditions of functions as a JSON array for audit tooling, with
+/// the line each check starts on. The checks in both arms of an `if`/`else` are listed
+/// in turn. Guards that stop without reverting and `assert(false)`, which has no
+/// condition, are left out
+pub fn render_audit_json(functions: &[(String, &[BranchPoint])]) -> String {
+    let functions: Vec<AuditFunction> = functions
+        .iter()
+        .map(|(name, branch_points)| {
+            let mut checks = Vec::new();
+            audit_checks(branch_points, &mut checks);
+            AuditFunction {
+                function: name,
+                checks,
+            }
+        })
+        .collect();
+
+    let mut output = serde_json::to_string_pretty(&functions).unwrap_or_default();
+    output.push('\n');
+    output
+}
+
+fn audit_checks(branch_points: &[BranchPoint], checks: &mut Vec<AuditCheck>) {
+    for bp in branch_points {
+        if let Some(arms) = &bp.arms {
+            audit_checks(&arms.then_checks, checks);
+            audit_checks(&arms.else_checks, checks);
+            continue;
+        }
+        if matches!(
+            bp.kind,
+            CheckKind::EarlyReturn
+                | CheckKind::Continue
+                | CheckKind::Break
+                | CheckKind::Unreachable
+        ) {
+            continue;
+        }
+
+        checks.push(AuditCheck {
+            condition: bp.condition.to_string(),
+            context: match bp.context.overall() {
+                ConditionContext::Storage => "storage",
+                _ => "external",
+            },
+            is_if_revert: bp.is_if_revert,
+            is_external_call: bp.is_external_call,
+            line: bp.span.map(|(line, _)| line),
+        });
+    }
+}
+
+/// Render trees as a Solidity test file, like `bulloak scaffold`: a contract per tree
+/// with an empty test for each condition ending in leaves, and a modifier for each
+/// condition with conditions nested below it. Leaves become comments in their test, and
+/// `labels` tells which of them revert
+pub fn render_scaffold(trees: &[TreeNode], labels: &LabelConfig) -> String {
+    let mut output =
+        String::from("// SPDX-License-Identifier: UNLICENSED\npragma solidity ^0.8.0;\n");
+    for tree in trees {
+        if let TreeNode::Root { name, children } = tree {
             output.push('\n');
+            output.push_str(&scaffold_contract(name, children, labels));
+        }
+    }
+    output
+}
+
+/// Test contract for one tree, named like a bulloak root (`withdraw_Test`)
+fn scaffold_contract(name: &str, children: &[TreeNode], labels: &LabelConfig) -> String {
+    let mut scaffold = Scaffold::default();
+    scaffold.visit(name, children, labels, &mut Vec::new(), true);
+
+    let contract = if name.ends_with("_Test") {
+        name.to_string()
+    } else {
+        bulloak_root(name)
+    };
+
+    let blocks: Vec<String> = scaffold
+        .modifiers
+        .iter()
+        .map(|modifier| format!("    modifier {}() {{\n        _;\n    }}\n", modifier))
+        .chain(scaffold.tests.iter().map(|(_, test)| test.clone()))
+        .collect();
+    format!("contract {} {{\n{}}}\n", contract, blocks.join("\n"))
+}
+
+/// Modifiers and tests of a scaffolded contract, in tree order
+#[derive(Default)]
+struct Scaffold {
+    modifiers: Vec<String>,
+    /// Test function names with their rendered function
+    tests: Vec<(String, String)>,
+}
+
+impl Scaffold {
+    /// Add the test for the leaves directly below `label`, then recurse into the
+    /// nested conditions with `label`'s modifier applied
+    fn visit(
+        &mut self,
+        label: &str,
+        children: &[TreeNode],
+        labels: &LabelConfig,
+        applied: &mut Vec<String>,
+        is_root: bool,
+    ) {
+        let leaves: Vec<&str> = children
+            .iter()
+            .filter_map(|child| match child {
+                TreeNode::Leaf { label } => Some(label.as_str()),
+                _ => None,
+            })
+            .collect();
+
+        if !leaves.is_empty() {
+            let reverts = leaves.iter().any(|leaf| labels.is_revert(leaf));
+            let name = self.unique_test_name(test_name(label, reverts));
+
+            let mut test = format!("    function {}() external", name);
+            for modifier in applied.iter() {
+                test.push(' ');
+                test.push_str(modifier);
+            }
+            test.push_str(" {\n");
+            for leaf in leaves {
+                test.push_str(&format!("        // {}\n", leaf));
+            }
+            test.push_str("    }\n");
+            self.tests.push((name, test));
+        }
+
+        let has_branches = children
+            .iter()
+            .any(|child| matches!(child, TreeNode::Branch { .. }));
+        let nested = has_branches && !is_root;
+        if nested {
+            let modifier = modifier_name(label);
+            if !self.modifiers.contains(&modifier) {
+                self.modifiers.push(modifier.clone());
+            }
+            applied.push(modifier);
+        }
+
+        for child in children {
+            if let TreeNode::Branch {
+                label, children, ..
+            } = child
+            {
+                self.visit(label, children, labels, applied, false);
+            }
+        }
+
+        if nested {
+            applied.pop();
+        }
+    }
+
+    /// `name`, numbered when the same condition already has a test elsewhere in the tree
+    fn unique_test_name(&self, name: String) -> String {
+        let taken = |candidate: &str| self.tests.iter().any(|(test, _)| test == candidate);
+        if !taken(&name) {
+            return name;
+        }
+        (2..)
+            .map(|n| format!("{}_{}", name, n))
+            .find(|candidate| !taken(candidate))
+            .unwrap()
+    }
+}
+
+/// Words of a label usable in an identifier, each capitalized: `msg.sender is owner`
+/// gives `Msg`, `Sender`, `Is`, `Owner`
+fn identifier_words(label: &str) -> Vec<String> {
+    label
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            let first = chars.next().unwrap_or_default();
+            first.to_ascii_uppercase().to_string() + chars.as_str()
+        })
+        .collect()
+}
+
+/// Test function name for a condition, as bulloak names them:
+/// `when amount is zero` gives `test_WhenAmountIsZero`, or `test_RevertWhen_AmountIsZero`
+/// when its leaves revert
+fn test_name(label: &str, reverts: bool) -> String {
+    let words = identifier_words(label);
+    let (keyword, rest) = match words.split_first() {
+        Some((keyword, rest)) => (keyword.as_str(), rest.concat()),
+        None => ("", String::new()),
+    };
+
+    match (reverts, rest.is_empty()) {
+        (true, false) => format!("test_Revert{}_{}", keyword, rest),
+        (true, true) => format!("test_Revert{}", keyword),
+        (false, _) => format!("test_{}{}", keyword, rest),
+    }
+}
+
+/// Modifier name for a condition: `when amount is not zero` gives `whenAmountIsNotZero`
+fn modifier_name(label: &str) -> String {
+    let name = identifier_words(label).concat();
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+        None => name,
+    }
+}
+
+/// Read a single tree rendered by `render_to_string` (or `render_annotated_to_string`)
+/// back into nodes. This is the inverse of rendering, except that branch sources only
+/// come back from annotated output
+pub fn parse_tree(content: &str) -> Result<TreeNode, OutputError> {
+    let mut trees = parse_trees(content)?;
+    match trees.len() {
+        1 => Ok(trees.remove(0)),
+        count => Err(OutputError::ParseError {
+            line: 1,
+            reason: format!("expected one tree, found {}", count),
+        }),
+    }
+}
+
+/// Read every tree in a file, such as the blank-line separated trees of overloads.
+/// `//` header lines above a tree are skipped
+pub fn parse_trees(content: &str) -> Result<Vec<TreeNode>, OutputError> {
+    let mut trees = Vec::new();
+    let mut root: Option<String> = None;
+    let mut items = Vec::new();
+
+    for (i, line) in content.lines().enumerate() {
+        let line_number = i + 1;
+        if line.trim().is_empty() {
+            if let Some(name) = root.take() {
+                trees.push(build_tree(name, &std::mem::take(&mut items))?);
+            }
+            continue;
+        }
+
+        if root.is_none() {
+            if !line.starts_with("//") {
+                root = Some(line.trim_end().to_string());
+            }
+            continue;
+        }
+
+        let item = parse_tree_line(line, line_number).ok_or_else(|| OutputError::ParseError {
+            line: line_number,
+            reason: format!("expected a `├──` or `└──` node, found `{}`", line.trim()),
+        })?;
+        items.push(item);
+    }
+
+    if let Some(name) = root {
+        trees.push(build_tree(name, &items)?);
+    }
+    Ok(trees)
+}
+
+/// A rendered node line
+struct TreeLine {
+    number: usize,
+    depth: usize,
+    label: String,
+    /// Trailing `// ...` annotation, without its `L42` line
+    source: Option<String>,
+    /// Source line from a trailing `// L42` annotation
+    line: Option<usize>,
+}
+
+fn parse_tree_line(line: &str, number: usize) -> Option<TreeLine> {
+    // Trees drawn with either set of connectors read back the same
+    let styles = [Connectors::UNICODE, Connectors::ASCII];
+    let mut rest = line;
+    let mut depth = 0;
+    while let Some(inner) = styles.iter().find_map(|style| {
+        rest.strip_prefix(style.pipe)
+            .or_else(|| rest.strip_prefix(style.blank))
+    }) {
+        rest = inner;
+        depth += 1;
+    }
+
+    let rest = styles.iter().find_map(|style| {
+        rest.strip_prefix(style.fork)
+            .or_else(|| rest.strip_prefix(style.last))
+    })?;
+    let (label, (line, source)) = match rest.split_once("  // ") {
+        Some((label, comment)) => (label, split_line_note(comment)),
+        None => (rest, (None, None)),
+    };
+
+    Some(TreeLine {
+        number,
+        depth,
+        label: label.trim_end().to_string(),
+        source: source.map(str::to_string),
+        line,
+    })
+}
+
+/// Split a trailing comment written by `--with-lines` (`L42` or `L42: require(...)`)
+/// into its line and source. Any other comment is all source
+fn split_line_note(comment: &str) -> (Option<usize>, Option<&str>) {
+    let Some(rest) = comment.strip_prefix('L') else {
+        return (None, Some(comment));
+    };
+    let (digits, source) = match rest.split_once(": ") {
+        Some((digits, source)) => (digits, Some(source)),
+        None => (rest, None),
+    };
+    match digits.parse() {
+        Ok(line) => (Some(line), source),
+        Err(_) => (None, Some(comment)),
+    }
+}
+
+/// Nest the node lines below a root
+fn build_tree(name: String, lines: &[TreeLine]) -> Result<TreeNode, OutputError> {
+    let mut index = 0;
+    let children = parse_children(lines, &mut index, 0);
+
+    // Anything left over is indented deeper than its parent allows
+    if let Some(line) = lines.get(index) {
+        return Err(OutputError::ParseError {
+            line: line.number,
+            reason: format!("`{}` is nested too deep", line.label),
+        });
+    }
+
+    Ok(TreeNode::Root { name, children })
+}
+
+/// Nodes at `depth` starting at `index`, with everything nested below them. A node
+/// without children is a leaf
+fn parse_children(lines: &[TreeLine], index: &mut usize, depth: usize) -> Vec<TreeNode> {
+    let mut nodes = Vec::new();
+    while let Some(line) = lines.get(*index) {
+        if line.depth != depth {
+            break;
+        }
+        *index += 1;
+
+        let children = parse_children(lines, index, depth + 1);
+        nodes.push(if children.is_empty() {
+            TreeNode::Leaf {
+                label: line.label.clone(),
+            }
+        } else {
+            TreeNode::Branch {
+                label: line.label.clone(),
+                source: line.source.clone(),
+                line: line.line,
+                children,
+            }
+        });
+    }
+    nodes
+}
+
+/// What the rendered line of a branch can mention in a trailing comment
+#[derive(Debug, Clone, Copy)]
+struct Notes<'t> {
+    source: Option<&'t str>,
+    line: Option<usize>,
+}
+
+/// Flatten a node into rendered lines, paired with the source of branch conditions
+fn render_node<'t>(
+    node: &'t TreeNode,
+    lines: &mut Vec<(String, Option<Notes<'t>>)>,
+    prefix: &str,
+    is_last: bool,
+    connectors: &Connectors,
+) {
+    match node {
+        TreeNode::Root { name, children } => {
+            lines.push((name.clone(), None));
 
             for (i, child) in children.iter().enumerate() {
                 let is_last_child = i == children.len() - 1;
-                render_node(child, output, "", is_last_child);
+                render_node(child, lines, "", is_last_child, connectors);
             }
         }
 
-        TreeNode::Branch { label, children } => {
-            let connector = if is_last { "└── " } else { "├── " };
-            output.push_str(prefix);
-            output.push_str(connector);
-            output.push_str(label);
-            output.push('\n');
+        TreeNode::Branch {
+            label,
+            source,
+            line,
+            children,
+        } => {
+            let connector = connectors.connector(is_last);
+            lines.push((
+                format!("{}{}{}", prefix, connector, label),
+                Some(Notes {
+                    source: source.as_deref(),
+                    line: *line,
+                }),
+            ));
 
-            let child_prefix = format!("{}{}", prefix, if is_last { "    " } else { "│   " });
+            let below = if is_last {
+                connectors.blank
+            } else {
+                connectors.pipe
+            };
+            let child_prefix = format!("{}{}", prefix, below);
 
             for (i, child) in children.iter().enumerate() {
                 let is_last_child = i == children.len() - 1;
-                render_node(child, output, &child_prefix, is_last_child);
+                render_node(child, lines, &child_prefix, is_last_child, connectors);
             }
         }
 
         TreeNode::Leaf { label } => {
-            let connector = if is_last { "└── " } else { "├── " };
-            output.push_str(prefix);
-            output.push_str(connector);
-            output.push_str(label);
-            output.push('\n');
+            let connector = connectors.connector(is_last);
+            lines.push((format!("{}{}{}", prefix, connector, label), None));
         }
     }
 }
@@ -76,6 +751,125 @@ fn render_node(node: &TreeNode, output: &mut String, prefix: &str, is_last: bool
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_bulloak_root_escapes_signature() {
+        assert_eq!(bulloak_root("withdraw"), "withdraw_Test");
+        assert_eq!(
+            bulloak_root("transfer(address,uint256)"),
+            "transfer_address_uint256_Test"
+        );
+        assert_eq!(
+            bulloak_root("batch(uint256[],bytes)"),
+            "batch_uint256_bytes_Test"
+        );
+    }
+
+    #[test]
+    fn test_scaffold_names_tests_and_modifiers_after_conditions() {
+        let tree = parse_tree(
+            "\
+withdraw
+├── when amount is zero
+│   └── it should revert
+└── when amount is not zero
+    ├── given msg.sender is not owner
+    │   └── it should revert with \"Not owner\"
+    └── given msg.sender is owner
+        ├── it should succeed
+        └── it should emit a {Withdraw} event
+",
+        )
+        .unwrap();
+
+        assert_eq!(
+            render_scaffold(&[tree], &LabelConfig::default()),
+            "\
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.0;
+
+contract withdraw_Test {
+    modifier whenAmountIsNotZero() {
+        _;
+    }
+
+    function test_RevertWhen_AmountIsZero() external {
+        // it should revert
+    }
+
+    function test_RevertGiven_MsgSenderIsNotOwner() external whenAmountIsNotZero {
+        // it should revert with \"Not owner\"
+    }
+
+    function test_GivenMsgSenderIsOwner() external whenAmountIsNotZero {
+        // it should succeed
+        // it should emit a {Withdraw} event
+    }
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_scaffold_numbers_repeated_conditions() {
+        let tree = parse_tree(
+            "\
+deposit
+├── when paused
+│   └── given balance is zero
+│       └── it should revert
+└── when not paused
+    └── given balance is zero
+        └── it should succeed
+",
+        )
+        .unwrap();
+
+        let scaffold = render_scaffold(&[tree], &LabelConfig::default());
+        assert!(
+            scaffold.contains("function test_RevertGiven_BalanceIsZero() external whenPaused {")
+        );
+        assert!(scaffold.contains("function test_GivenBalanceIsZero() external whenNotPaused {"));
+
+        let tree = parse_tree(
+            "\
+deposit
+├── when paused
+│   └── given balance is zero
+│       └── it should succeed
+└── when not paused
+    └── given balance is zero
+        └── it should succeed
+",
+        )
+        .unwrap();
+        let scaffold = render_scaffold(&[tree], &LabelConfig::default());
+        assert!(scaffold.contains("function test_GivenBalanceIsZero() external whenPaused {"));
+        assert!(scaffold.contains("function test_GivenBalanceIsZero_2() external whenNotPaused {"));
+    }
+
+    #[test]
+    fn test_scaffold_names_reverts_by_the_configured_label() {
+        let tree = parse_tree(
+            "\
+pause
+├── when msg.sender is not owner
+│   └── it fails with NotOwner
+└── when msg.sender is owner
+    └── it works
+",
+        )
+        .unwrap();
+        let labels = LabelConfig {
+            revert: "it fails".to_string(),
+            success: "it works".to_string(),
+            ..LabelConfig::default()
+        };
+
+        let scaffold = render_scaffold(&[tree], &labels);
+        assert!(scaffold.contains("function test_RevertWhen_MsgSenderIsNotOwner() external {"));
+        assert!(scaffold.contains("function test_WhenMsgSenderIsOwner() external {"));
+    }
+
     #[test]
     fn test_simple_tree_rendering() {
         let tree = TreeNode::Root {
@@ -83,12 +877,16 @@ mod tests {
             children: vec![
                 TreeNode::Branch {
                     label: "when msg.sender is not owner".to_string(),
+                    source: None,
+                    line: None,
                     children: vec![TreeNode::Leaf {
                         label: "it should revert".to_string(),
                     }],
                 },
                 TreeNode::Branch {
                     label: "when msg.sender is owner".to_string(),
+                    source: None,
+                    line: None,
                     children: vec![TreeNode::Leaf {
                         label: "it should succeed".to_string(),
                     }],
@@ -105,5 +903,340 @@ increment
     └── it should succeed
 ";
         assert_eq!(output, expected);
+
+        let ascii = render_styled(&tree, Connectors::ASCII, false, false);
+        let expected = "\
+increment
+|-- when msg.sender is not owner
+|   `-- it should revert
+`-- when msg.sender is owner
+    `-- it should succeed
+";
+        assert_eq!(ascii, expected);
+        assert_eq!(parse_tree(&ascii).unwrap(), tree);
+    }
+
+    #[test]
+    fn test_annotated_tree_rendering() {
+        let source = Some("require(msg.sender == owner)".to_string());
+        let tree = TreeNode::Root {
+            name: "increment".to_string(),
+            children: vec![
+                TreeNode::Branch {
+                    label: "when msg.sender is not owner".to_string(),
+                    source: source.clone(),
+                    line: None,
+                    children: vec![TreeNode::Leaf {
+                        label: "it should revert".to_string(),
+                    }],
+                },
+                TreeNode::Branch {
+                    label: "when msg.sender is owner".to_string(),
+                    source,
+                    line: None,
+                    children: vec![TreeNode::Leaf {
+                        label: "it should succeed".to_string(),
+                    }],
+                },
+            ],
+        };
+
+        let output = render_annotated_to_string(&tree);
+        let expected = "\
+increment
+├── when msg.sender is not owner  // require(msg.sender == owner)
+│   └── it should revert
+└── when msg.sender is owner      // require(msg.sender == owner)
+    └── it should succeed
+";
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_line_annotations_read_back() {
+        let tree = TreeNode::Root {
+            name: "withdraw".to_string(),
+            children: vec![
+                TreeNode::Branch {
+                    label: "when amount is at most zero".to_string(),
+                    source: Some("require(amount > 0)".to_string()),
+                    line: Some(42),
+                    children: vec![TreeNode::Leaf {
+                        label: "it should revert".to_string(),
+                    }],
+                },
+                TreeNode::Branch {
+                    label: "when amount is greater than zero".to_string(),
+                    source: Some("require(amount > 0)".to_string()),
+                    line: Some(42),
+                    children: vec![TreeNode::Leaf {
+                        label: "it should succeed".to_string(),
+                    }],
+                },
+            ],
+        };
+
+        let output = render_with_lines(&tree, false);
+        let expected = "\
+withdraw
+├── when amount is at most zero       // L42
+│   └── it should revert
+└── when amount is greater than zero  // L42
+    └── it should succeed
+";
+        assert_eq!(output, expected);
+
+        let annotated = render_with_lines(&tree, true);
+        assert!(annotated.contains("zero  // L42: require(amount > 0)\n"));
+        assert_eq!(parse_tree(&annotated).unwrap(), tree);
+    }
+
+    #[test]
+    fn test_parse_tree_reads_rendered_tree() {
+        let source = Some("require(msg.sender == owner)".to_string());
+        let tree = TreeNode::Root {
+            name: "increment".to_string(),
+            children: vec![
+                TreeNode::Branch {
+                    label: "when msg.sender is not owner".to_string(),
+                    source: source.clone(),
+                    line: None,
+                    children: vec![TreeNode::Leaf {
+                        label: "it should revert".to_string(),
+                    }],
+                },
+                TreeNode::Branch {
+                    label: "when msg.sender is owner".to_string(),
+                    source,
+                    line: None,
+                    children: vec![
+                        TreeNode::Leaf {
+                            label: "it should succeed".to_string(),
+                        },
+                        TreeNode::Leaf {
+                            label: "it should emit Incremented".to_string(),
+                        },
+                    ],
+                },
+            ],
+        };
+
+        assert_eq!(
+            parse_tree(&render_annotated_to_string(&tree)).unwrap(),
+            tree
+        );
+    }
+
+    #[test]
+    fn test_parse_trees_reads_overload_sections() {
+        let content = "\
+// transfer(address)
+transfer(address)
+└── it should succeed
+
+// transfer(address,uint256)
+transfer(address,uint256)
+├── when amount is zero
+│   └── it should revert
+└── when amount is not zero
+    └── it should succeed
+";
+
+        let trees = parse_trees(content).unwrap();
+        let names: Vec<&str> = trees
+            .iter()
+            .map(|tree| match tree {
+                TreeNode::Root { name, .. } => name.as_str(),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(names, ["transfer(address)", "transfer(address,uint256)"]);
+        assert_eq!(trees[1].leaf_paths().len(), 2);
+        assert!(parse_tree(content).is_err());
+    }
+
+    #[test]
+    fn test_parse_tree_reports_malformed_line() {
+        let err = parse_tree("increment\n    └── orphan\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Malformed tree at line 2: `orphan` is nested too deep"
+        );
+
+        let err = parse_tree("increment\n- not a node\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Malformed tree at line 2: expected a `├──` or `└──` node, found `- not a node`"
+        );
+    }
+
+    #[test]
+    fn test_mermaid_rendering() {
+        let tree = TreeNode::Root {
+            name: "increment".to_string(),
+            children: vec![
+                TreeNode::Branch {
+                    label: "when msg.sender is not owner".to_string(),
+                    source: None,
+                    line: None,
+                    children: vec![TreeNode::Leaf {
+                        label: "it should revert".to_string(),
+                    }],
+                },
+                TreeNode::Branch {
+                    label: "when msg.sender is owner".to_string(),
+                    source: None,
+                    line: None,
+                    children: vec![TreeNode::Leaf {
+                        label: "it should succeed".to_string(),
+                    }],
+                },
+            ],
+        };
+
+        let output = render_to_mermaid(&tree, &LabelConfig::default());
+        let expected = "\
+flowchart TD
+    n0[\"increment\"]
+    n1(\"when msg.sender is not owner\")
+    n2([\"it should revert\"]):::revert
+    n3(\"when msg.sender is owner\")
+    n4([\"it should succeed\"]):::success
+    n0 --> n1
+    n1 --> n2
+    n0 --> n3
+    n3 --> n4
+    classDef revert fill:#fdd,stroke:#c00
+    classDef success fill:#dfd,stroke:#080
+";
+        assert_eq!(output, expected);
+        assert_eq!(output.matches(" --> ").count(), 4);
+    }
+
+    #[test]
+    fn test_mermaid_counts_nested_nodes_and_styles_named_reverts() {
+        let leaf = |label: &str| TreeNode::Leaf {
+            label: label.to_string(),
+        };
+        let branch = |label: &str, children| TreeNode::Branch {
+            label: label.to_string(),
+            source: None,
+            line: None,
+            children,
+        };
+        let tree = TreeNode::Root {
+            name: "withdraw".to_string(),
+            children: vec![
+                branch(
+                    "when amount is zero",
+                    vec![leaf("it should revert with InvalidAmount")],
+                ),
+                branch(
+                    "when amount is not zero",
+                    vec![
+                        branch(
+                            "given balance is less than amount",
+                            vec![leaf("it should revert with \"Insufficient\"")],
+                        ),
+                        branch(
+                            "given balance is at least amount",
+                            vec![leaf("it should succeed")],
+                        ),
+                    ],
+                ),
+            ],
+        };
+
+        let output = render_to_mermaid(&tree, &LabelConfig::default());
+
+        // One node per tree node, one edge per non-root node
+        let nodes = output
+            .lines()
+            .filter(|l| l.trim_start().starts_with('n') && !l.contains("-->"));
+        assert_eq!(nodes.count(), 8);
+        assert_eq!(output.matches(" --> ").count(), 7);
+        assert_eq!(output.matches(":::revert").count(), 2);
+        assert_eq!(output.matches(":::success").count(), 1);
+        assert!(output.contains("([\"it should revert with #quot;Insufficient#quot;\"]):::revert"));
+    }
+
+    #[test]
+    fn test_colorize_only_wraps_labels() {
+        let tree = TreeNode::Root {
+            name: "increment".to_string(),
+            children: vec![
+                TreeNode::Branch {
+                    label: "when msg.sender is not owner".to_string(),
+                    source: None,
+                    line: None,
+                    children: vec![TreeNode::Leaf {
+                        label: "it should revert".to_string(),
+                    }],
+                },
+                TreeNode::Branch {
+                    label: "when msg.sender is owner".to_string(),
+                    source: None,
+                    line: None,
+                    children: vec![TreeNode::Leaf {
+                        label: "it should succeed".to_string(),
+                    }],
+                },
+            ],
+        };
+        let content = render_to_string(&tree);
+        let strip = |colored: &str| {
+            colored
+                .replace("\x1b[2m", "")
+                .replace("\x1b[31m", "")
+                .replace("\x1b[32m", "")
+                .replace("\x1b[0m", "")
+        };
+
+        // Without color the output is byte-identical to the plain rendering
+        let labels = LabelConfig::default();
+        assert_eq!(colorize(&content, false, &labels), content);
+
+        let colored = colorize(&content, true, &labels);
+        assert_eq!(
+            colored,
+            "\
+increment
+├── \x1b[2mwhen msg.sender is not owner\x1b[0m
+│   └── \x1b[31mit should revert\x1b[0m
+└── \x1b[2mwhen msg.sender is owner\x1b[0m
+    └── \x1b[32mit should succeed\x1b[0m
+"
+        );
+        assert_eq!(strip(&colored), content);
+
+        // Line endings are kept, including a missing final newline
+        let crlf = content.replace('\n', "\r\n");
+        assert_eq!(strip(&colorize(&crlf, true, &labels)), crlf);
+        let unterminated = content.trim_end();
+        assert_eq!(strip(&colorize(unterminated, true, &labels)), unterminated);
+    }
+
+    #[test]
+    fn test_custom_revert_label_is_styled_as_revert() {
+        let content = "\
+pause
+├── when msg.sender is not owner
+│   └── it fails with NotOwner
+└── when msg.sender is owner
+    └── it works
+";
+        let labels = LabelConfig {
+            revert: "it fails".to_string(),
+            success: "it works".to_string(),
+            ..LabelConfig::default()
+        };
+
+        let colored = colorize(content, true, &labels);
+        assert!(colored.contains("\x1b[31mit fails with NotOwner\x1b[0m"));
+        assert!(colored.contains("\x1b[32mit works\x1b[0m"));
+
+        let mermaid = render_to_mermaid(&parse_tree(content).unwrap(), &labels);
+        assert!(mermaid.contains("([\"it fails with NotOwner\"]):::revert"));
+        assert!(mermaid.contains("([\"it works\"]):::success"));
     }
 }
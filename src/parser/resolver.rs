@@ -4,6 +4,7 @@
 
 use crate::foundry::FoundryProject;
 use solar_parse::ast::{self, ItemKind};
+use solar_parse::interface::source_map::FileName;
 use solar_parse::interface::Session;
 use solar_parse::Parser;
 use std::collections::HashMap;
@@ -22,13 +23,26 @@ struct ParsedContract {
     name: String,
     parents: Vec<String>,
     modifiers: Vec<String>,
+    state_variables: Vec<String>,
+    /// Names of the regular functions (used to look up library methods)
+    functions: Vec<String>,
+    /// Libraries attached with `using L for T`
+    using_libraries: Vec<String>,
+}
+
+/// Import directive of a parsed file
+#[derive(Debug, Clone)]
+struct ParsedImport {
+    path: String,
+    /// Named symbols as (original name, local name); `None` for plain and glob imports
+    symbols: Option<Vec<(String, String)>>,
 }
 
 /// Parsed file info
 #[derive(Debug)]
 struct ParsedFile {
     path: PathBuf,
-    imports: Vec<String>,
+    imports: Vec<ParsedImport>,
     contracts: Vec<ParsedContract>,
 }
 
@@ -36,6 +50,10 @@ struct ParsedFile {
 pub struct InheritanceResolver<'a> {
     project: &'a FoundryProject,
     file_cache: HashMap<PathBuf, ParsedFile>,
+    /// Source text to parse instead of reading these files from disk
+    sources: HashMap<PathBuf, String>,
+    /// Number of files parsed, which the cache keeps to one per file
+    parse_count: usize,
 }
 
 impl<'a> InheritanceResolver<'a> {
@@ -43,9 +61,22 @@ impl<'a> InheritanceResolver<'a> {
         Self {
             project,
             file_cache: HashMap::new(),
+            sources: HashMap::new(),
+            parse_count: 0,
         }
     }
 
+    /// Parse `source` whenever `path` is requested, such as text piped on stdin
+    pub fn add_source(&mut self, path: PathBuf, source: String) {
+        self.file_cache.remove(&path);
+        self.sources.insert(path, source);
+    }
+
+    /// Number of files parsed so far
+    pub fn parse_count(&self) -> usize {
+        self.parse_count
+    }
+
     /// Resolve an import path to an absolute file path
     pub fn resolve_import(&self, import_path: &str, from_file: &Path) -> Option<PathBuf> {
         self.project.resolve_import(import_path, from_file)
@@ -57,11 +88,21 @@ impl<'a> InheritanceResolver<'a> {
             return self.file_cache.get(file_path);
         }
 
+        self.parse_count += 1;
         let sess = Session::builder().with_silent_emitter(None).build();
 
         let parsed = sess.enter(|| {
             let arena = ast::Arena::new();
-            let mut parser = Parser::from_file(&sess, &arena, file_path).ok()?;
+            let mut parser = match self.sources.get(file_path) {
+                Some(source) => Parser::from_source_code(
+                    &sess,
+                    &arena,
+                    FileName::Real(file_path.to_path_buf()),
+                    source.clone(),
+                ),
+                None => Parser::from_file(&sess, &arena, file_path),
+            }
+            .ok()?;
 
             let source_unit = parser.parse_file().ok()?;
 
@@ -70,8 +111,23 @@ impl<'a> InheritanceResolver<'a> {
             for item in source_unit.items.iter() {
                 if let ItemKind::Import(import) = &item.kind {
                     // Get the import path as a string
-                    let path_str = import.path.value.as_str();
-                    imports.push(path_str.to_string());
+                    let path = import.path.value.as_str().to_string();
+
+                    // `import {A, B as C} from "..."` only brings in the listed symbols
+                    let symbols = match &import.items {
+                        ast::ImportItems::Aliases(aliases) => Some(
+                            aliases
+                                .iter()
+                                .map(|(name, alias)| {
+                                    let local = alias.as_ref().unwrap_or(name);
+                                    (name.to_string(), local.to_string())
+                                })
+                                .collect(),
+                        ),
+                        _ => None,
+                    };
+
+                    imports.push(ParsedImport { path, symbols });
                 }
             }
 
@@ -102,10 +158,51 @@ impl<'a> InheritanceResolver<'a> {
                         })
                         .collect();
 
+                    // Get state variable names
+                    let state_variables: Vec<String> = contract
+                        .body
+                        .iter()
+                        .filter_map(|item| match &item.kind {
+                            ItemKind::Variable(var) => var.name.as_ref().map(|n| n.to_string()),
+                            _ => None,
+                        })
+                        .collect();
+
+                    // Get function names
+                    let functions: Vec<String> = contract
+                        .body
+                        .iter()
+                        .filter_map(|item| match &item.kind {
+                            ItemKind::Function(func)
+                                if func.kind == ast::FunctionKind::Function =>
+                            {
+                                func.header.name.as_ref().map(|n| n.to_string())
+                            }
+                            _ => None,
+                        })
+                        .collect();
+
+                    // Get libraries from `using L for T` (`using {f, g} for T` attaches
+                    // free functions, not a library)
+                    let using_libraries: Vec<String> = contract
+                        .body
+                        .iter()
+                        .filter_map(|item| match &item.kind {
+                            ItemKind::Using(using) => match &using.list {
+                                ast::UsingList::Single(path) => Some(path.last().to_string()),
+                                ast::UsingList::Multiple(_) => None,
+                            },
+                            _ => None,
+                        })
+                        .collect();
+
                     contracts.push(ParsedContract {
                         name,
                         parents,
                         modifiers,
+                        state_variables,
+                        functions,
+                        using_libraries,
                     });
                 }
             }
@@ -127,25 +224,66 @@ impl<'a> InheritanceResolver<'a> {
 
     /// Find which file contains a contract by searching imports
     pub fn find_contract_file(&mut self, contract_name: &str, from_file: &Path) -> Option<PathBuf> {
+        self.resolve_contract(contract_name, from_file)
+            .map(|(path, _)| path)
+    }
+
+    /// Resolve a contract name as seen from a file to the file declaring it and its declared name,
+    /// which differs from `contract_name` when it was imported under an alias
+    pub fn resolve_contract(
+        &mut self,
+        contract_name: &str,
+        from_file: &Path,
+    ) -> Option<(PathBuf, String)> {
+        let mut visited = std::collections::HashSet::new();
+        self.resolve_contract_recursive(contract_name, from_file, &mut visited)
+    }
+
+    fn resolve_contract_recursive(
+        &mut self,
+        contract_name: &str,
+        from_file: &Path,
+        visited: &mut std::collections::HashSet<PathBuf>,
+    ) -> Option<(PathBuf, String)> {
+        if !visited.insert(from_file.to_path_buf()) {
+            return None; // Avoid import cycles
+        }
+
         // First parse the current file
         let parsed = self.parse_and_cache(from_file)?;
 
         // Check if contract is in current file
         if parsed.contracts.iter().any(|c| c.name == contract_name) {
-            return Some(from_file.to_path_buf());
+            return Some((from_file.to_path_buf(), contract_name.to_string()));
         }
 
-        // Search in imports
-        let imports: Vec<String> = parsed.imports.clone();
-        for import_path in imports {
-            if let Some(resolved) = self.resolve_import(&import_path, from_file) {
+        let imports = parsed.imports.clone();
+
+        // A named import tells us exactly which file the symbol comes from
+        for import in &imports {
+            let Some(symbols) = &import.symbols else {
+                continue;
+            };
+            let Some((original, _)) = symbols.iter().find(|(_, local)| local == contract_name)
+            else {
+                continue;
+            };
+
+            let resolved = self.resolve_import(&import.path, from_file)?;
+            // The imported file may itself re-export the symbol from one of its imports
+            return self.resolve_contract_recursive(original, &resolved, visited);
+        }
+
+        // Otherwise search the plain and glob imports
+        for import in imports.iter().filter(|i| i.symbols.is_none()) {
+            if let Some(resolved) = self.resolve_import(&import.path, from_file) {
                 if let Some(parsed_import) = self.parse_and_cache(&resolved) {
                     if parsed_import
                         .contracts
                         .iter()
                         .any(|c| c.name == contract_name)
                     {
-                        return Some(resolved);
+                        return Some((resolved, contract_name.to_string()));
                     }
                 }
             }
@@ -174,6 +312,58 @@ impl<'a> InheritanceResolver<'a> {
         Vec::new()
     }
 
+    /// Get all state variable names from a contract (not including inherited)
+    pub fn get_state_variable_names(
+        &mut self,
+        contract_name: &str,
+        file_path: &Path,
+    ) -> Vec<String> {
+        if let Some(parsed) = self.parse_and_cache(file_path) {
+            if let Some(contract) = parsed.contracts.iter().find(|c| c.name == contract_name) {
+                return contract.state_variables.clone();
+            }
+        }
+        Vec::new()
+    }
+
+    /// Methods a contract attaches with `using L for T`, as (method name, library name).
+    /// Libraries whose source can't be found are skipped
+    pub fn get_library_methods(
+        &mut self,
+        contract_name: &str,
+        file_path: &Path,
+    ) -> Vec<(String, String)> {
+        let libraries = match self.parse_and_cache(file_path) {
+            Some(parsed) => parsed
+                .contracts
+                .iter()
+                .find(|c| c.name == contract_name)
+                .map(|c| c.using_libraries.clone())
+                .unwrap_or_default(),
+            None => return Vec::new(),
+        };
+
+        let mut methods = Vec::new();
+        for library in libraries {
+            let Some((library_file, library_name)) = self.resolve_contract(&library, file_path)
+            else {
+                continue;
+            };
+            let Some(parsed) = self.parse_and_cache(&library_file) else {
+                continue;
+            };
+            if let Some(contract) = parsed.contracts.iter().find(|c| c.name == library_name) {
+                methods.extend(
+                    contract
+                        .functions
+                        .iter()
+                        .map(|function| (function.clone(), library.clone())),
+                );
+            }
+        }
+        methods
+    }
+
     /// Build the full inheritance chain for a contract
     /// Returns list of (file_path, contract_name) from root ancestor to child
     pub fn build_inheritance_chain(
@@ -206,8 +396,10 @@ impl<'a> InheritanceResolver<'a> {
 
         // Recurse into parents first (so they appear before children in the chain)
         for parent_name in parents {
-            if let Some(parent_file) = self.find_contract_file(&parent_name, file_path) {
-                self.build_chain_recursive(&parent_name, &parent_file, chain, visited);
+            if let Some((parent_file, declared_name)) =
+                self.resolve_contract(&parent_name, file_path)
+            {
+                self.build_chain_recursive(&declared_name, &parent_file, chain, visited);
             }
         }
 
@@ -3,14 +3,21 @@
 #![allow(dead_code)]
 
 use solar_parse::ast::{self, ItemKind};
-use solar_parse::interface::Session;
+use solar_parse::interface::source_map::FileName;
+use solar_parse::interface::{Session, Span};
 use solar_parse::Parser;
-use std::path::Path;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
-use crate::analysis::{BinaryOp, BranchPoint, ConditionContext, ConditionExpr};
+use crate::analysis::{
+    Arms, BinaryOp, BranchPoint, CheckKind, ConditionContext, ConditionExpr, Warning, WarningKind,
+};
 use crate::foundry::FoundryProject;
 
+use super::resolver::InheritanceResolver;
+
 #[derive(Error, Debug)]
 pub enum ParserError {
     #[error("Failed to parse file: {0}")]
@@ -27,7 +34,7 @@ pub enum ParserError {
 }
 
 /// Context for analyzing a function - uses owned data extracted from AST
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct FunctionContext {
     pub function_name: String,
     /// The parameter signature (e.g., "address,uint256") for distinguishing overloads
@@ -35,549 +42,950 @@ pub struct FunctionContext {
     pub branch_points: Vec<BranchPoint>,
     pub parameters: Vec<String>,
     pub state_variables: Vec<String>,
+    /// Constructs that were skipped or couldn't be resolved while analyzing the function
+    pub warnings: Vec<Warning>,
+    /// `@custom:btt` lines from the function's NatSpec, e.g. `given the vault is empty`
+    pub hints: Vec<String>,
+}
+
+/// State shared while walking the statements of one function (or inlined modifier)
+struct Walk<'a, 'ast> {
+    sess: &'a Session,
+    file: &'a Path,
+    state_vars: &'a [String],
+    params: &'a [String],
+    /// Methods attached with `using L for T`, as (method name, library name)
+    library_methods: &'a [(String, String)],
+    /// Locals initialized from state, e.g. `bal` in `uint256 bal = balances[msg.sender]`
+    storage_locals: Vec<String>,
+    branch_points: Vec<BranchPoint>,
+    warnings: Vec<Warning>,
+    /// Success flags of low-level calls (`ok` in `(bool ok, ) = target.call(data)`),
+    /// with the index of the call's branch point
+    call_results: Vec<(String, usize)>,
+    /// Inside an `unchecked` block, where arithmetic wraps instead of reverting
+    in_unchecked: bool,
+    /// Internal functions of the contract whose checks are inlined where they're called
+    helpers: &'a [HelperDef<'ast>],
+    /// Number of helper calls being inlined around the current statement
+    helper_depth: usize,
+    /// User-defined types that aren't contracts, see [`TypeNames::values`]
+    value_types: &'a [String],
+    /// Variables holding a contract (`vault` in `IVault vault`), whose members live in
+    /// that contract's storage rather than this one's
+    contract_vars: Vec<String>,
+}
+
+impl<'a, 'ast> Walk<'a, 'ast> {
+    fn new(
+        sess: &'a Session,
+        file: &'a Path,
+        state_vars: &'a [String],
+        params: &'a [String],
+        library_methods: &'a [(String, String)],
+    ) -> Self {
+        Self {
+            sess,
+            file,
+            state_vars,
+            params,
+            library_methods,
+            storage_locals: Vec::new(),
+            branch_points: Vec::new(),
+            warnings: Vec::new(),
+            call_results: Vec::new(),
+            in_unchecked: false,
+            helpers: &[],
+            helper_depth: 0,
+            value_types: &[],
+            contract_vars: Vec::new(),
+        }
+    }
+
+    /// Branch point of the low-level call whose success flag is `flag`
+    fn call_branch(&mut self, flag: &ast::Expr<'_>) -> Option<&mut BranchPoint> {
+        let ast::ExprKind::Ident(ident) = &flag.kind else {
+            return None;
+        };
+        let index = self
+            .call_results
+            .iter()
+            .rev()
+            .find(|(name, _)| name == ident.as_str())?
+            .1;
+        self.branch_points.get_mut(index)
+    }
+
+    /// Record a warning at the line of `span`
+    fn warn(&mut self, span: Span, kind: WarningKind) {
+        let line = self.sess.source_map().lookup_char_pos(span.lo()).line;
+        self.warnings.push(Warning {
+            kind,
+            file: self.file.to_path_buf(),
+            line: Some(line),
+        });
+    }
+
+    /// Line and column (both from 1) where `span` starts
+    fn location(&self, span: Span) -> Option<(usize, usize)> {
+        let loc = self.sess.source_map().lookup_char_pos(span.lo());
+        Some((loc.line, loc.col.0 + 1))
+    }
+}
+
+/// Parsed files of the ancestors defining a contract's inherited modifiers, or why a
+/// file couldn't be parsed
+type AncestorUnits<'ast> = HashMap<PathBuf, Result<ast::SourceUnit<'ast>, String>>;
+
+/// What a contract inherits from its ancestors
+#[derive(Default)]
+struct Inheritance {
+    /// Inheritance chain, from root ancestor to the contract itself
+    chain: Vec<(PathBuf, String)>,
+    /// Modifiers defined by the ancestors as (modifier, file, contract), naming the
+    /// most-derived ancestor for modifiers that are overridden
+    modifiers: Vec<(String, PathBuf, String)>,
+    /// State variables declared by the ancestors
+    state_variables: Vec<String>,
+    /// Methods the contract itself attaches with `using L for T`, as (method, library)
+    library_methods: Vec<(String, String)>,
+    /// With `--resolve-constants`, the constants of the ancestors (nearest first) and
+    /// of the file, as (name, value)
+    constants: Vec<(String, String)>,
 }
 
+/// A named function (or modifier) of a contract with its analysis
+#[derive(Debug, Clone)]
+struct ParsedFunction {
+    /// Visibility of a regular function (public if unspecified); `None` for modifiers,
+    /// constructors, `fallback` and `receive`
+    visibility: Option<ast::Visibility>,
+    context: FunctionContext,
+}
+
+/// User-defined types declared around a contract
+#[derive(Default)]
+struct TypeNames {
+    enums: Vec<String>,
+    /// Every type that isn't a contract: enums, structs and user-defined value types
+    values: Vec<String>,
+}
+
+/// A modifier defined in a contract
+struct ModifierDef<'ast> {
+    name: String,
+    params: Vec<String>,
+    body: Option<&'ast ast::Block<'ast>>,
+}
+
+/// An internal or private function defined in a contract
+struct HelperDef<'ast> {
+    name: String,
+    params: Vec<String>,
+    body: &'ast ast::Block<'ast>,
+}
+
+/// Levels of helper calls followed by `--inline-helpers`, which also cuts off recursion
+const MAX_HELPER_DEPTH: usize = 4;
+
 /// Solar parser wrapper
 pub struct SolarParser<'a> {
     project: &'a FoundryProject,
+    /// Analyzed functions per (file, contract), so each contract is parsed once no
+    /// matter how many of its functions or overloads are requested
+    cache: RefCell<HashMap<(PathBuf, String), Vec<ParsedFunction>>>,
+    /// Methods that revert when their result overflows, e.g. `toUint128`
+    overflow_calls: Vec<String>,
+    /// Inline the checks of internal functions called from the analyzed function
+    inline_helpers: bool,
+    /// Branch on checked `+`/`*` that can overflow
+    arithmetic_overflow: bool,
+    /// Compare against the values of constants rather than their names
+    resolve_constants: bool,
+    /// Source text to parse instead of reading these files from disk
+    sources: HashMap<PathBuf, String>,
+    /// Shared by every contract, so an ancestor common to several of them is read once
+    resolver: RefCell<InheritanceResolver<'a>>,
+    /// Number of files parsed for analysis, to keep an eye on repeated work
+    parse_count: Cell<usize>,
 }
 
 impl<'a> SolarParser<'a> {
     pub fn new(project: &'a FoundryProject) -> Self {
-        Self { project }
+        Self {
+            project,
+            cache: RefCell::new(HashMap::new()),
+            overflow_calls: safe_cast_methods(),
+            inline_helpers: false,
+            arithmetic_overflow: false,
+            resolve_constants: false,
+            sources: HashMap::new(),
+            resolver: RefCell::new(InheritanceResolver::new(project)),
+            parse_count: Cell::new(0),
+        }
+    }
+
+    /// Treat calls to these methods as checked conversions that revert on overflow,
+    /// instead of OpenZeppelin `SafeCast`'s `toUintN`/`toIntN`
+    pub fn with_overflow_calls(mut self, overflow_calls: Vec<String>) -> Self {
+        self.overflow_calls = overflow_calls;
+        self
+    }
+
+    /// Inline the checks of the contract's internal and private functions where a
+    /// statement calls them, so a guard living in `_validate(amount)` shows up in the
+    /// tree of the function calling it
+    pub fn with_inline_helpers(mut self, inline_helpers: bool) -> Self {
+        self.inline_helpers = inline_helpers;
+        self
+    }
+
+    /// Give checked additions and multiplications (`totalSupply + amount`) an overflow
+    /// branch, since they revert on overflow outside `unchecked` blocks
+    pub fn with_arithmetic_overflow(mut self, arithmetic_overflow: bool) -> Self {
+        self.arithmetic_overflow = arithmetic_overflow;
+        self
     }
 
-    /// Parse a function from a contract file and extract branch points
+    /// Write constants compared in conditions as their values, so `require(x <= MAX)`
+    /// with `uint256 constant MAX = 100` reads "x is at most 100"
+    pub fn with_resolved_constants(mut self, resolve_constants: bool) -> Self {
+        self.resolve_constants = resolve_constants;
+        self
+    }
+
+    /// Parse `source` whenever `path` is requested, e.g. an editor's unsaved buffer
+    /// or text piped on stdin. The path needn't exist on disk
+    pub fn with_source(mut self, path: impl Into<PathBuf>, source: impl Into<String>) -> Self {
+        let (path, source) = (path.into(), source.into());
+        self.resolver
+            .get_mut()
+            .add_source(path.clone(), source.clone());
+        self.sources.insert(path, source);
+        self
+    }
+
+    /// Parse a function from a contract file and extract branch points.
+    /// Modifiers are resolved across the contract's whole inheritance chain
     pub fn parse_function(
         &self,
         file_path: &Path,
         contract_name: &str,
         function_name: &str,
     ) -> Result<FunctionContext, ParserError> {
-        // Create a session for parsing
-        let sess = Session::builder().with_silent_emitter(None).build();
-
-        sess.enter(|| {
-            let arena = ast::Arena::new();
-
-            // Create parser from file
-            let mut parser = Parser::from_file(&sess, &arena, file_path)
-                .map_err(|e| ParserError::ParseError(format!("{:?}", e)))?;
-
-            // Parse the file
-            let source_unit = parser.parse_file().map_err(|e| {
-                e.emit();
-                ParserError::ParseError(file_path.display().to_string())
-            })?;
-
-            // Find the contract
-            let contract = self.find_contract(&source_unit, contract_name)?;
-
-            // Extract state variables for storage classification
-            let state_vars = self.extract_state_variables(contract);
-
-            // Find the function
-            let function = self.find_function(contract, function_name)?;
-
-            // Get function parameters
-            let params = self.extract_parameters(function);
-
-            // Extract modifier definitions for inlining
-            let modifier_defs = self.extract_modifier_definitions(contract);
-
-            // Extract branch points from modifiers and function body
-            let mut branch_points = Vec::new();
-
-            // First, extract from modifiers
-            for modifier in function.header.modifiers.iter() {
-                let modifier_name = modifier.name.last().as_str();
-                if let Some(def) = modifier_defs.iter().find(|(name, _)| name == modifier_name) {
-                    if let Some(body) = &def.1 {
-                        self.extract_branch_points_from_block(
-                            body,
-                            &state_vars,
-                            &params,
-                            &mut branch_points,
-                            false,
-                        );
-                    }
-                }
-            }
-
-            // Then extract from function body
-            if let Some(body) = &function.body {
-                self.extract_branch_points_from_block(
-                    body,
-                    &state_vars,
-                    &params,
-                    &mut branch_points,
-                    false,
-                );
-            }
-
-            let signature = self.get_function_signature(function);
-
-            Ok(FunctionContext {
-                function_name: function_name.to_string(),
-                signature,
-                branch_points,
-                parameters: params,
-                state_variables: state_vars,
-            })
+        self.with_functions(file_path, contract_name, |functions| {
+            functions
+                .iter()
+                .find(|f| f.context.function_name == function_name)
+                .map(|f| f.context.clone())
+                .ok_or_else(|| {
+                    ParserError::FunctionNotFound(
+                        function_name.to_string(),
+                        contract_name.to_string(),
+                    )
+                })
         })
     }
 
-    /// Parse a function with full inheritance support - resolves modifiers from parent contracts
+    /// Parse a function with full inheritance support - resolves modifiers from parent contracts.
+    /// Same as `parse_function`, which now always resolves inherited modifiers
     pub fn parse_function_with_inheritance(
         &self,
         file_path: &Path,
         contract_name: &str,
         function_name: &str,
     ) -> Result<FunctionContext, ParserError> {
-        use super::resolver::InheritanceResolver;
+        self.parse_function(file_path, contract_name, function_name)
+    }
 
-        // Build inheritance chain
-        let mut resolver = InheritanceResolver::new(self.project);
-        let chain = resolver.build_inheritance_chain(contract_name, file_path);
+    /// Parse a specific function overload by its signature (e.g., "address,uint256")
+    /// Now includes inheritance support for modifiers
+    pub fn parse_function_by_signature(
+        &self,
+        file_path: &Path,
+        contract_name: &str,
+        function_name: &str,
+        signature: &str,
+    ) -> Result<FunctionContext, ParserError> {
+        self.with_functions(file_path, contract_name, |functions| {
+            functions
+                .iter()
+                .find(|f| {
+                    f.context.function_name == function_name && f.context.signature == signature
+                })
+                .map(|f| f.context.clone())
+                .ok_or_else(|| {
+                    ParserError::FunctionNotFound(
+                        format!("{}({})", function_name, signature),
+                        contract_name.to_string(),
+                    )
+                })
+        })
+    }
 
-        // Collect modifiers from all contracts in the inheritance chain
-        // We'll extract branch points from each modifier in sequence
-        let mut inherited_branch_points: Vec<BranchPoint> = Vec::new();
+    /// Parse all functions with the given name (returns multiple FunctionContexts for overloads)
+    pub fn parse_all_functions(
+        &self,
+        file_path: &Path,
+        contract_name: &str,
+        function_name: &str,
+    ) -> Result<Vec<FunctionContext>, ParserError> {
+        self.with_functions(file_path, contract_name, |functions| {
+            let overloads: Vec<FunctionContext> = functions
+                .iter()
+                .filter(|f| f.context.function_name == function_name)
+                .map(|f| f.context.clone())
+                .collect();
 
-        // First pass: collect branch points from modifiers in parent contracts
-        for (parent_file, parent_contract_name) in &chain {
-            // Skip the actual contract itself - we'll handle it in the main pass
-            if parent_contract_name == contract_name && parent_file == file_path {
-                continue;
+            if overloads.is_empty() {
+                return Err(ParserError::FunctionNotFound(
+                    function_name.to_string(),
+                    contract_name.to_string(),
+                ));
             }
 
-            // Parse parent file and extract modifier branch points
-            if let Ok(parent_modifiers) =
-                self.extract_modifier_branch_points(parent_file, parent_contract_name)
-            {
-                inherited_branch_points.extend(parent_modifiers);
-            }
-        }
+            Ok(overloads)
+        })
+    }
+
+    /// Parse all public and external functions in a contract
+    pub fn parse_all_public_functions(
+        &self,
+        file_path: &Path,
+        contract_name: &str,
+    ) -> Result<Vec<FunctionContext>, ParserError> {
+        self.parse_all_functions_filtered(file_path, contract_name, |visibility| {
+            matches!(
+                visibility,
+                ast::Visibility::Public | ast::Visibility::External
+            )
+        })
+    }
+
+    /// Parse the regular functions of a contract (no modifiers, constructors, fallback
+    /// or receive) whose visibility passes `include`
+    pub fn parse_all_functions_filtered(
+        &self,
+        file_path: &Path,
+        contract_name: &str,
+        include: impl Fn(ast::Visibility) -> bool,
+    ) -> Result<Vec<FunctionContext>, ParserError> {
+        self.with_functions(file_path, contract_name, |functions| {
+            Ok(functions
+                .iter()
+                .filter(|f| f.visibility.is_some_and(&include))
+                .map(|f| f.context.clone())
+                .collect())
+        })
+    }
 
-        // Now parse the actual function
+    /// Parse the functions declared at file level, outside any contract. They can't have
+    /// modifiers or state, but may read the file's constants
+    pub fn parse_free_functions(
+        &self,
+        file_path: &Path,
+    ) -> Result<Vec<FunctionContext>, ParserError> {
         let sess = Session::builder().with_silent_emitter(None).build();
 
         sess.enter(|| {
             let arena = ast::Arena::new();
-            let mut parser = Parser::from_file(&sess, &arena, file_path)
-                .map_err(|e| ParserError::ParseError(format!("{:?}", e)))?;
+            let mut parser = self.open(&sess, &arena, file_path)?;
 
             let source_unit = parser.parse_file().map_err(|e| {
                 e.emit();
                 ParserError::ParseError(file_path.display().to_string())
             })?;
 
-            let contract = self.find_contract(&source_unit, contract_name)?;
-            let state_vars = self.extract_state_variables(contract);
-            let function = self.find_function(contract, function_name)?;
-            let params = self.extract_parameters(function);
-
-            // Get modifier definitions from this contract
-            let modifier_defs = self.extract_modifier_definitions(contract);
-
-            // Start with inherited branch points from parent modifiers
-            let mut branch_points = Vec::new();
-
-            // For each modifier used by the function, check if it's from a parent
-            for modifier in function.header.modifiers.iter() {
-                let modifier_name = modifier.name.last().as_str();
-
-                // First check local definition
-                if let Some(def) = modifier_defs.iter().find(|(name, _)| name == modifier_name) {
-                    if let Some(body) = &def.1 {
-                        self.extract_branch_points_from_block(
-                            body,
-                            &state_vars,
-                            &params,
-                            &mut branch_points,
-                            false,
-                        );
-                    }
-                } else {
-                    // Modifier not found locally - search in inheritance chain
-                    for (parent_file, parent_contract_name) in &chain {
-                        if parent_contract_name == contract_name && parent_file == file_path {
-                            continue;
-                        }
-
-                        // Check if this parent has the modifier
-                        if let Ok(parent_bp) = self.extract_specific_modifier_branch_points(
-                            parent_file,
-                            parent_contract_name,
-                            modifier_name,
-                            &state_vars,
-                            &params,
-                        ) {
-                            branch_points.extend(parent_bp);
-                            break; // Found it, stop searching
-                        }
+            let types = self.type_names(&source_unit, &[]);
+            Ok(source_unit
+                .items
+                .iter()
+                .filter_map(|item| match &item.kind {
+                    ItemKind::Function(function) if function.header.name.is_some() => {
+                        Some((item, function))
                     }
-                }
-            }
-
-            // Extract from function body
-            if let Some(body) = &function.body {
-                self.extract_branch_points_from_block(
-                    body,
-                    &state_vars,
-                    &params,
-                    &mut branch_points,
-                    false,
-                );
-            }
-
-            let signature = self.get_function_signature(function);
-
-            Ok(FunctionContext {
-                function_name: function_name.to_string(),
-                signature,
-                branch_points,
-                parameters: params,
-                state_variables: state_vars,
-            })
+                    _ => None,
+                })
+                .map(|(item, function)| FunctionContext {
+                    hints: btt_hints(&item.docs),
+                    ..self.analyze_function(
+                        &sess,
+                        file_path,
+                        &source_unit.items,
+                        &Inheritance::default(),
+                        &AncestorUnits::new(),
+                        &types,
+                        function,
+                    )
+                })
+                .collect())
         })
     }
 
-    /// Extract branch points from modifiers in a contract (for inheritance)
-    fn extract_modifier_branch_points(
+    /// Run `select` on the analyzed functions of a contract, parsing the file on first use
+    fn with_functions<T>(
         &self,
-        _file_path: &Path,
-        _contract_name: &str,
-    ) -> Result<Vec<BranchPoint>, ParserError> {
-        // This parses just to collect modifier names for the resolver
-        // Actual extraction happens in extract_specific_modifier_branch_points
-        Ok(Vec::new())
+        file_path: &Path,
+        contract_name: &str,
+        select: impl FnOnce(&[ParsedFunction]) -> Result<T, ParserError>,
+    ) -> Result<T, ParserError> {
+        let key = (file_path.to_path_buf(), contract_name.to_string());
+
+        if !self.cache.borrow().contains_key(&key) {
+            let functions = self.analyze_contract(file_path, contract_name)?;
+            self.cache.borrow_mut().insert(key.clone(), functions);
+        }
+
+        select(&self.cache.borrow()[&key])
     }
 
-    /// Extract branch points from a specific modifier in a parent contract
-    fn extract_specific_modifier_branch_points(
+    /// Parse a contract's file and analyze each of its named functions and modifiers
+    fn analyze_contract(
         &self,
         file_path: &Path,
         contract_name: &str,
-        modifier_name: &str,
-        state_vars: &[String],
-        params: &[String],
-    ) -> Result<Vec<BranchPoint>, ParserError> {
+    ) -> Result<Vec<ParsedFunction>, ParserError> {
+        let mut inheritance = self.inheritance(file_path, contract_name);
+
+        // Create a session for parsing
         let sess = Session::builder().with_silent_emitter(None).build();
 
         sess.enter(|| {
             let arena = ast::Arena::new();
-            let mut parser = Parser::from_file(&sess, &arena, file_path)
-                .map_err(|e| ParserError::ParseError(format!("{:?}", e)))?;
 
+            // Create parser from file
+            let mut parser = self.open(&sess, &arena, file_path)?;
+
+            // Parse the file
             let source_unit = parser.parse_file().map_err(|e| {
                 e.emit();
                 ParserError::ParseError(file_path.display().to_string())
             })?;
 
             let contract = self.find_contract(&source_unit, contract_name)?;
+            let types = self.type_names(&source_unit, &contract.body);
 
-            // Also collect parent state vars
-            let parent_state_vars = self.extract_state_variables(contract);
-            let combined_state_vars: Vec<String> = state_vars
+            // Ancestors defining inherited modifiers are parsed once here rather than
+            // once for every function applying one of their modifiers. Resolving
+            // constants needs every ancestor in other files
+            let constant_files = inheritance
+                .chain
                 .iter()
-                .cloned()
-                .chain(parent_state_vars.iter().cloned())
-                .collect();
+                .map(|(file, _)| file)
+                .filter(|file| self.resolve_constants && file.as_path() != file_path);
+            let mut ancestors = AncestorUnits::new();
+            for parent_file in inheritance
+                .modifiers
+                .iter()
+                .map(|(_, file, _)| file)
+                .chain(constant_files)
+            {
+                if !ancestors.contains_key(parent_file) {
+                    let unit = self
+                        .open(&sess, &arena, parent_file)
+                        .and_then(|mut parser| {
+                            parser.parse_file().map_err(|e| {
+                                e.emit();
+                                ParserError::ParseError(parent_file.display().to_string())
+                            })
+                        })
+                        .map_err(|e| e.to_string());
+                    ancestors.insert(parent_file.clone(), unit);
+                }
+            }
 
-            // Find the modifier
-            for item in contract.body.iter() {
-                if let ItemKind::Function(func) = &item.kind {
-                    if func.kind == ast::FunctionKind::Modifier {
-                        if let Some(name) = &func.header.name {
-                            if name.as_str() == modifier_name {
-                                if let Some(body) = &func.body {
-                                    let mut branch_points = Vec::new();
-                                    self.extract_branch_points_from_block(
-                                        body,
-                                        &combined_state_vars,
-                                        params,
-                                        &mut branch_points,
-                                        false,
-                                    );
-                                    return Ok(branch_points);
-                                }
-                            }
-                        }
+            if self.resolve_constants {
+                let mut constants = Vec::new();
+                for (file, name) in inheritance.chain.iter().rev() {
+                    if file == file_path && name == contract_name {
+                        continue;
+                    }
+                    let unit = if file == file_path {
+                        Some(&source_unit)
+                    } else {
+                        ancestors.get(file).and_then(|unit| unit.as_ref().ok())
+                    };
+                    if let Some(parent) = unit.and_then(|unit| self.find_contract(unit, name).ok())
+                    {
+                        constants.extend(self.constant_values(&parent.body));
                     }
                 }
+                constants.extend(self.constant_values(&source_unit.items));
+                inheritance.constants = constants;
             }
 
-            Ok(Vec::new())
+            Ok(contract
+                .body
+                .iter()
+                .filter_map(|item| match &item.kind {
+                    ItemKind::Function(function) if function.header.name.is_some() => {
+                        Some((item, function))
+                    }
+                    _ => None,
+                })
+                .map(|(item, function)| ParsedFunction {
+                    visibility: self.function_visibility(function),
+                    context: FunctionContext {
+                        hints: btt_hints(&item.docs),
+                        ..self.analyze_function(
+                            &sess,
+                            file_path,
+                            &contract.body,
+                            &inheritance,
+                            &ancestors,
+                            &types,
+                            function,
+                        )
+                    },
+                })
+                .collect())
         })
     }
 
-    /// Parse a specific function overload by its signature (e.g., "address,uint256")
-    /// Now includes inheritance support for modifiers
-    pub fn parse_function_by_signature(
+    /// Parser over the in-memory source registered for `file_path`, or the file itself
+    fn open<'sess, 'ast>(
         &self,
+        sess: &'sess Session,
+        arena: &'ast ast::Arena,
         file_path: &Path,
-        contract_name: &str,
-        function_name: &str,
-        signature: &str,
-    ) -> Result<FunctionContext, ParserError> {
-        use super::resolver::InheritanceResolver;
+    ) -> Result<Parser<'sess, 'ast>, ParserError> {
+        self.parse_count.set(self.parse_count.get() + 1);
+        match self.sources.get(file_path) {
+            Some(source) => Parser::from_source_code(
+                sess,
+                arena,
+                FileName::Real(file_path.to_path_buf()),
+                source.clone(),
+            ),
+            None => Parser::from_file(sess, arena, file_path),
+        }
+        .map_err(|e| ParserError::ParseError(format!("{:?}", e)))
+    }
 
-        // Build inheritance chain
-        let mut resolver = InheritanceResolver::new(self.project);
+    /// Inheritance chain of a contract and the state variables it inherits
+    fn inheritance(&self, file_path: &Path, contract_name: &str) -> Inheritance {
+        let mut resolver = self.resolver.borrow_mut();
         let chain = resolver.build_inheritance_chain(contract_name, file_path);
+        let ancestors: Vec<&(PathBuf, String)> = chain
+            .iter()
+            .filter(|(file, name)| !(file == file_path && name == contract_name))
+            .collect();
 
-        let sess = Session::builder().with_silent_emitter(None).build();
+        // The contract's own state variables come from its AST
+        let state_variables = ancestors
+            .iter()
+            .flat_map(|(file, name)| resolver.get_state_variable_names(name, file))
+            .collect();
+
+        // Nearest ancestor first, so an override is kept over the `virtual` modifier
+        // it replaces
+        let mut modifiers: Vec<(String, PathBuf, String)> = Vec::new();
+        for (file, name) in ancestors.iter().rev() {
+            for modifier in resolver.get_modifier_names(name, file) {
+                if !modifiers.iter().any(|(known, ..)| *known == modifier) {
+                    modifiers.push((modifier, file.clone(), name.clone()));
+                }
+            }
+        }
 
-        sess.enter(|| {
-            let arena = ast::Arena::new();
-            let mut parser = Parser::from_file(&sess, &arena, file_path)
-                .map_err(|e| ParserError::ParseError(format!("{:?}", e)))?;
+        let library_methods = resolver.get_library_methods(contract_name, file_path);
 
-            let source_unit = parser.parse_file().map_err(|e| {
-                e.emit();
-                ParserError::ParseError(file_path.display().to_string())
-            })?;
+        Inheritance {
+            chain,
+            modifiers,
+            state_variables,
+            library_methods,
+            constants: Vec::new(),
+        }
+    }
 
-            let contract = self.find_contract(&source_unit, contract_name)?;
-            let state_vars = self.extract_state_variables(contract);
-            let function = self.find_function_by_signature(contract, function_name, signature)?;
-            let params = self.extract_parameters(function);
-            let modifier_defs = self.extract_modifier_definitions(contract);
-
-            let mut branch_points = Vec::new();
-
-            for modifier in function.header.modifiers.iter() {
-                let modifier_name = modifier.name.last().as_str();
-
-                // First check local definition
-                if let Some(def) = modifier_defs.iter().find(|(name, _)| name == modifier_name) {
-                    if let Some(body) = &def.1 {
-                        self.extract_branch_points_from_block(
-                            body,
-                            &state_vars,
-                            &params,
-                            &mut branch_points,
-                            false,
-                        );
-                    }
-                } else {
-                    // Modifier not found locally - search in inheritance chain
-                    for (parent_file, parent_contract_name) in &chain {
-                        if parent_contract_name == contract_name && parent_file == file_path {
-                            continue;
-                        }
+    /// Extract branch points from a function's modifiers (in order) and then its body.
+    /// `items` is the body of the function's contract, or the file's top level for a
+    /// free function. Modifiers not defined there come from the most-derived ancestor
+    /// defining them, as with `virtual`/`override`, whose file is among the parsed
+    /// `ancestors`.
+    #[allow(clippy::too_many_arguments)]
+    fn analyze_function(
+        &self,
+        sess: &Session,
+        file_path: &Path,
+        items: &[ast::Item<'_>],
+        inheritance: &Inheritance,
+        ancestors: &AncestorUnits<'_>,
+        types: &TypeNames,
+        function: &ast::ItemFunction<'_>,
+    ) -> FunctionContext {
+        let function_name = function
+            .header
+            .name
+            .as_ref()
+            .map(|n| n.to_string())
+            .unwrap_or_default();
+        let mut state_vars = self.extract_state_variables(items);
+        state_vars.extend(inheritance.state_variables.iter().cloned());
+        let params = self.extract_parameters(function);
+        let modifier_defs = self.extract_modifier_definitions(items);
+        let helper_defs = if self.inline_helpers {
+            self.extract_helper_definitions(items)
+        } else {
+            Vec::new()
+        };
+
+        let mut walk = Walk::new(
+            sess,
+            file_path,
+            &state_vars,
+            &params,
+            &inheritance.library_methods,
+        );
+        walk.helpers = &helper_defs;
+        walk.value_types = &types.values;
+        walk.contract_vars = items
+            .iter()
+            .filter_map(|item| match &item.kind {
+                ItemKind::Variable(var) => Some(var),
+                _ => None,
+            })
+            .chain(function.header.parameters.iter())
+            .filter(|var| self.is_contract_type(&var.ty, &types.values))
+            .filter_map(|var| var.name.as_ref().map(|name| name.to_string()))
+            .collect();
+
+        // Checks that modifiers run after `_`, in the order the modifiers are applied
+        let mut post_checks = Vec::new();
+
+        for modifier in function.header.modifiers.iter() {
+            let modifier_name = modifier.name.last().as_str();
+            // `onlyRole(ADMIN)` binds the modifier's `role` parameter to `ADMIN`
+            let args: Vec<String> = modifier
+                .arguments
+                .exprs()
+                .map(|arg| self.expr_to_string(arg))
+                .collect();
 
-                        if let Ok(parent_bp) = self.extract_specific_modifier_branch_points(
+            // First check local definition
+            if let Some(def) = modifier_defs.iter().find(|def| def.name == modifier_name) {
+                post_checks.push(self.inline_modifier(&mut walk, def, &args));
+                continue;
+            }
+
+            // Modifier not found locally - use the nearest ancestor defining it
+            let mut resolved = false;
+            let owner = inheritance
+                .modifiers
+                .iter()
+                .find(|(name, ..)| name == modifier_name);
+            if let Some((_, parent_file, parent_contract_name)) = owner {
+                match ancestors.get(parent_file) {
+                    Some(Ok(source_unit)) => {
+                        match self.extract_specific_modifier_branch_points(
+                            &mut walk,
                             parent_file,
+                            source_unit,
                             parent_contract_name,
                             modifier_name,
-                            &state_vars,
-                            &params,
+                            &args,
                         ) {
-                            if !parent_bp.is_empty() {
-                                branch_points.extend(parent_bp);
-                                break;
+                            Ok(Some(post)) => {
+                                post_checks.push(post);
+                                resolved = true;
                             }
+                            Ok(None) => {}
+                            Err(e) => walk.warnings.push(Warning {
+                                kind: WarningKind::ParseSkipped {
+                                    reason: e.to_string(),
+                                },
+                                file: parent_file.clone(),
+                                line: None,
+                            }),
                         }
                     }
+                    Some(Err(reason)) => walk.warnings.push(Warning {
+                        kind: WarningKind::ParseSkipped {
+                            reason: reason.clone(),
+                        },
+                        file: parent_file.clone(),
+                        line: None,
+                    }),
+                    None => {}
                 }
             }
 
-            if let Some(body) = &function.body {
-                self.extract_branch_points_from_block(
-                    body,
-                    &state_vars,
-                    &params,
-                    &mut branch_points,
-                    false,
+            if !resolved {
+                walk.warn(
+                    modifier.name.span(),
+                    WarningKind::ModifierUnresolved {
+                        modifier: modifier_name.to_string(),
+                        function: function_name.clone(),
+                    },
                 );
             }
+        }
 
-            Ok(FunctionContext {
-                function_name: function_name.to_string(),
-                signature: signature.to_string(),
-                branch_points,
-                parameters: params,
-                state_variables: state_vars,
-            })
-        })
+        // Then extract from function body
+        if let Some(body) = &function.body {
+            self.extract_branch_points_from_block(&mut walk, body, None);
+        }
+
+        // Modifiers unwind innermost first, so the last modifier's post-checks run first
+        for post in post_checks.into_iter().rev() {
+            walk.branch_points.extend(post);
+        }
+
+        if self.resolve_constants {
+            let mut constants = self.constant_values(items);
+            constants.extend(inheritance.constants.iter().cloned());
+            let mut branch_points = std::mem::take(&mut walk.branch_points);
+            self.resolve_constants(&mut branch_points, &constants, &walk);
+            walk.branch_points = branch_points;
+        }
+
+        let Walk {
+            mut branch_points,
+            warnings,
+            ..
+        } = walk;
+
+        // `!(a == b)` reads as `a != b`, and `state == State.Active` as "state is Active"
+        tidy_conditions(&mut branch_points, &types.enums);
+
+        FunctionContext {
+            function_name,
+            signature: self.get_function_signature(function),
+            branch_points,
+            parameters: params,
+            state_variables: state_vars,
+            warnings,
+            hints: Vec::new(),
+        }
     }
 
-    /// Parse all functions with the given name (returns multiple FunctionContexts for overloads)
-    pub fn parse_all_functions(
+    /// Inline a modifier applied with `args`, writing the call-site arguments in place of
+    /// its parameters. Returns the checks that run after `_`
+    fn inline_modifier(
         &self,
-        file_path: &Path,
-        contract_name: &str,
-        function_name: &str,
-    ) -> Result<Vec<FunctionContext>, ParserError> {
-        let sess = Session::builder().with_silent_emitter(None).build();
+        walk: &mut Walk<'_, '_>,
+        def: &ModifierDef<'_>,
+        args: &[String],
+    ) -> Vec<BranchPoint> {
+        let Some(body) = def.body else {
+            return Vec::new();
+        };
+
+        let start = walk.branch_points.len();
+        let mut post = self.extract_modifier_branch_points(walk, body);
+
+        let bindings: Vec<(String, String)> = def
+            .params
+            .iter()
+            .cloned()
+            .zip(args.iter().cloned())
+            .filter(|(param, arg)| param != arg)
+            .collect();
+        if !bindings.is_empty() {
+            let mut pre = walk.branch_points.split_off(start);
+            for bp in pre.iter_mut().chain(&mut post) {
+                self.bind_params(bp, &bindings, walk);
+            }
+            walk.branch_points.append(&mut pre);
+        }
+        post
+    }
 
-        sess.enter(|| {
-            let arena = ast::Arena::new();
-            let mut parser = Parser::from_file(&sess, &arena, file_path)
-                .map_err(|e| ParserError::ParseError(format!("{:?}", e)))?;
+    /// Replace modifier parameters with their arguments in a check (and the checks of its
+    /// `if`/`else` arms), classifying it again since an argument may be a state variable
+    fn bind_params(
+        &self,
+        bp: &mut BranchPoint,
+        bindings: &[(String, String)],
+        walk: &Walk<'_, '_>,
+    ) {
+        let before = bp.condition.to_string();
+        substitute_params(&mut bp.condition, bindings);
+        if !bp.is_external_call && bp.condition.to_string() != before {
+            bp.context = self.classify_condition(&bp.condition, walk);
+        }
 
-            let source_unit = parser.parse_file().map_err(|e| {
-                e.emit();
-                ParserError::ParseError(file_path.display().to_string())
-            })?;
+        if let Some(arms) = &mut bp.arms {
+            for check in arms.then_checks.iter_mut().chain(&mut arms.else_checks) {
+                self.bind_params(check, bindings, walk);
+            }
+        }
+    }
 
-            let contract = self.find_contract(&source_unit, contract_name)?;
-            let state_vars = self.extract_state_variables(contract);
-            let modifier_defs = self.extract_modifier_definitions(contract);
-            let functions = self.find_all_functions_by_name(contract, function_name);
+    /// Inline the checks of a helper called as `name(args)`, writing the arguments in
+    /// place of its parameters. Does nothing if `name` isn't a helper of the contract
+    fn inline_helper(
+        &self,
+        walk: &mut Walk<'_, '_>,
+        name: &str,
+        args: &[String],
+        loop_vars: Option<&[String]>,
+    ) {
+        let helpers = walk.helpers;
+        let Some(def) = helpers
+            .iter()
+            .find(|def| def.name == name && def.params.len() == args.len())
+        else {
+            return;
+        };
+        if walk.helper_depth >= MAX_HELPER_DEPTH {
+            return;
+        }
 
-            if functions.is_empty() {
-                return Err(ParserError::FunctionNotFound(
-                    function_name.to_string(),
-                    contract_name.to_string(),
-                ));
-            }
+        let start = walk.branch_points.len();
+        let locals = walk.storage_locals.len();
+        walk.helper_depth += 1;
+        self.extract_branch_points_from_block(walk, def.body, loop_vars);
+        walk.helper_depth -= 1;
 
-            let mut results = Vec::new();
+        // The helper's locals go out of scope, and a `return` in it only leaves the helper
+        walk.storage_locals.truncate(locals);
+        walk.call_results.retain(|(_, index)| *index < start);
+        let mut inlined = fork_helper_returns(walk.branch_points.split_off(start));
 
-            for function in functions {
-                let params = self.extract_parameters(function);
-                let signature = self.get_function_signature(function);
-                let mut branch_points = Vec::new();
+        let bindings: Vec<(String, String)> = def
+            .params
+            .iter()
+            .cloned()
+            .zip(args.iter().cloned())
+            .filter(|(param, arg)| param != arg)
+            .collect();
+        for bp in &mut inlined {
+            self.bind_params(bp, &bindings, walk);
+        }
+        walk.branch_points.append(&mut inlined);
+    }
 
-                for modifier in function.header.modifiers.iter() {
-                    let modifier_name = modifier.name.last().as_str();
-                    if let Some(def) = modifier_defs.iter().find(|(name, _)| name == modifier_name)
-                    {
-                        if let Some(body) = &def.1 {
-                            self.extract_branch_points_from_block(
-                                body,
-                                &state_vars,
-                                &params,
-                                &mut branch_points,
-                                false,
-                            );
-                        }
-                    }
-                }
-
-                if let Some(body) = &function.body {
-                    self.extract_branch_points_from_block(
-                        body,
-                        &state_vars,
-                        &params,
-                        &mut branch_points,
-                        false,
-                    );
-                }
+    /// Inline a modifier body. Checks before the `_` placeholder go straight into `walk`;
+    /// the ones after it are returned so they can be placed after the function body.
+    /// Without a top-level `_`, the whole body counts as running before the function.
+    fn extract_modifier_branch_points(
+        &self,
+        walk: &mut Walk<'_, '_>,
+        body: &ast::Block<'_>,
+    ) -> Vec<BranchPoint> {
+        let placeholder = body
+            .stmts
+            .iter()
+            .position(|stmt| matches!(stmt.kind, ast::StmtKind::Placeholder))
+            .unwrap_or(body.stmts.len());
+        let (pre, post) = body.stmts.split_at(placeholder);
 
-                results.push(FunctionContext {
-                    function_name: function_name.to_string(),
-                    signature,
-                    branch_points,
-                    parameters: params,
-                    state_variables: state_vars.clone(),
-                });
-            }
+        for stmt in pre {
+            self.extract_branch_points_from_stmt(walk, stmt, None);
+        }
 
-            Ok(results)
-        })
+        let start = walk.branch_points.len();
+        for stmt in post {
+            self.extract_branch_points_from_stmt(walk, stmt, None);
+        }
+        walk.branch_points.split_off(start)
     }
 
-    /// Parse all public and external functions in a contract
-    pub fn parse_all_public_functions(
+    /// Inline a modifier defined in a parent contract of `source_unit`, the parsed
+    /// `file_path`. Returns `None` if the contract doesn't define it, otherwise the
+    /// checks that run after `_`
+    fn extract_specific_modifier_branch_points(
         &self,
+        walk: &mut Walk<'_, '_>,
         file_path: &Path,
+        source_unit: &ast::SourceUnit<'_>,
         contract_name: &str,
-    ) -> Result<Vec<FunctionContext>, ParserError> {
+        modifier_name: &str,
+        args: &[String],
+    ) -> Result<Option<Vec<BranchPoint>>, ParserError> {
+        let contract = self.find_contract(source_unit, contract_name)?;
+
+        // Also collect parent state vars
+        let parent_state_vars = self.extract_state_variables(&contract.body);
+        let combined_state_vars: Vec<String> = walk
+            .state_vars
+            .iter()
+            .cloned()
+            .chain(parent_state_vars)
+            .collect();
+
+        // Find the modifier
+        let modifier_defs = self.extract_modifier_definitions(&contract.body);
+        let Some(def) = modifier_defs.iter().find(|def| def.name == modifier_name) else {
+            return Ok(None);
+        };
+
+        let mut inner = Walk::new(
+            walk.sess,
+            file_path,
+            &combined_state_vars,
+            walk.params,
+            // `using` directives aren't inherited, so the child's don't apply here
+            &[],
+        );
+        let post = self.inline_modifier(&mut inner, def, args);
+        walk.branch_points.append(&mut inner.branch_points);
+        walk.warnings.append(&mut inner.warnings);
+
+        Ok(Some(post))
+    }
+
+    /// Get every contract defined in a Solidity file with the names of its direct base contracts
+    pub fn get_contract_bases(
+        &self,
+        file_path: &Path,
+    ) -> Result<Vec<(String, Vec<String>)>, ParserError> {
         let sess = Session::builder().with_silent_emitter(None).build();
 
         sess.enter(|| {
             let arena = ast::Arena::new();
-            let mut parser = Parser::from_file(&sess, &arena, file_path)
-                .map_err(|e| ParserError::ParseError(format!("{:?}", e)))?;
+            let mut parser = self.open(&sess, &arena, file_path)?;
 
             let source_unit = parser.parse_file().map_err(|e| {
                 e.emit();
                 ParserError::ParseError(file_path.display().to_string())
             })?;
 
-            let contract = self.find_contract(&source_unit, contract_name)?;
-            let state_vars = self.extract_state_variables(contract);
-            let modifier_defs = self.extract_modifier_definitions(contract);
-            let functions = self.find_all_public_external_functions(contract);
-
-            let mut results = Vec::new();
-
-            for function in functions {
-                let func_name = function
-                    .header
-                    .name
-                    .as_ref()
-                    .map(|n| n.to_string())
-                    .unwrap_or_default();
-                let params = self.extract_parameters(function);
-                let signature = self.get_function_signature(function);
-                let mut branch_points = Vec::new();
-
-                for modifier in function.header.modifiers.iter() {
-                    let modifier_name = modifier.name.last().as_str();
-                    if let Some(def) = modifier_defs.iter().find(|(name, _)| name == modifier_name)
-                    {
-                        if let Some(body) = &def.1 {
-                            self.extract_branch_points_from_block(
-                                body,
-                                &state_vars,
-                                &params,
-                                &mut branch_points,
-                                false,
-                            );
-                        }
-                    }
-                }
-
-                if let Some(body) = &function.body {
-                    self.extract_branch_points_from_block(
-                        body,
-                        &state_vars,
-                        &params,
-                        &mut branch_points,
-                        false,
-                    );
+            let mut contracts = Vec::new();
+            for item in source_unit.items.iter() {
+                if let ItemKind::Contract(contract) = &item.kind {
+                    let bases = contract
+                        .bases
+                        .iter()
+                        .map(|base| base.name.last().to_string())
+                        .collect();
+                    contracts.push((contract.name.to_string(), bases));
                 }
-
-                results.push(FunctionContext {
-                    function_name: func_name,
-                    signature,
-                    branch_points,
-                    parameters: params,
-                    state_variables: state_vars.clone(),
-                });
             }
 
-            Ok(results)
+            Ok(contracts)
         })
     }
 
-    /// Get all contract names defined in a Solidity file
-    pub fn get_contract_names(&self, file_path: &Path) -> Result<Vec<String>, ParserError> {
+    /// Get the names of all functions defined in any contract of a Solidity file
+    pub fn get_function_names(&self, file_path: &Path) -> Result<Vec<String>, ParserError> {
         let sess = Session::builder().with_silent_emitter(None).build();
 
         sess.enter(|| {
             let arena = ast::Arena::new();
-            let mut parser = Parser::from_file(&sess, &arena, file_path)
-                .map_err(|e| ParserError::ParseError(format!("{:?}", e)))?;
+            let mut parser = self.open(&sess, &arena, file_path)?;
 
             let source_unit = parser.parse_file().map_err(|e| {
                 e.emit();
                 ParserError::ParseError(file_path.display().to_string())
             })?;
 
-            let mut contracts = Vec::new();
+            let mut functions = Vec::new();
             for item in source_unit.items.iter() {
                 if let ItemKind::Contract(contract) = &item.kind {
-                    contracts.push(contract.name.to_string());
+                    for item in contract.body.iter() {
+                        if let ItemKind::Function(func) = &item.kind {
+                            if let Some(name) = &func.header.name {
+                                functions.push(name.to_string());
+                            }
+                        }
+                    }
                 }
             }
 
-            Ok(contracts)
+            Ok(functions)
         })
     }
 
@@ -596,105 +1004,50 @@ impl<'a> SolarParser<'a> {
         Err(ParserError::ContractNotFound(name.to_string()))
     }
 
-    fn find_function<'ast>(
-        &self,
-        contract: &'ast ast::ItemContract<'ast>,
-        name: &str,
-    ) -> Result<&'ast ast::ItemFunction<'ast>, ParserError> {
-        for item in contract.body.iter() {
-            if let ItemKind::Function(func) = &item.kind {
-                if let Some(func_name) = &func.header.name {
-                    if func_name.as_str() == name {
-                        return Ok(func);
-                    }
+    /// Enums, structs and value types declared in the file or among `items`, the body of
+    /// the contract
+    fn type_names(&self, source_unit: &ast::SourceUnit<'_>, items: &[ast::Item<'_>]) -> TypeNames {
+        let mut types = TypeNames::default();
+        for item in source_unit.items.iter().chain(items.iter()) {
+            match &item.kind {
+                ItemKind::Enum(item_enum) => {
+                    types.enums.push(item_enum.name.to_string());
+                    types.values.push(item_enum.name.to_string());
                 }
+                ItemKind::Struct(item_struct) => types.values.push(item_struct.name.to_string()),
+                ItemKind::Udvt(udvt) => types.values.push(udvt.name.to_string()),
+                _ => {}
             }
         }
-        Err(ParserError::FunctionNotFound(
-            name.to_string(),
-            contract.name.to_string(),
-        ))
+        types
     }
 
-    /// Find all functions with the given name (for handling overloads)
-    fn find_all_functions_by_name<'ast>(
-        &self,
-        contract: &'ast ast::ItemContract<'ast>,
-        name: &str,
-    ) -> Vec<&'ast ast::ItemFunction<'ast>> {
-        let mut functions = Vec::new();
-        for item in contract.body.iter() {
-            if let ItemKind::Function(func) = &item.kind {
-                if let Some(func_name) = &func.header.name {
-                    if func_name.as_str() == name {
-                        functions.push(func);
-                    }
-                }
+    /// Whether a declared type is a contract or interface: an unqualified user-defined
+    /// type that isn't one of the enums, structs or value types in `values`.
+    /// Structs imported from other files can't be told apart and count as contracts
+    fn is_contract_type(&self, ty: &ast::Type<'_>, values: &[String]) -> bool {
+        match &ty.kind {
+            ast::TypeKind::Custom(path) => {
+                path.segments().len() == 1 && !values.iter().any(|v| v == path.last().as_str())
             }
+            _ => false,
         }
-        functions
     }
 
-    /// Find all public and external functions in a contract (excludes modifiers, constructors, internal, private)
-    fn find_all_public_external_functions<'ast>(
-        &self,
-        contract: &'ast ast::ItemContract<'ast>,
-    ) -> Vec<&'ast ast::ItemFunction<'ast>> {
-        use ast::FunctionKind;
-        use ast::Visibility;
-
-        let mut functions = Vec::new();
-        for item in contract.body.iter() {
-            if let ItemKind::Function(func) = &item.kind {
-                // Skip modifiers, constructors, fallback, receive
-                if func.kind != FunctionKind::Function {
-                    continue;
-                }
-
-                // Must have a name
-                if func.header.name.is_none() {
-                    continue;
-                }
-
-                // Check visibility - only public and external
-                let is_public_or_external = match &func.header.visibility {
-                    Some(spanned) => {
-                        matches!(spanned.data, Visibility::Public | Visibility::External)
-                    }
-                    None => true, // Default visibility for functions is public in Solidity
-                };
-
-                if is_public_or_external {
-                    functions.push(func);
-                }
-            }
+    /// Visibility of a regular function, defaulting to public when unspecified.
+    /// `None` for modifiers, constructors, `fallback` and `receive`
+    fn function_visibility(&self, function: &ast::ItemFunction<'_>) -> Option<ast::Visibility> {
+        if function.kind != ast::FunctionKind::Function {
+            return None;
         }
-        functions
-    }
 
-    /// Find a function by name and parameter signature (e.g., "address,uint256")
-    fn find_function_by_signature<'ast>(
-        &self,
-        contract: &'ast ast::ItemContract<'ast>,
-        name: &str,
-        signature: &str,
-    ) -> Result<&'ast ast::ItemFunction<'ast>, ParserError> {
-        for item in contract.body.iter() {
-            if let ItemKind::Function(func) = &item.kind {
-                if let Some(func_name) = &func.header.name {
-                    if func_name.as_str() == name {
-                        let func_sig = self.get_function_signature(func);
-                        if func_sig == signature {
-                            return Ok(func);
-                        }
-                    }
-                }
-            }
-        }
-        Err(ParserError::FunctionNotFound(
-            format!("{}({})", name, signature),
-            contract.name.to_string(),
-        ))
+        Some(
+            function
+                .header
+                .visibility
+                .as_ref()
+                .map_or(ast::Visibility::Public, |spanned| spanned.data),
+        )
     }
 
     /// Extract the parameter type signature from a function (e.g., "address,uint256")
@@ -723,15 +1076,19 @@ impl<'a> SolarParser<'a> {
 
     fn extract_modifier_definitions<'ast>(
         &self,
-        contract: &'ast ast::ItemContract<'ast>,
-    ) -> Vec<(String, Option<&'ast ast::Block<'ast>>)> {
+        items: &'ast [ast::Item<'ast>],
+    ) -> Vec<ModifierDef<'ast>> {
         let mut modifiers = Vec::new();
 
-        for item in contract.body.iter() {
+        for item in items.iter() {
             if let ItemKind::Function(func) = &item.kind {
                 if func.kind == ast::FunctionKind::Modifier {
                     if let Some(name) = &func.header.name {
-                        modifiers.push((name.to_string(), func.body.as_ref()));
+                        modifiers.push(ModifierDef {
+                            name: name.to_string(),
+                            params: self.extract_parameters(func),
+                            body: func.body.as_ref(),
+                        });
                     }
                 }
             }
@@ -740,179 +1097,985 @@ impl<'a> SolarParser<'a> {
         modifiers
     }
 
-    fn extract_state_variables(&self, contract: &ast::ItemContract<'_>) -> Vec<String> {
+    fn extract_helper_definitions<'ast>(
+        &self,
+        items: &'ast [ast::Item<'ast>],
+    ) -> Vec<HelperDef<'ast>> {
+        items
+            .iter()
+            .filter_map(|item| match &item.kind {
+                ItemKind::Function(func) => Some(func),
+                _ => None,
+            })
+            .filter(|func| {
+                matches!(
+                    self.function_visibility(func),
+                    Some(ast::Visibility::Internal | ast::Visibility::Private)
+                )
+            })
+            .filter_map(|func| {
+                Some(HelperDef {
+                    name: func.header.name.as_ref()?.to_string(),
+                    params: self.extract_parameters(func),
+                    body: func.body.as_ref()?,
+                })
+            })
+            .collect()
+    }
+
+    fn extract_state_variables(&self, items: &[ast::Item<'_>]) -> Vec<String> {
         let mut vars = Vec::new();
 
-        for item in contract.body.iter() {
+        for item in items.iter() {
             if let ItemKind::Variable(var) = &item.kind {
                 if let Some(name) = &var.name {
                     vars.push(name.to_string());
                 }
             }
         }
-
-        vars
+
+        vars
+    }
+
+    /// Constants declared among `items` with the Solidity of their values, as (name, value)
+    fn constant_values(&self, items: &[ast::Item<'_>]) -> Vec<(String, String)> {
+        items
+            .iter()
+            .filter_map(|item| match &item.kind {
+                ItemKind::Variable(var) if var.mutability == Some(ast::VarMut::Constant) => Some((
+                    var.name?.to_string(),
+                    self.expr_to_string(var.initializer.as_ref()?),
+                )),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Write the constants compared in each condition as their values (`MAX` -> `100`).
+    /// Conditions that change are classified again, since a value doesn't read state
+    fn resolve_constants(
+        &self,
+        branch_points: &mut [BranchPoint],
+        constants: &[(String, String)],
+        walk: &Walk<'_, '_>,
+    ) {
+        for bp in branch_points {
+            if substitute_constants(&mut bp.condition, constants) && !bp.is_external_call {
+                bp.context = self.classify_condition(&bp.condition, walk);
+            }
+            if let Some(arms) = &mut bp.arms {
+                self.resolve_constants(&mut arms.then_checks, constants, walk);
+                self.resolve_constants(&mut arms.else_checks, constants, walk);
+            }
+        }
+    }
+
+    fn extract_parameters(&self, function: &ast::ItemFunction<'_>) -> Vec<String> {
+        function
+            .header
+            .parameters
+            .iter()
+            .filter_map(|p| p.name.as_ref().map(|n| n.to_string()))
+            .collect()
+    }
+
+    fn extract_branch_points_from_block(
+        &self,
+        walk: &mut Walk<'_, '_>,
+        block: &ast::Block<'_>,
+        loop_vars: Option<&[String]>,
+    ) {
+        for stmt in block.stmts.iter() {
+            self.extract_branch_points_from_stmt(walk, stmt, loop_vars);
+        }
+    }
+
+    fn extract_branch_points_from_stmt(
+        &self,
+        walk: &mut Walk<'_, '_>,
+        stmt: &ast::Stmt<'_>,
+        loop_vars: Option<&[String]>,
+    ) {
+        use ast::ExprKind::*;
+        use ast::StmtKind::*;
+
+        match &stmt.kind {
+            // require(condition, message) or require(condition)
+            Expr(expr) => {
+                // Checked conversions run before the statement that uses their result
+                self.push_overflow_checks(walk, expr, loop_vars);
+
+                // cond ? doSomething() : revert("...")
+                self.push_ternary_guard(walk, expr, loop_vars);
+
+                // (ok, ) = target.call(data)
+                if let Assign(lhs, None, rhs) = &expr.kind {
+                    self.push_ternary_guard(walk, rhs, loop_vars);
+
+                    if let Some(call_desc) = self.low_level_call(rhs) {
+                        let flag = match &lhs.kind {
+                            Tuple(items) => items.first().and_then(|item| item.as_deref()),
+                            _ => None,
+                        };
+                        let flag = flag.and_then(|item| match &item.kind {
+                            Ident(ident) => Some(ident.to_string()),
+                            _ => None,
+                        });
+                        self.push_low_level_call(walk, call_desc, rhs, flag, loop_vars);
+                    }
+                }
+
+                if let Call(callee, args) = &expr.kind {
+                    // Handle require/assert
+                    if let Ident(ident) = &callee.kind {
+                        let name = ident.as_str();
+                        if name == "require" || name == "assert" {
+                            if let Some(first_arg) = args.exprs().next() {
+                                let reason = args
+                                    .exprs()
+                                    .nth(1)
+                                    .and_then(|arg| self.custom_error_name(arg));
+                                let message =
+                                    args.exprs().nth(1).and_then(|arg| self.string_literal(arg));
+
+                                // `require(ok)` after a low-level call is covered by the call's branch
+                                if let Some(call) = walk.call_branch(first_arg) {
+                                    call.revert_reason = reason;
+                                    call.revert_message = message;
+                                    return;
+                                }
+
+                                let condition = self.expr_to_condition(first_arg);
+                                if condition.is_none() {
+                                    self.warn_unhandled_condition(walk, first_arg);
+                                }
+                                if let Some(condition) = condition {
+                                    let context = self.classify_condition(&condition, walk);
+                                    let source = self
+                                        .source_snippet(walk.sess, first_arg.span)
+                                        .map(|cond| format!("{}({})", name, cond));
+                                    // `assert(false)` marks code that can't run, and `require(true)`
+                                    // never fails
+                                    let kind = match self.literal_bool(first_arg) {
+                                        Some(true) => return,
+                                        Some(false) if name == "assert" => CheckKind::Unreachable,
+                                        _ if name == "assert" => CheckKind::Assert,
+                                        _ => CheckKind::Require,
+                                    };
+                                    walk.branch_points.push(BranchPoint {
+                                        condition,
+                                        context,
+                                        is_loop: self.is_per_item_check(first_arg, loop_vars),
+                                        is_unchecked: walk.in_unchecked,
+                                        is_external_call: false,
+                                        is_if_revert: false,
+                                        kind,
+                                        source,
+                                        span: walk.location(first_arg.span),
+                                        revert_reason: reason,
+                                        revert_message: message,
+                                        arms: None,
+                                    });
+                                }
+                            }
+                        } else if self.is_unreachable_revert(expr) {
+                            walk.branch_points.push(BranchPoint {
+                                condition: ConditionExpr::Ident("false".to_string()),
+                                context: ConditionContext::External,
+                                is_loop: false,
+                                is_unchecked: walk.in_unchecked,
+                                is_external_call: false,
+                                is_if_revert: false,
+                                kind: CheckKind::Unreachable,
+                                source: self.source_snippet(walk.sess, expr.span),
+                                span: walk.location(expr.span),
+                                revert_reason: None,
+                                revert_message: None,
+                                arms: None,
+                            });
+                        } else {
+                            let args: Vec<String> =
+                                args.exprs().map(|arg| self.expr_to_string(arg)).collect();
+                            self.inline_helper(walk, name, &args, loop_vars);
+                        }
+                    }
+
+                    // Handle ALL external calls (member function calls on objects)
+                    // This includes contract calls, library calls, and low-level calls
+                    // (with or without `{value: ...}` options)
+                    let callee = match &callee.kind {
+                        CallOptions(inner, _) => inner,
+                        _ => callee,
+                    };
+                    let member_call = match &callee.kind {
+                        Member(base, member) if !self.is_overflow_call(member.as_str()) => {
+                            Some((base, member))
+                        }
+                        _ => None,
+                    };
+                    if let Some((base, member)) = member_call {
+                        let method_name = member.as_str();
+                        let obj_name = self.expr_to_string(base);
+                        let mut call_desc = format!("{}.{}", obj_name, method_name);
+
+                        // `token.safeTransfer` through `using SafeERC20 for IERC20`
+                        if let Some((_, library)) =
+                            walk.library_methods.iter().find(|(m, _)| m == method_name)
+                        {
+                            call_desc = format!("{} ({})", call_desc, library);
+                        }
+
+                        walk.branch_points.push(BranchPoint {
+                            condition: ConditionExpr::ExternalCall(call_desc),
+                            context: ConditionContext::External,
+                            is_loop: loop_vars.is_some(),
+                            is_unchecked: walk.in_unchecked,
+                            is_external_call: true,
+                            is_if_revert: false,
+                            kind: CheckKind::Require,
+                            source: self.source_snippet(walk.sess, expr.span),
+                            span: walk.location(expr.span),
+                            revert_reason: None,
+                            revert_message: None,
+                            arms: None,
+                        });
+                    }
+                }
+            }
+
+            // if (condition) { ... revert ... }
+            If(cond, then_stmt, else_stmt) => {
+                self.push_overflow_checks(walk, cond, loop_vars);
+
+                // `if (!ok) revert ...` after a low-level call is covered by the call's branch
+                let call_flag = match &cond.kind {
+                    Unary(op, inner) if op.kind == ast::UnOpKind::Not => Some(inner),
+                    _ => None,
+                };
+                let covered_call = self.stmt_contains_revert(then_stmt)
+                    && call_flag.is_some_and(|flag| walk.call_branch(flag).is_some());
+
+                if covered_call {
+                    let reason = self.revert_error_name(then_stmt);
+                    let message = self.revert_string(then_stmt);
+                    if let Some(call) = call_flag.and_then(|flag| walk.call_branch(flag)) {
+                        call.revert_reason = reason;
+                        call.revert_message = message;
+                    }
+                } else if self.stmt_contains_revert(then_stmt) {
+                    let condition = self.expr_to_condition(cond);
+                    if condition.is_none() {
+                        self.warn_unhandled_condition(walk, cond);
+                    }
+                    if let Some(condition) = condition {
+                        let context = self.classify_condition(&condition, walk);
+                        walk.branch_points.push(BranchPoint {
+                            condition,
+                            context,
+                            is_loop: self.is_per_item_check(cond, loop_vars),
+                            is_unchecked: walk.in_unchecked,
+                            is_external_call: false,
+                            is_if_revert: true,
+                            kind: CheckKind::Require,
+                            source: self
+                                .source_snippet(walk.sess, cond.span)
+                                .map(|cond| format!("if ({})", cond)),
+                            span: walk.location(cond.span),
+                            revert_reason: self.revert_error_name(then_stmt),
+                            revert_message: self.revert_string(then_stmt),
+                            arms: None,
+                        });
+                    }
+                } else if let Some(else_revert) = else_stmt
+                    .as_deref()
+                    .filter(|stmt| self.stmt_contains_revert(stmt))
+                {
+                    // `if (ok) { ... } else { revert(); }` guards like `require(ok)`: the
+                    // false case reverts and the then-branch carries on. When both arms
+                    // revert, the then-arm above already recorded the check
+                    let reason = self.revert_error_name(else_revert);
+                    let message = self.revert_string(else_revert);
+                    if let Some(call) = walk.call_branch(cond) {
+                        call.revert_reason = reason;
+                        call.revert_message = message;
+                    } else if let Some(condition) = self.expr_to_condition(cond) {
+                        let context = self.classify_condition(&condition, walk);
+                        walk.branch_points.push(BranchPoint {
+                            condition,
+                            context,
+                            is_loop: self.is_per_item_check(cond, loop_vars),
+                            is_unchecked: walk.in_unchecked,
+                            is_external_call: false,
+                            is_if_revert: false,
+                            kind: CheckKind::Require,
+                            source: self
+                                .source_snippet(walk.sess, cond.span)
+                                .map(|cond| format!("if ({})", cond)),
+                            span: walk.location(cond.span),
+                            revert_reason: reason,
+                            revert_message: message,
+                            arms: None,
+                        });
+                    } else {
+                        self.warn_unhandled_condition(walk, cond);
+                    }
+
+                    self.extract_branch_points_from_stmt(walk, then_stmt, loop_vars);
+                    return;
+                } else if let Some(kind) = self.stop_kind(then_stmt, loop_vars.is_some()) {
+                    // Guard clause: `if (cond) return;` ends the function without reverting,
+                    // and `if (cond) continue;` or `break;` ends the item or the loop. Checks
+                    // in its body run before it stops
+                    let then_checks =
+                        self.extract_arm(walk, std::slice::from_ref(then_stmt), loop_vars);
+                    if let Some(condition) = self.expr_to_condition(cond) {
+                        let context = self.classify_condition(&condition, walk);
+                        walk.branch_points.push(BranchPoint {
+                            condition,
+                            context,
+                            is_loop: self.is_per_item_check(cond, loop_vars),
+                            is_unchecked: walk.in_unchecked,
+                            is_external_call: false,
+                            is_if_revert: true,
+                            kind,
+                            source: self
+                                .source_snippet(walk.sess, cond.span)
+                                .map(|cond| format!("if ({})", cond)),
+                            span: walk.location(cond.span),
+                            revert_reason: None,
+                            revert_message: None,
+                            arms: (!then_checks.is_empty()).then(|| Arms {
+                                then_checks,
+                                else_checks: Vec::new(),
+                            }),
+                        });
+                    } else {
+                        self.warn_unhandled_condition(walk, cond);
+                        walk.branch_points.extend(then_checks);
+                    }
+                } else {
+                    // Neither arm stops the function, so the tree forks on the condition
+                    self.push_if_else(walk, cond, then_stmt, else_stmt.as_deref(), loop_vars);
+                    return;
+                }
+
+                if let Some(else_stmt) = else_stmt {
+                    self.extract_branch_points_from_stmt(walk, else_stmt, loop_vars);
+                }
+            }
+
+            // uint256 x = cond ? a : revert("...")
+            DeclSingle(var) => {
+                if let Some(init) = &var.initializer {
+                    self.push_overflow_checks(walk, init, loop_vars);
+                    self.push_ternary_guard(walk, init, loop_vars);
+
+                    // A local holding a state read is labeled like the state itself
+                    if let Some(name) = var.name.filter(|_| self.reads_storage(init, walk)) {
+                        walk.storage_locals.push(name.to_string());
+                    }
+                }
+                if let Some(name) = var
+                    .name
+                    .filter(|_| self.is_contract_type(&var.ty, walk.value_types))
+                {
+                    walk.contract_vars.push(name.to_string());
+                }
+            }
+
+            // (bool ok, bytes memory data) = target.call(data)
+            DeclMulti(vars, init) => {
+                self.push_overflow_checks(walk, init, loop_vars);
+                if let Some(call_desc) = self.low_level_call(init) {
+                    let flag = vars
+                        .first()
+                        .and_then(Option::as_ref)
+                        .and_then(|var| var.name)
+                        .map(|name| name.to_string());
+                    self.push_low_level_call(walk, call_desc, init, flag, loop_vars);
+                }
+            }
+
+            // for loop - track the induction variable so counter checks aren't "any" checks
+            For {
+                init, cond, body, ..
+            } => {
+                let mut counters = loop_vars.map(<[String]>::to_vec).unwrap_or_default();
+                counters.extend(self.loop_counter(init.as_deref(), cond.as_deref()));
+                self.extract_branch_points_from_stmt(walk, body, Some(&counters));
+            }
+
+            // while loop
+            While(_, body) => {
+                self.extract_branch_points_from_stmt(
+                    walk,
+                    body,
+                    Some(loop_vars.unwrap_or_default()),
+                );
+            }
+
+            // do-while loop
+            DoWhile(body, _) => {
+                self.extract_branch_points_from_stmt(
+                    walk,
+                    body,
+                    Some(loop_vars.unwrap_or_default()),
+                );
+            }
+
+            // try/catch: the first clause runs on success, the others on failure
+            Try(try_stmt) => {
+                let Some((success, catches)) = try_stmt.clauses.split_first() else {
+                    return;
+                };
+                let catch_stmts = || catches.iter().flat_map(|clause| clause.block.stmts.iter());
+                let rethrows = catches.iter().all(|clause| {
+                    clause
+                        .block
+                        .stmts
+                        .iter()
+                        .any(|s| self.stmt_contains_revert(s))
+                });
+
+                // A catch that rethrows makes the failure a revert, and the success
+                // block's checks run before the rest of the function. Otherwise the
+                // failure is handled and each side continues with its own checks
+                let arms = if rethrows {
+                    None
+                } else {
+                    Some(Arms {
+                        then_checks: self.extract_arm(walk, &success.block.stmts, loop_vars),
+                        else_checks: catches
+                            .iter()
+                            .flat_map(|clause| {
+                                self.extract_arm(walk, &clause.block.stmts, loop_vars)
+                            })
+                            .collect(),
+                    })
+                };
+
+                let call_name = self.expr_to_string(&try_stmt.expr);
+                walk.branch_points.push(BranchPoint {
+                    condition: ConditionExpr::ExternalCall(call_name),
+                    context: ConditionContext::External,
+                    is_loop: loop_vars.is_some(),
+                    is_unchecked: walk.in_unchecked,
+                    is_external_call: true,
+                    is_if_revert: false,
+                    kind: CheckKind::Require,
+                    source: self
+                        .source_snippet(walk.sess, try_stmt.expr.span)
+                        .map(|call| format!("try {}", call)),
+                    span: walk.location(try_stmt.expr.span),
+                    revert_reason: rethrows
+                        .then(|| catch_stmts().find_map(|s| self.revert_error_name(s)))
+                        .flatten(),
+                    revert_message: rethrows
+                        .then(|| catch_stmts().find_map(|s| self.revert_string(s)))
+                        .flatten(),
+                    arms,
+                });
+
+                if rethrows {
+                    self.extract_branch_points_from_block(walk, &success.block, loop_vars);
+                }
+            }
+
+            // return amount.toUint128();
+            Return(Some(expr)) => self.push_overflow_checks(walk, expr, loop_vars),
+
+            // emit Deposited(amount.toUint128());
+            Emit(_, args) => {
+                for arg in args.exprs() {
+                    self.push_overflow_checks(walk, arg, loop_vars);
+                }
+            }
+
+            // Block
+            Block(block) => {
+                self.extract_branch_points_from_block(walk, block, loop_vars);
+            }
+
+            // Unchecked block
+            UncheckedBlock(block) => {
+                let outer = std::mem::replace(&mut walk.in_unchecked, true);
+                self.extract_branch_points_from_block(walk, block, loop_vars);
+                walk.in_unchecked = outer;
+            }
+
+            // assembly { if iszero(to) { revert(0, 0) } }
+            Assembly(assembly) => {
+                self.extract_branch_points_from_yul(walk, &assembly.block, loop_vars);
+            }
+
+            _ => {}
+        }
+    }
+
+    /// Whether a method is a checked conversion that reverts on overflow
+    fn is_overflow_call(&self, method: &str) -> bool {
+        self.overflow_calls.iter().any(|m| m == method)
+    }
+
+    /// Record a branch for every checked conversion in an expression, innermost first.
+    /// These still revert inside `unchecked` blocks, so they're kept there. With
+    /// `--arithmetic-overflow`, checked `+` and `*` (and `+=`, `*=`) get one too
+    fn push_overflow_checks(
+        &self,
+        walk: &mut Walk<'_, '_>,
+        expr: &ast::Expr<'_>,
+        loop_vars: Option<&[String]>,
+    ) {
+        use ast::ExprKind::*;
+
+        let children: Vec<&ast::Expr<'_>> = match &expr.kind {
+            Call(callee, args) => std::iter::once(&**callee).chain(args.exprs()).collect(),
+            CallOptions(inner, options) => std::iter::once(&**inner)
+                .chain(options.iter().map(|option| &*option.value))
+                .collect(),
+            Member(base, _) | Unary(_, base) | Delete(base) => vec![base],
+            Index(base, ast::IndexKind::Index(index)) => {
+                std::iter::once(&**base).chain(index.as_deref()).collect()
+            }
+            Binary(left, _, right) | Assign(left, _, right) => vec![left, right],
+            Ternary(cond, then_expr, else_expr) => vec![cond, then_expr, else_expr],
+            Tuple(items) => items.iter().flatten().map(|item| &**item).collect(),
+            Array(items) => items.iter().map(|item| &**item).collect(),
+            _ => Vec::new(),
+        };
+        for child in children {
+            self.push_overflow_checks(walk, child, loop_vars);
+        }
+
+        if let Some(operation) = self.overflowing_arithmetic(expr, walk) {
+            walk.branch_points.push(BranchPoint {
+                condition: ConditionExpr::ExternalCall(operation),
+                context: ConditionContext::External,
+                is_loop: loop_vars.is_some(),
+                is_unchecked: false,
+                is_external_call: true,
+                is_if_revert: false,
+                kind: CheckKind::Overflow,
+                source: self.source_snippet(walk.sess, expr.span),
+                span: walk.location(expr.span),
+                revert_reason: None,
+                revert_message: None,
+                arms: None,
+            });
+            return;
+        }
+
+        // `amount.toUint128()` through `using SafeCast for uint256`, or
+        // `SafeCast.toUint128(amount)`
+        let Call(callee, args) = &expr.kind else {
+            return;
+        };
+        let Member(base, method) = &callee.kind else {
+            return;
+        };
+        if !self.is_overflow_call(method.as_str()) {
+            return;
+        }
+        let value = args.exprs().next().unwrap_or(base);
+
+        walk.branch_points.push(BranchPoint {
+            condition: ConditionExpr::ExternalCall(format!(
+                "{}.{}",
+                self.expr_to_string(value),
+                method
+            )),
+            context: ConditionContext::External,
+            is_loop: loop_vars.is_some(),
+            is_unchecked: walk.in_unchecked,
+            is_external_call: true,
+            is_if_revert: false,
+            kind: CheckKind::Overflow,
+            source: self.source_snippet(walk.sess, expr.span),
+            span: walk.location(expr.span),
+            revert_reason: None,
+            revert_message: None,
+            arms: None,
+        });
+    }
+
+    /// A checked addition or multiplication that reverts on overflow, as `a + b`.
+    /// Operations on literals alone are folded by the compiler and never overflow
+    fn overflowing_arithmetic(&self, expr: &ast::Expr<'_>, walk: &Walk<'_, '_>) -> Option<String> {
+        use ast::ExprKind::*;
+
+        if !self.arithmetic_overflow || walk.in_unchecked {
+            return None;
+        }
+        let (left, op, right) = match &expr.kind {
+            Binary(left, op, right) | Assign(left, Some(op), right) => (left, op, right),
+            _ => return None,
+        };
+        if !matches!(op.kind, ast::BinOpKind::Add | ast::BinOpKind::Mul)
+            || matches!((&left.kind, &right.kind), (Lit(..), Lit(..)))
+        {
+            return None;
+        }
+
+        Some(format!(
+            "{} {} {}",
+            self.expr_to_string(left),
+            binary_op_symbol(op.kind),
+            self.expr_to_string(right)
+        ))
+    }
+
+    /// Description of a low-level call (`target.call`, `target.delegatecall`,
+    /// `target.staticcall`), looking through `{value: ...}` call options
+    fn low_level_call(&self, expr: &ast::Expr<'_>) -> Option<String> {
+        use ast::ExprKind::*;
+
+        let Call(callee, _) = &expr.kind else {
+            return None;
+        };
+        let callee = match &callee.kind {
+            CallOptions(inner, _) => inner,
+            _ => callee,
+        };
+        let Member(base, member) = &callee.kind else {
+            return None;
+        };
+
+        matches!(member.as_str(), "call" | "delegatecall" | "staticcall")
+            .then(|| format!("{}.{}", self.expr_to_string(base), member.as_str()))
+    }
+
+    /// Record a low-level call as an external-call branch point, remembering its
+    /// success flag so a later `require(ok)` doesn't add a second branch
+    fn push_low_level_call(
+        &self,
+        walk: &mut Walk<'_, '_>,
+        call_desc: String,
+        call: &ast::Expr<'_>,
+        flag: Option<String>,
+        loop_vars: Option<&[String]>,
+    ) {
+        if let Some(flag) = flag {
+            walk.call_results.push((flag, walk.branch_points.len()));
+        }
+        walk.branch_points.push(BranchPoint {
+            condition: ConditionExpr::ExternalCall(call_desc),
+            context: ConditionContext::External,
+            is_loop: loop_vars.is_some(),
+            is_unchecked: walk.in_unchecked,
+            is_external_call: true,
+            is_if_revert: false,
+            kind: CheckKind::Require,
+            source: self.source_snippet(walk.sess, call.span),
+            span: walk.location(call.span),
+            revert_reason: None,
+            revert_message: None,
+            arms: None,
+        });
+    }
+
+    /// Record an `if`/`else` whose arms contain checks as a fork, each side holding its
+    /// own arm's checks. Arms without any checks don't add anything
+    fn push_if_else(
+        &self,
+        walk: &mut Walk<'_, '_>,
+        cond: &ast::Expr<'_>,
+        then_stmt: &ast::Stmt<'_>,
+        else_stmt: Option<&ast::Stmt<'_>>,
+        loop_vars: Option<&[String]>,
+    ) {
+        let then_checks = self.extract_arm(walk, std::slice::from_ref(then_stmt), loop_vars);
+        let else_checks = else_stmt
+            .map(|else_stmt| self.extract_arm(walk, std::slice::from_ref(else_stmt), loop_vars))
+            .unwrap_or_default();
+        if then_checks.is_empty() && else_checks.is_empty() {
+            return;
+        }
+
+        let Some(condition) = self.expr_to_condition(cond) else {
+            self.warn_unhandled_condition(walk, cond);
+            walk.branch_points.extend(then_checks);
+            walk.branch_points.extend(else_checks);
+            return;
+        };
+        let context = self.classify_condition(&condition, walk);
+
+        walk.branch_points.push(BranchPoint {
+            condition,
+            context,
+            is_loop: self.is_per_item_check(cond, loop_vars),
+            is_unchecked: walk.in_unchecked,
+            is_external_call: false,
+            is_if_revert: false,
+            kind: CheckKind::Require,
+            source: self
+                .source_snippet(walk.sess, cond.span)
+                .map(|cond| format!("if ({})", cond)),
+            span: walk.location(cond.span),
+            revert_reason: None,
+            revert_message: None,
+            arms: Some(Arms {
+                then_checks,
+                else_checks,
+            }),
+        });
+    }
+
+    /// Checks in one arm of an `if` (or clause of a `try`), collected apart from the
+    /// enclosing statements. Low-level call flags from outside the arm aren't matched
+    /// inside it
+    fn extract_arm(
+        &self,
+        walk: &mut Walk<'_, '_>,
+        stmts: &[ast::Stmt<'_>],
+        loop_vars: Option<&[String]>,
+    ) -> Vec<BranchPoint> {
+        let outer = std::mem::take(&mut walk.branch_points);
+        let outer_calls = std::mem::take(&mut walk.call_results);
+
+        for stmt in stmts {
+            self.extract_branch_points_from_stmt(walk, stmt, loop_vars);
+        }
+
+        walk.call_results = outer_calls;
+        std::mem::replace(&mut walk.branch_points, outer)
+    }
+
+    /// Record a ternary with a `revert(...)` branch as a guard on its condition:
+    /// reverting when true reads like `if (cond) revert`, reverting when false like
+    /// `require(cond)`
+    fn push_ternary_guard(
+        &self,
+        walk: &mut Walk<'_, '_>,
+        expr: &ast::Expr<'_>,
+        loop_vars: Option<&[String]>,
+    ) {
+        let ast::ExprKind::Ternary(cond, then_expr, else_expr) = &expr.kind else {
+            return;
+        };
+        let (is_if_revert, revert) = if self.is_revert_call(then_expr) {
+            (true, then_expr)
+        } else if self.is_revert_call(else_expr) {
+            (false, else_expr)
+        } else {
+            return;
+        };
+
+        let Some(condition) = self.expr_to_condition(cond) else {
+            self.warn_unhandled_condition(walk, cond);
+            return;
+        };
+        let context = self.classify_condition(&condition, walk);
+        let revert_message = match &revert.kind {
+            ast::ExprKind::Call(_, args) => {
+                args.exprs().next().and_then(|arg| self.string_literal(arg))
+            }
+            _ => None,
+        };
+
+        walk.branch_points.push(BranchPoint {
+            condition,
+            context,
+            is_loop: self.is_per_item_check(cond, loop_vars),
+            is_unchecked: walk.in_unchecked,
+            is_external_call: false,
+            is_if_revert,
+            kind: CheckKind::Require,
+            source: self.source_snippet(walk.sess, expr.span),
+            span: walk.location(expr.span),
+            revert_reason: None,
+            revert_message,
+            arms: None,
+        });
+    }
+
+    /// Whether an expression is a `revert(...)` call
+    fn is_revert_call(&self, expr: &ast::Expr<'_>) -> bool {
+        use ast::ExprKind::*;
+
+        matches!(&expr.kind, Call(callee, _) if matches!(&callee.kind, Ident(ident) if ident.as_str() == "revert"))
+    }
+
+    /// Warn about a check whose condition couldn't be turned into a label
+    fn warn_unhandled_condition(&self, walk: &mut Walk<'_, '_>, expr: &ast::Expr<'_>) {
+        let construct = match self.source_snippet(walk.sess, expr.span) {
+            Some(source) => format!("condition `{}`", source),
+            None => "condition".to_string(),
+        };
+        walk.warn(expr.span, WarningKind::UnhandledConstruct { construct });
+    }
+
+    /// Find the induction variable of a `for` loop, from its init (`uint256 i = 0` / `i = 0`)
+    /// or, failing that, the left side of its condition (`i < n`)
+    fn loop_counter(
+        &self,
+        init: Option<&ast::Stmt<'_>>,
+        cond: Option<&ast::Expr<'_>>,
+    ) -> Option<String> {
+        use ast::ExprKind;
+        use ast::StmtKind;
+
+        if let Some(init) = init {
+            match &init.kind {
+                StmtKind::DeclSingle(var) => {
+                    if let Some(name) = &var.name {
+                        return Some(name.to_string());
+                    }
+                }
+                StmtKind::Expr(expr) => {
+                    if let ExprKind::Assign(lhs, _, _) = &expr.kind {
+                        if let ExprKind::Ident(ident) = &lhs.kind {
+                            return Some(ident.to_string());
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(cond) = cond {
+            if let ExprKind::Binary(lhs, _, _) = &cond.kind {
+                if let ExprKind::Ident(ident) = &lhs.kind {
+                    return Some(ident.to_string());
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Whether a check inside a loop is about each item (gets the "any" prefix)
+    /// rather than about the loop counter itself (e.g. `require(i < maxIterations)`)
+    fn is_per_item_check(&self, expr: &ast::Expr<'_>, loop_vars: Option<&[String]>) -> bool {
+        match loop_vars {
+            Some(counters) => !self.references_counter(expr, counters),
+            None => false,
+        }
+    }
+
+    /// Whether an expression uses a loop counter as a value. Uses as an index
+    /// subscript (`amounts[i]`) don't count - those are per-item checks.
+    fn references_counter(&self, expr: &ast::Expr<'_>, counters: &[String]) -> bool {
+        use ast::ExprKind::*;
+
+        match &expr.kind {
+            Ident(ident) => counters.iter().any(|c| c == ident.as_str()),
+            Index(base, _) | Member(base, _) => self.references_counter(base, counters),
+            Binary(left, _, right) => {
+                self.references_counter(left, counters) || self.references_counter(right, counters)
+            }
+            Unary(_, inner) => self.references_counter(inner, counters),
+            Call(callee, args) => {
+                self.references_counter(callee, counters)
+                    || args
+                        .exprs()
+                        .any(|arg| self.references_counter(arg, counters))
+            }
+            Ternary(cond, then_expr, else_expr) => {
+                self.references_counter(cond, counters)
+                    || self.references_counter(then_expr, counters)
+                    || self.references_counter(else_expr, counters)
+            }
+            _ => false,
+        }
     }
 
-    fn extract_parameters(&self, function: &ast::ItemFunction<'_>) -> Vec<String> {
-        function
-            .header
-            .parameters
-            .iter()
-            .filter_map(|p| p.name.as_ref().map(|n| n.to_string()))
-            .collect()
+    /// Slice the original source for a span, collapsed onto a single line
+    fn source_snippet(&self, sess: &Session, span: Span) -> Option<String> {
+        let snippet = sess.source_map().span_to_snippet(span).ok()?;
+        Some(snippet.split_whitespace().collect::<Vec<_>>().join(" "))
     }
 
-    fn extract_branch_points_from_block(
-        &self,
-        block: &ast::Block<'_>,
-        state_vars: &[String],
-        params: &[String],
-        branch_points: &mut Vec<BranchPoint>,
-        in_loop: bool,
-    ) {
-        for stmt in block.stmts.iter() {
-            self.extract_branch_points_from_stmt(stmt, state_vars, params, branch_points, in_loop);
+    /// Name of the custom error raised by the first `revert Error(...)` in a statement
+    fn revert_error_name(&self, stmt: &ast::Stmt<'_>) -> Option<String> {
+        use ast::StmtKind::*;
+
+        match &stmt.kind {
+            Revert(path, _) => Some(path.last().to_string()),
+            Block(block) => block.stmts.iter().find_map(|s| self.revert_error_name(s)),
+            _ => None,
         }
     }
 
-    fn extract_branch_points_from_stmt(
-        &self,
-        stmt: &ast::Stmt<'_>,
-        state_vars: &[String],
-        params: &[String],
-        branch_points: &mut Vec<BranchPoint>,
-        in_loop: bool,
-    ) {
+    /// Message of the first `revert("...")` in a statement
+    fn revert_string(&self, stmt: &ast::Stmt<'_>) -> Option<String> {
         use ast::ExprKind::*;
         use ast::StmtKind::*;
 
         match &stmt.kind {
-            // require(condition, message) or require(condition)
-            Expr(expr) => {
-                if let Call(callee, args) = &expr.kind {
-                    // Handle require/assert
-                    if let Ident(ident) = &callee.kind {
-                        let name = ident.as_str();
-                        if name == "require" || name == "assert" {
-                            if let Some(first_arg) = args.exprs().next() {
-                                if let Some(condition) = self.expr_to_condition(first_arg) {
-                                    let context =
-                                        self.classify_condition(&condition, state_vars, params);
-                                    branch_points.push(BranchPoint {
-                                        condition,
-                                        context,
-                                        is_loop: in_loop,
-                                        is_external_call: false,
-                                        is_if_revert: false,
-                                    });
-                                }
-                            }
-                        }
-                    }
-
-                    // Handle ALL external calls (member function calls on objects)
-                    // This includes contract calls, library calls, and low-level calls
-                    if let Member(base, member) = &callee.kind {
-                        let method_name = member.as_str();
-                        let obj_name = self.expr_to_string(base);
-                        let call_desc = format!("{}.{}", obj_name, method_name);
-
-                        branch_points.push(BranchPoint {
-                            condition: ConditionExpr::ExternalCall(call_desc),
-                            context: ConditionContext::External,
-                            is_loop: in_loop,
-                            is_external_call: true,
-                            is_if_revert: false,
-                        });
-                    }
-                }
-            }
-
-            // if (condition) { ... revert ... }
-            If(cond, then_stmt, else_stmt) => {
-                if self.stmt_contains_revert(then_stmt) {
-                    if let Some(condition) = self.expr_to_condition(cond) {
-                        let context = self.classify_condition(&condition, state_vars, params);
-                        branch_points.push(BranchPoint {
-                            condition,
-                            context,
-                            is_loop: in_loop,
-                            is_external_call: false,
-                            is_if_revert: true,
-                        });
-                    }
-                } else {
-                    self.extract_branch_points_from_stmt(
-                        then_stmt,
-                        state_vars,
-                        params,
-                        branch_points,
-                        in_loop,
-                    );
+            Expr(expr) => match &expr.kind {
+                Call(callee, args) if matches!(&callee.kind, Ident(ident) if ident.as_str() == "revert") => {
+                    args.exprs().next().and_then(|arg| self.string_literal(arg))
                 }
+                _ => None,
+            },
+            Block(block) => block.stmts.iter().find_map(|s| self.revert_string(s)),
+            _ => None,
+        }
+    }
 
-                if let Some(else_stmt) = else_stmt {
-                    self.extract_branch_points_from_stmt(
-                        else_stmt,
-                        state_vars,
-                        params,
-                        branch_points,
-                        in_loop,
-                    );
-                }
+    /// `revert("unreachable")`, the string-revert spelling of `assert(false)`
+    fn is_unreachable_revert(&self, expr: &ast::Expr<'_>) -> bool {
+        match &expr.kind {
+            ast::ExprKind::Call(callee, args) => {
+                matches!(&callee.kind, ast::ExprKind::Ident(ident) if ident.as_str() == "revert")
+                    && args.exprs().next().and_then(|arg| self.string_literal(arg))
+                        == Some("unreachable".to_string())
             }
+            _ => false,
+        }
+    }
 
-            // for loop
-            For { body, .. } => {
-                self.extract_branch_points_from_stmt(body, state_vars, params, branch_points, true);
+    /// Value of a string literal, without quotes
+    fn string_literal(&self, expr: &ast::Expr<'_>) -> Option<String> {
+        match &expr.kind {
+            ast::ExprKind::Lit(lit, _) if matches!(lit.kind, ast::LitKind::Str(..)) => {
+                Some(lit.symbol.to_string())
             }
+            _ => None,
+        }
+    }
 
-            // while loop
-            While(_, body) => {
-                self.extract_branch_points_from_stmt(body, state_vars, params, branch_points, true);
-            }
+    /// Name of a custom error constructed as a `require` argument, e.g. `NotOwner()`
+    /// or `Errors.NotOwner(msg.sender)`. Error names are capitalized by convention,
+    /// which tells them apart from calls to ordinary functions.
+    fn custom_error_name(&self, expr: &ast::Expr<'_>) -> Option<String> {
+        use ast::ExprKind::*;
 
-            // do-while loop
-            DoWhile(body, _) => {
-                self.extract_branch_points_from_stmt(body, state_vars, params, branch_points, true);
-            }
+        let Call(callee, _) = &expr.kind else {
+            return None;
+        };
+        let name = match &callee.kind {
+            Ident(ident) => ident.to_string(),
+            Member(_, member) => member.to_string(),
+            _ => return None,
+        };
 
-            // try/catch
-            Try(try_stmt) => {
-                let call_name = self.expr_to_string(&try_stmt.expr);
-                branch_points.push(BranchPoint {
-                    condition: ConditionExpr::ExternalCall(call_name),
-                    context: ConditionContext::External,
-                    is_loop: in_loop,
-                    is_external_call: true,
-                    is_if_revert: false,
-                });
-            }
+        name.starts_with(|c: char| c.is_ascii_uppercase())
+            .then_some(name)
+    }
 
-            // Block
-            Block(block) => {
-                self.extract_branch_points_from_block(
-                    block,
-                    state_vars,
-                    params,
-                    branch_points,
-                    in_loop,
-                );
-            }
+    /// Whether a statement ends in a `return` (`return;` or a block whose last statement returns)
+    fn stmt_returns(&self, stmt: &ast::Stmt<'_>) -> bool {
+        use ast::StmtKind::*;
 
-            // Unchecked block
-            UncheckedBlock(block) => {
-                self.extract_branch_points_from_block(
-                    block,
-                    state_vars,
-                    params,
-                    branch_points,
-                    in_loop,
-                );
-            }
+        match &stmt.kind {
+            Return(_) => true,
+            Block(block) => block.stmts.last().is_some_and(|s| self.stmt_returns(s)),
+            _ => false,
+        }
+    }
 
-            _ => {}
+    /// How a statement stops without reverting: a `return`, or inside a loop a
+    /// `continue` or `break`
+    fn stop_kind(&self, stmt: &ast::Stmt<'_>, in_loop: bool) -> Option<CheckKind> {
+        use ast::StmtKind::*;
+
+        match &stmt.kind {
+            Continue if in_loop => Some(CheckKind::Continue),
+            Break if in_loop => Some(CheckKind::Break),
+            Block(block) => block.stmts.last().and_then(|s| self.stop_kind(s, in_loop)),
+            _ if self.stmt_returns(stmt) => Some(CheckKind::EarlyReturn),
+            _ => None,
         }
     }
 
@@ -922,6 +2085,8 @@ impl<'a> SolarParser<'a> {
 
         match &stmt.kind {
             Revert(..) => true,
+            // `revert("unreachable")` marks code that can't run rather than a guard
+            Expr(expr) if self.is_unreachable_revert(expr) => false,
             Expr(expr) => {
                 if let Call(callee, _) = &expr.kind {
                     if let Ident(ident) = &callee.kind {
@@ -935,6 +2100,103 @@ impl<'a> SolarParser<'a> {
         }
     }
 
+    /// Revert guards in inline assembly: `if cond { revert(..) }` and `switch` cases that
+    /// revert. Everything else in the block is ignored
+    fn extract_branch_points_from_yul(
+        &self,
+        walk: &mut Walk<'_, '_>,
+        block: &ast::yul::Block<'_>,
+        loop_vars: Option<&[String]>,
+    ) {
+        use ast::yul::StmtKind::*;
+
+        for stmt in block.stmts.iter() {
+            match &stmt.kind {
+                If(cond, body) if yul_block_reverts(body) => {
+                    let source = self
+                        .source_snippet(walk.sess, cond.span)
+                        .map(|cond| format!("if {}", cond));
+                    self.push_yul_guard(walk, yul_to_condition(cond), source, stmt.span, loop_vars);
+                }
+                If(_, body) | Block(body) => {
+                    self.extract_branch_points_from_yul(walk, body, loop_vars);
+                }
+                For { body, .. } => {
+                    self.extract_branch_points_from_yul(
+                        walk,
+                        body,
+                        Some(loop_vars.unwrap_or_default()),
+                    );
+                }
+                Switch(switch) => {
+                    let selector = yul_to_string(&switch.selector);
+                    let source = self.source_snippet(walk.sess, switch.selector.span);
+                    let case_guard = |op, constant: &ast::Lit| ConditionExpr::Binary {
+                        left: selector.clone(),
+                        op,
+                        right: literal_to_string(constant, None),
+                    };
+
+                    for case in switch.branches.iter() {
+                        if yul_block_reverts(&case.body) {
+                            let source = source.as_ref().map(|s| {
+                                format!(
+                                    "switch {} case {}",
+                                    s,
+                                    literal_to_string(&case.constant, None)
+                                )
+                            });
+                            let condition = case_guard(BinaryOp::Eq, &case.constant);
+                            self.push_yul_guard(walk, condition, source, stmt.span, loop_vars);
+                        } else {
+                            self.extract_branch_points_from_yul(walk, &case.body, loop_vars);
+                        }
+                    }
+
+                    // A reverting default only reads as one condition against a single case
+                    if let Some(default) = &switch.default_case {
+                        match &switch.branches[..] {
+                            [case] if yul_block_reverts(default) => {
+                                let source =
+                                    source.as_ref().map(|s| format!("switch {} default", s));
+                                let condition = case_guard(BinaryOp::NotEq, &case.constant);
+                                self.push_yul_guard(walk, condition, source, stmt.span, loop_vars);
+                            }
+                            _ => self.extract_branch_points_from_yul(walk, default, loop_vars),
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Record an assembly check that reverts when `condition` holds
+    fn push_yul_guard(
+        &self,
+        walk: &mut Walk<'_, '_>,
+        condition: ConditionExpr,
+        source: Option<String>,
+        span: Span,
+        loop_vars: Option<&[String]>,
+    ) {
+        let context = self.classify_condition(&condition, walk);
+        walk.branch_points.push(BranchPoint {
+            condition,
+            context,
+            is_loop: loop_vars.is_some(),
+            is_unchecked: walk.in_unchecked,
+            is_external_call: false,
+            is_if_revert: true,
+            kind: CheckKind::Require,
+            source,
+            span: walk.location(span),
+            revert_reason: None,
+            revert_message: None,
+            arms: None,
+        });
+    }
+
     fn expr_to_condition(&self, expr: &ast::Expr<'_>) -> Option<ConditionExpr> {
         use ast::BinOpKind::*;
         use ast::ExprKind::*;
@@ -985,50 +2247,102 @@ impl<'a> SolarParser<'a> {
 
             Ident(_) | Member(..) => Some(ConditionExpr::Ident(self.expr_to_string(expr))),
 
+            // Parenthesized condition: `((x > 0))`
+            Tuple(items) if items.len() == 1 => {
+                let inner = items[0].as_deref()?;
+                self.expr_to_condition(inner)
+            }
+
+            // `c ? p : q` holds when (c and p) or (not c and q)
+            Ternary(cond, then_expr, else_expr) => {
+                let cond = self.expr_to_condition(cond)?;
+                let then_cond = self.expr_to_condition(then_expr)?;
+                let else_cond = self.expr_to_condition(else_expr)?;
+                Some(ConditionExpr::Or(
+                    Box::new(ConditionExpr::And(
+                        Box::new(cond.clone()),
+                        Box::new(then_cond),
+                    )),
+                    Box::new(ConditionExpr::And(
+                        Box::new(ConditionExpr::Not(Box::new(cond))),
+                        Box::new(else_cond),
+                    )),
+                ))
+            }
+
             _ => Some(ConditionExpr::Ident(self.expr_to_string(expr))),
         }
     }
 
+    /// Value of a condition that is a boolean literal, such as the `false` of
+    /// `assert(false)`, possibly negated or parenthesized
+    fn literal_bool(&self, expr: &ast::Expr<'_>) -> Option<bool> {
+        use ast::ExprKind::*;
+
+        match &expr.kind {
+            Lit(lit, _) => match lit.kind {
+                ast::LitKind::Bool(value) => Some(value),
+                _ => None,
+            },
+            Unary(op, inner) if op.kind == ast::UnOpKind::Not => {
+                self.literal_bool(inner).map(|value| !value)
+            }
+            Tuple(items) if items.len() == 1 => self.literal_bool(items[0].as_deref()?),
+            _ => None,
+        }
+    }
+
     fn expr_to_string(&self, expr: &ast::Expr<'_>) -> String {
         use ast::ExprKind::*;
 
         match &expr.kind {
             Ident(ident) => ident.to_string(),
-            Lit(lit, _) => format!("{:?}", lit.kind),
+            Lit(lit, sub_denomination) => literal_to_string(lit, sub_denomination.as_ref()),
             Member(base, member) => {
                 format!("{}.{}", self.expr_to_string(base), member.as_str())
             }
-            Index(base, _kind) => {
-                format!("{}[...]", self.expr_to_string(base))
+            Index(base, kind) => {
+                let key = match kind {
+                    ast::IndexKind::Index(index) => index
+                        .as_deref()
+                        .map(|index| self.expr_to_string(index))
+                        .unwrap_or_default(),
+                    ast::IndexKind::Range(start, end) => format!(
+                        "{}:{}",
+                        start
+                            .as_deref()
+                            .map(|start| self.expr_to_string(start))
+                            .unwrap_or_default(),
+                        end.as_deref()
+                            .map(|end| self.expr_to_string(end))
+                            .unwrap_or_default()
+                    ),
+                };
+                format!("{}[{}]", self.expr_to_string(base), key)
+            }
+            // Type conversions keep their argument so `address(this)` can be humanized, and
+            // named calls theirs so `isWhitelisted(user)` says who is checked
+            Call(callee, args) if matches!(callee.kind, Type(_) | Ident(_) | Member(..)) => {
+                let args: Vec<String> = args.exprs().map(|arg| self.expr_to_string(arg)).collect();
+                format!("{}({})", self.expr_to_string(callee), args.join(", "))
             }
             Call(callee, _) => {
                 format!("{}(...)", self.expr_to_string(callee))
             }
-            Binary(left, op, right) => {
-                use ast::BinOpKind::*;
-                let op_str = match op.kind {
-                    Add => "+",
-                    Sub => "-",
-                    Mul => "*",
-                    Div => "/",
-                    Mod => "%",
-                    // Comparison and logical operators are handled by the labeler
-                    _ => {
-                        return format!(
-                            "{} {:?} {}",
-                            self.expr_to_string(left),
-                            op.kind,
-                            self.expr_to_string(right)
-                        )
-                    }
-                };
-                format!(
-                    "{} {} {}",
-                    self.expr_to_string(left),
-                    op_str,
-                    self.expr_to_string(right)
-                )
-            }
+            // `type(uint256)`, as in `type(uint256).max`
+            TypeCall(ty) => format!("type({})", self.type_to_string(ty)),
+            Type(ty) => self.type_to_string(ty),
+            Binary(left, op, right) => format!(
+                "{} {} {}",
+                self.expr_to_string(left),
+                binary_op_symbol(op.kind),
+                self.expr_to_string(right)
+            ),
+            // Parentheses are kept since they may change precedence: `(a + b) * c`
+            Tuple(items) if items.len() == 1 => match items[0].as_deref() {
+                Some(inner) => format!("({})", self.expr_to_string(inner)),
+                None => "expr".to_string(),
+            },
             _ => "expr".to_string(),
         }
     }
@@ -1036,32 +2350,38 @@ impl<'a> SolarParser<'a> {
     fn classify_condition(
         &self,
         condition: &ConditionExpr,
-        state_vars: &[String],
-        params: &[String],
+        walk: &Walk<'_, '_>,
     ) -> ConditionContext {
         match condition {
+            // The payment sent with the call is external context, whatever it's compared to
+            ConditionExpr::Binary { left, right, .. }
+                if left == "msg.value" || right == "msg.value" =>
+            {
+                ConditionContext::External
+            }
             ConditionExpr::Binary { left, right, .. } => {
-                if self.is_storage_ref(left, state_vars, params)
-                    || self.is_storage_ref(right, state_vars, params)
-                {
+                if self.is_storage_ref(left, walk) || self.is_storage_ref(right, walk) {
                     ConditionContext::Storage
                 } else {
                     ConditionContext::External
                 }
             }
-            ConditionExpr::Not(inner) => self.classify_condition(inner, state_vars, params),
+            ConditionExpr::Not(inner) => self.classify_condition(inner, walk),
             ConditionExpr::And(left, right) | ConditionExpr::Or(left, right) => {
-                if self.classify_condition(left, state_vars, params) == ConditionContext::Storage
-                    || self.classify_condition(right, state_vars, params)
-                        == ConditionContext::Storage
-                {
-                    ConditionContext::Storage
-                } else {
-                    ConditionContext::External
+                let left = self.classify_condition(left, walk);
+                let right = self.classify_condition(right, walk);
+                match (left, right) {
+                    (ConditionContext::Storage, ConditionContext::Storage) => {
+                        ConditionContext::Storage
+                    }
+                    (ConditionContext::External, ConditionContext::External) => {
+                        ConditionContext::External
+                    }
+                    (left, right) => ConditionContext::Mixed(Box::new(left), Box::new(right)),
                 }
             }
             ConditionExpr::Ident(name) => {
-                if self.is_storage_ref(name, state_vars, params) {
+                if self.is_storage_ref(name, walk) {
                     ConditionContext::Storage
                 } else {
                     ConditionContext::External
@@ -1071,29 +2391,61 @@ impl<'a> SolarParser<'a> {
         }
     }
 
-    fn is_storage_ref(&self, s: &str, state_vars: &[String], params: &[String]) -> bool {
-        // Check if it's a direct state variable reference
-        if state_vars.iter().any(|v| {
-            s == v || s.starts_with(&format!("{}.", v)) || s.starts_with(&format!("{}[", v))
-        }) {
-            return true;
+    /// Whether an expression's value comes from contract storage: a state variable (or
+    /// storage-derived local), a member or element of one, or arithmetic on them.
+    /// Function call results don't count, apart from type conversions
+    fn reads_storage(&self, expr: &ast::Expr<'_>, walk: &Walk<'_, '_>) -> bool {
+        use ast::ExprKind::*;
+
+        match &expr.kind {
+            Ident(ident) => walk
+                .state_vars
+                .iter()
+                .chain(&walk.storage_locals)
+                .any(|v| v == ident.as_str()),
+            // Members of another contract live in its storage
+            Member(base, _) if matches!(&base.kind, Ident(ident) if walk.contract_vars.iter().any(|v| v == ident.as_str())) => {
+                false
+            }
+            Member(base, _) | Index(base, _) | Unary(_, base) => self.reads_storage(base, walk),
+            Binary(left, _, right) | Ternary(_, left, right) => {
+                self.reads_storage(left, walk) || self.reads_storage(right, walk)
+            }
+            Tuple(items) => items
+                .iter()
+                .flatten()
+                .any(|item| self.reads_storage(item, walk)),
+            Call(callee, args) if matches!(callee.kind, Type(_)) => {
+                args.exprs().any(|arg| self.reads_storage(arg, walk))
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether an operand reads this contract's storage, judged by the base identifier
+    /// of its member/index chain: `orders[id].filled` reads the `orders` mapping, while
+    /// `order.filled` on a memory struct parameter doesn't. `msg.`, `block.` and `tx.`
+    /// chains, parameters (which shadow state variables) and anything other than a
+    /// chain are external
+    fn is_storage_ref(&self, s: &str, walk: &Walk<'_, '_>) -> bool {
+        // `this.paused()` reads this contract's own `paused` through its getter
+        if let Some(member) = s.strip_prefix("this.") {
+            return self.is_storage_ref(member.strip_suffix("()").unwrap_or(member), walk);
         }
 
-        // External context prefixes
-        let external_prefixes = ["msg.", "block.", "tx."];
-        if external_prefixes.iter().any(|p| s.starts_with(p)) {
+        let Some((base, rest)) = chain_base(s) else {
             return false;
-        }
+        };
+        let named = |names: &[String]| names.iter().any(|name| name == base);
 
-        // Check if it's a parameter
-        if params
-            .iter()
-            .any(|p| s == p || s.starts_with(&format!("{}.", p)))
-        {
+        // `vault.paused()` reads the other contract's storage, even if this contract
+        // has a `paused` of its own
+        if named(walk.params) || (rest.starts_with('.') && named(&walk.contract_vars)) {
             return false;
         }
 
-        false
+        // A state variable, or a local copied from one
+        named(walk.state_vars) || named(&walk.storage_locals)
     }
 
     #[allow(dead_code)]
@@ -1101,3 +2453,330 @@ impl<'a> SolarParser<'a> {
         self.project
     }
 }
+
+/// Split a member/index chain such as `orders[id].filled` into its base identifier and
+/// the rest (`orders`, `[id].filled`), or `None` for other text like `a + b` or `f()`
+fn chain_base(s: &str) -> Option<(&str, &str)> {
+    let end = s
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '$'))
+        .unwrap_or(s.len());
+    let (base, rest) = s.split_at(end);
+    (!base.is_empty() && (rest.is_empty() || rest.starts_with(['.', '[']))).then_some((base, rest))
+}
+
+/// Turn the guard clauses of an inlined helper into forks: its `return` only leaves the
+/// helper, so the caller carries on either way, and the helper's later checks only run
+/// when the guard doesn't return
+fn fork_helper_returns(mut checks: Vec<BranchPoint>) -> Vec<BranchPoint> {
+    let Some(at) = checks
+        .iter()
+        .position(|bp| bp.kind == CheckKind::EarlyReturn)
+    else {
+        return checks;
+    };
+    let else_checks = fork_helper_returns(checks.split_off(at + 1));
+    let Some(mut guard) = checks.pop() else {
+        return checks;
+    };
+
+    let then_checks = guard
+        .arms
+        .take()
+        .map(|arms| arms.then_checks)
+        .unwrap_or_default();
+    if !then_checks.is_empty() || !else_checks.is_empty() {
+        checks.push(BranchPoint {
+            kind: CheckKind::Require,
+            is_if_revert: false,
+            arms: Some(Arms {
+                then_checks,
+                else_checks,
+            }),
+            ..guard
+        });
+    }
+    checks
+}
+
+/// Normalize every condition and strip enum types from it, including the checks
+/// inside `if`/`else` arms
+fn tidy_conditions(branch_points: &mut [BranchPoint], enums: &[String]) {
+    for bp in branch_points {
+        let condition = std::mem::replace(&mut bp.condition, ConditionExpr::Ident(String::new()));
+        bp.condition = condition.normalize();
+        strip_enum_types(&mut bp.condition, enums);
+        if let Some(arms) = &mut bp.arms {
+            tidy_conditions(&mut arms.then_checks, enums);
+            tidy_conditions(&mut arms.else_checks, enums);
+        }
+    }
+}
+
+/// Drop the type from enum members compared in a condition (`State.Active` -> `Active`)
+fn strip_enum_types(condition: &mut ConditionExpr, enums: &[String]) {
+    let strip = |value: &mut String| {
+        let member = value
+            .split_once('.')
+            .filter(|(ty, member)| enums.iter().any(|e| e == ty) && !member.contains('.'))
+            .map(|(_, member)| member.to_string());
+        if let Some(member) = member {
+            *value = member;
+        }
+    };
+
+    match condition {
+        ConditionExpr::Binary { left, right, .. } => {
+            strip(left);
+            strip(right);
+        }
+        ConditionExpr::Not(inner) => strip_enum_types(inner, enums),
+        ConditionExpr::And(left, right) | ConditionExpr::Or(left, right) => {
+            strip_enum_types(left, enums);
+            strip_enum_types(right, enums);
+        }
+        ConditionExpr::Ident(_) | ConditionExpr::ExternalCall(_) => {}
+    }
+}
+
+/// Replace the sides of comparisons that name a constant with its value. Returns whether
+/// anything was replaced
+fn substitute_constants(condition: &mut ConditionExpr, constants: &[(String, String)]) -> bool {
+    let substitute = |value: &mut String| match constants.iter().find(|(name, _)| name == value) {
+        Some((_, constant)) => {
+            *value = constant.clone();
+            true
+        }
+        None => false,
+    };
+
+    match condition {
+        ConditionExpr::Binary { left, right, .. } => substitute(left) | substitute(right),
+        ConditionExpr::Not(inner) => substitute_constants(inner, constants),
+        ConditionExpr::And(left, right) | ConditionExpr::Or(left, right) => {
+            substitute_constants(left, constants) | substitute_constants(right, constants)
+        }
+        ConditionExpr::Ident(_) | ConditionExpr::ExternalCall(_) => false,
+    }
+}
+
+/// Text of the `@custom:btt` tags in a doc comment, one per tag
+fn btt_hints(docs: &ast::DocComments<'_>) -> Vec<String> {
+    docs.iter()
+        .flat_map(|doc| {
+            doc.symbol
+                .as_str()
+                .lines()
+                .map(|line| line.trim().trim_start_matches('*').trim())
+                .filter_map(|line| line.strip_prefix("@custom:btt"))
+                .map(|hint| hint.trim().to_string())
+                .filter(|hint| !hint.is_empty())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// OpenZeppelin `SafeCast` conversions: `toUint8` ... `toUint256` and `toInt8` ... `toInt256`
+fn safe_cast_methods() -> Vec<String> {
+    (8..=256)
+        .step_by(8)
+        .flat_map(|bits| [format!("toUint{}", bits), format!("toInt{}", bits)])
+        .collect()
+}
+
+/// Write modifier arguments in place of the parameters they're bound to
+fn substitute_params(condition: &mut ConditionExpr, bindings: &[(String, String)]) {
+    match condition {
+        ConditionExpr::Binary { left, right, .. } => {
+            *left = substitute_identifiers(left, bindings);
+            *right = substitute_identifiers(right, bindings);
+        }
+        ConditionExpr::Not(inner) => substitute_params(inner, bindings),
+        ConditionExpr::And(left, right) | ConditionExpr::Or(left, right) => {
+            substitute_params(left, bindings);
+            substitute_params(right, bindings);
+        }
+        ConditionExpr::Ident(name) | ConditionExpr::ExternalCall(name) => {
+            *name = substitute_identifiers(name, bindings);
+        }
+    }
+}
+
+/// Replace whole identifiers in an expression string, leaving member names (`x.role`),
+/// numbers and string literals alone
+fn substitute_identifiers(text: &str, bindings: &[(String, String)]) -> String {
+    let bytes = text.as_bytes();
+    let is_ident = |c: u8| c.is_ascii_alphanumeric() || c == b'_' || c == b'$';
+    let mut result = String::with_capacity(text.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let start = i;
+        let c = bytes[i];
+
+        if c == b'"' || c == b'\'' {
+            i += 1;
+            while i < bytes.len() && bytes[i] != c {
+                i += 1;
+            }
+            i = (i + 1).min(bytes.len());
+            result.push_str(&text[start..i]);
+        } else if is_ident(c) {
+            while i < bytes.len() && is_ident(bytes[i]) {
+                i += 1;
+            }
+            let word = &text[start..i];
+            let member = text[..start].ends_with('.');
+            match bindings.iter().find(|(param, _)| param == word) {
+                Some((_, arg)) if !member && !c.is_ascii_digit() => result.push_str(arg),
+                _ => result.push_str(word),
+            }
+        } else {
+            i += text[start..].chars().next().map_or(1, char::len_utf8);
+            result.push_str(&text[start..i]);
+        }
+    }
+
+    result
+}
+
+/// Whether a Yul block calls `revert`, directly or in a nested block
+fn yul_block_reverts(block: &ast::yul::Block<'_>) -> bool {
+    use ast::yul::{ExprKind, StmtKind};
+
+    block.stmts.iter().any(|stmt| match &stmt.kind {
+        StmtKind::Expr(expr) => {
+            matches!(&expr.kind, ExprKind::Call(call) if call.name.as_str() == "revert")
+        }
+        StmtKind::Block(block) => yul_block_reverts(block),
+        _ => false,
+    })
+}
+
+/// Best-effort condition for a Yul expression used as a boolean: comparisons map to
+/// their Solidity operators, `iszero` negates, and any other value is compared to zero
+fn yul_to_condition(expr: &ast::yul::Expr<'_>) -> ConditionExpr {
+    if let ast::yul::ExprKind::Call(call) = &expr.kind {
+        let binary =
+            |op, left: &ast::yul::Expr<'_>, right: &ast::yul::Expr<'_>| ConditionExpr::Binary {
+                left: yul_to_string(left),
+                op,
+                right: yul_to_string(right),
+            };
+
+        match (call.name.as_str(), &call.arguments[..]) {
+            ("iszero", [inner]) => return ConditionExpr::Not(Box::new(yul_to_condition(inner))),
+            ("eq", [left, right]) => return binary(BinaryOp::Eq, left, right),
+            ("lt" | "slt", [left, right]) => return binary(BinaryOp::Lt, left, right),
+            ("gt" | "sgt", [left, right]) => return binary(BinaryOp::Gt, left, right),
+            ("and", [left, right]) => {
+                return ConditionExpr::And(
+                    Box::new(yul_to_condition(left)),
+                    Box::new(yul_to_condition(right)),
+                )
+            }
+            ("or", [left, right]) => {
+                return ConditionExpr::Or(
+                    Box::new(yul_to_condition(left)),
+                    Box::new(yul_to_condition(right)),
+                )
+            }
+            _ => {}
+        }
+    }
+
+    ConditionExpr::Binary {
+        left: yul_to_string(expr),
+        op: BinaryOp::NotEq,
+        right: "0".to_string(),
+    }
+}
+
+/// A Yul expression as written in the source (`sload(slot)`, `caller()`, `0x20`)
+fn yul_to_string(expr: &ast::yul::Expr<'_>) -> String {
+    match &expr.kind {
+        ast::yul::ExprKind::Path(path) => path.to_string(),
+        ast::yul::ExprKind::Lit(lit) => literal_to_string(lit, None),
+        ast::yul::ExprKind::Call(call) => {
+            let args: Vec<String> = call.arguments.iter().map(yul_to_string).collect();
+            format!("{}({})", call.name, args.join(", "))
+        }
+    }
+}
+
+/// A literal as written in the source (`100`, `1e18`, `0xff`, `true`), with strings
+/// quoted and any unit kept (`1 ether`)
+fn literal_to_string(lit: &ast::Lit, sub_denomination: Option<&ast::SubDenomination>) -> String {
+    let value = match lit.kind {
+        ast::LitKind::Str(..) => format!("\"{}\"", lit.symbol),
+        _ => lit.symbol.to_string(),
+    };
+
+    match sub_denomination {
+        Some(unit) => format!("{} {}", value, unit),
+        None => value,
+    }
+}
+
+/// Solidity spelling of a binary operator
+fn binary_op_symbol(op: ast::BinOpKind) -> &'static str {
+    use ast::BinOpKind::*;
+
+    match op {
+        Lt => "<",
+        Le => "<=",
+        Gt => ">",
+        Ge => ">=",
+        Eq => "==",
+        Ne => "!=",
+        Or => "||",
+        And => "&&",
+        Shr => ">>",
+        Shl => "<<",
+        Sar => ">>>",
+        BitAnd => "&",
+        BitOr => "|",
+        BitXor => "^",
+        Add => "+",
+        Sub => "-",
+        Pow => "**",
+        Mul => "*",
+        Div => "/",
+        Mod => "%",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn testdata_project() -> FoundryProject {
+        let testdata = Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata");
+        FoundryProject {
+            root: testdata.clone(),
+            src_dir: testdata,
+            lib_dirs: vec![],
+            remappings: vec![],
+        }
+    }
+
+    #[test]
+    fn test_ancestors_are_parsed_once_per_contract() {
+        let project = testdata_project();
+        let parser = SolarParser::new(&project);
+        let child = project.root.join("inheritance/Child.sol");
+
+        // Both `setFee` overloads apply `onlyOwner` from Base.sol
+        let functions = parser.parse_all_public_functions(&child, "Child").unwrap();
+        assert_eq!(functions.len(), 2);
+        assert!(functions.iter().all(|f| !f.branch_points.is_empty()));
+
+        // Child.sol and Base.sol, once each for the analysis and for the resolver
+        assert_eq!(parser.parse_count.get(), 2);
+        assert_eq!(parser.resolver.borrow().parse_count(), 2);
+
+        // Later lookups come from the caches
+        parser.parse_function(&child, "Child", "setFee").unwrap();
+        assert_eq!(parser.parse_count.get(), 2);
+        assert_eq!(parser.resolver.borrow().parse_count(), 2);
+    }
+}
@@ -0,0 +1,145 @@
+//! End-of-run summary of how many branch points the generated functions have
+
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt;
+
+/// Number of functions listed under "most branches"
+const MOST_BRANCHES: usize = 5;
+
+/// Branch points found in one function
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FunctionStats {
+    pub contract: String,
+    /// Function signature, e.g. `withdraw(uint256)`
+    pub function: String,
+    pub branch_points: usize,
+    /// The function's tree is a lone leaf, without a single branch
+    #[serde(skip)]
+    pub unguarded: bool,
+}
+
+/// Totals over every function of a run, written as JSON by `--json-summary`
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct Summary {
+    pub contracts: usize,
+    pub functions: usize,
+    pub branch_points: usize,
+    /// Functions with the most branch points, most first
+    pub most_branches: Vec<FunctionStats>,
+    /// Functions whose tree has no branches (`Contract::function(args)`), which may be
+    /// guards acacia doesn't recognize
+    pub unguarded: Vec<String>,
+}
+
+impl Summary {
+    pub fn new(functions: &[FunctionStats]) -> Self {
+        let contracts: HashSet<&str> = functions.iter().map(|f| f.contract.as_str()).collect();
+
+        let mut most_branches: Vec<FunctionStats> = functions
+            .iter()
+            .filter(|f| f.branch_points > 0)
+            .cloned()
+            .collect();
+        most_branches.sort_by(|a, b| {
+            b.branch_points
+                .cmp(&a.branch_points)
+                .then_with(|| (&a.contract, &a.function).cmp(&(&b.contract, &b.function)))
+        });
+        most_branches.truncate(MOST_BRANCHES);
+
+        let mut unguarded: Vec<String> = functions
+            .iter()
+            .filter(|f| f.unguarded)
+            .map(|f| format!("{}::{}", f.contract, f.function))
+            .collect();
+        unguarded.sort();
+
+        Self {
+            contracts: contracts.len(),
+            functions: functions.len(),
+            branch_points: functions.iter().map(|f| f.branch_points).sum(),
+            most_branches,
+            unguarded,
+        }
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Summary: {} contracts, {} functions, {} branch points",
+            self.contracts, self.functions, self.branch_points
+        )?;
+
+        if !self.most_branches.is_empty() {
+            writeln!(f, "Most branches:")?;
+            for stats in &self.most_branches {
+                writeln!(
+                    f,
+                    "  {:>3}  {}::{}",
+                    stats.branch_points, stats.contract, stats.function
+                )?;
+            }
+        }
+
+        if !self.unguarded.is_empty() {
+            writeln!(f, "Without branches:")?;
+            for function in &self.unguarded {
+                writeln!(f, "  {}", function)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(contract: &str, function: &str, branch_points: usize) -> FunctionStats {
+        FunctionStats {
+            contract: contract.to_string(),
+            function: function.to_string(),
+            branch_points,
+            unguarded: branch_points == 0,
+        }
+    }
+
+    #[test]
+    fn test_summary_totals_and_ranking() {
+        let summary = Summary::new(&[
+            stats("Vault", "deposit(uint256)", 2),
+            stats("Vault", "withdraw(uint256)", 4),
+            stats("Vault", "owner()", 0),
+            stats("Token", "mint(address,uint256)", 2),
+        ]);
+
+        assert_eq!(summary.contracts, 2);
+        assert_eq!(summary.functions, 4);
+        assert_eq!(summary.branch_points, 8);
+        // Ties are listed by name, and functions without branches are left out
+        assert_eq!(
+            summary.most_branches,
+            vec![
+                stats("Vault", "withdraw(uint256)", 4),
+                stats("Token", "mint(address,uint256)", 2),
+                stats("Vault", "deposit(uint256)", 2),
+            ]
+        );
+
+        assert_eq!(
+            summary.to_string(),
+            "\
+Summary: 2 contracts, 4 functions, 8 branch points
+Most branches:
+    4  Vault::withdraw(uint256)
+    2  Token::mint(address,uint256)
+    2  Vault::deposit(uint256)
+Without branches:
+  Vault::owner()
+"
+        );
+    }
+}
@@ -3,9 +3,16 @@
 #![allow(dead_code)]
 
 use super::labeler::ConditionLabeler;
-use crate::analysis::BranchPoint;
+use crate::analysis::{Arms, BranchPoint, CheckKind, ConditionExpr};
+use std::collections::HashSet;
 use thiserror::Error;
 
+/// Leaf for code behind an `assert(false)`
+const NEVER_REACHED: &str = "it should never reach here";
+
+/// Leaf for a failed `assert`
+const PANIC: &str = "it should panic";
+
 #[derive(Error, Debug)]
 pub enum TreeError {
     #[error("Failed to build tree: {0}")]
@@ -13,7 +20,7 @@ pub enum TreeError {
 }
 
 /// A node in the BTT tree
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum TreeNode {
     /// Root node with function name
     Root {
@@ -23,12 +30,165 @@ pub enum TreeNode {
     /// Branch node with condition
     Branch {
         label: String,
+        /// Solidity source of the condition, for annotated output
+        source: Option<String>,
+        /// Line the condition is checked on, for `--with-lines`
+        line: Option<usize>,
         children: Vec<TreeNode>,
     },
     /// Leaf node with outcome
     Leaf { label: String },
 }
 
+impl TreeNode {
+    /// Whether any branch hangs below the root. A tree that is only `it should succeed`
+    /// means the function has no guards, or has guards that weren't recognized
+    pub fn has_branches(&self) -> bool {
+        match self {
+            TreeNode::Root { children, .. } | TreeNode::Branch { children, .. } => children
+                .iter()
+                .any(|child| matches!(child, TreeNode::Branch { .. })),
+            TreeNode::Leaf { .. } => false,
+        }
+    }
+
+    /// Labels along every path from the root to a leaf, excluding the root name
+    pub fn leaf_paths(&self) -> Vec<Vec<&str>> {
+        match self {
+            TreeNode::Root { children, .. } => children.iter().flat_map(Self::leaf_paths).collect(),
+            TreeNode::Branch {
+                label, children, ..
+            } => children
+                .iter()
+                .flat_map(Self::leaf_paths)
+                .map(|mut path| {
+                    path.insert(0, label.as_str());
+                    path
+                })
+                .collect(),
+            TreeNode::Leaf { label } => vec![vec![label.as_str()]],
+        }
+    }
+
+    /// Carry over hand-edited outcomes from a previous version of this tree. Wherever
+    /// both trees reach the same branch label path and end in leaves there, the
+    /// previous leaves are kept, so edits like "it should succeed and emit Transfer"
+    /// survive while new or changed branches come from `self`
+    pub fn keep_leaves_from(&mut self, previous: &TreeNode) {
+        let (children, previous_children) = match (self, previous) {
+            (
+                TreeNode::Root { children, .. },
+                TreeNode::Root {
+                    children: previous_children,
+                    ..
+                },
+            )
+            | (
+                TreeNode::Branch { children, .. },
+                TreeNode::Branch {
+                    children: previous_children,
+                    ..
+                },
+            ) => (children, previous_children),
+            _ => return,
+        };
+
+        let is_leaf = |node: &TreeNode| matches!(node, TreeNode::Leaf { .. });
+        if !children.is_empty()
+            && children.iter().all(is_leaf)
+            && !previous_children.is_empty()
+            && previous_children.iter().all(is_leaf)
+        {
+            *children = previous_children.clone();
+            return;
+        }
+
+        for child in children.iter_mut() {
+            let TreeNode::Branch { label, .. } = child else {
+                continue;
+            };
+            let previous_child = previous_children.iter().find(|previous| {
+                matches!(previous, TreeNode::Branch { label: previous_label, .. } if previous_label == label)
+            });
+            if let Some(previous_child) = previous_child {
+                child.keep_leaves_from(previous_child);
+            }
+        }
+    }
+}
+
+/// Names a condition reads, with member paths kept whole (`msg.sender`, `balances`)
+fn condition_identifiers(condition: &ConditionExpr) -> HashSet<String> {
+    condition
+        .to_string()
+        .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$' || c == '.'))
+        .filter(|word| word.starts_with(|c: char| c.is_alphabetic() || c == '_' || c == '$'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Whether a condition does arithmetic that wraps silently inside an `unchecked` block
+fn wraps_arithmetic(condition: &ConditionExpr) -> bool {
+    let text = condition.to_string();
+    [" + ", " - ", " * "].iter().any(|op| text.contains(op))
+}
+
+/// Options controlling how branch points are turned into a tree
+#[derive(Debug, Clone, Default)]
+pub struct TreeOptions {
+    /// Leading identifier prefixes removed from labels (e.g. `s_`, `i_`, `_`)
+    pub strip_prefixes: Vec<String>,
+    /// Render negated `&&`/`||` literally as `not (...)` instead of applying De Morgan
+    pub no_demorgan: bool,
+    /// Split `require(a && b)` into one branch level per conjunct (and `if (a || b) revert`
+    /// into one per disjunct). `require(a || b)` stays combined since neither side
+    /// reverts on its own
+    pub split_logical: bool,
+    /// Sort independent checks by condition instead of keeping source order, so
+    /// swapping two unrelated `require`s doesn't reshuffle the tree
+    pub stable_order: bool,
+    /// Phrase `block.timestamp <= deadline` as "the deadline has not passed"
+    pub time_labels: bool,
+    /// Flags read as "the contract is already initialized", instead of `initialized`
+    pub initialized_flags: Option<Vec<String>>,
+    /// Levels of branches to render before the remaining checks are summarized in one leaf
+    pub max_depth: Option<usize>,
+    /// Put the reverting branch of each check after the branch that continues, instead of
+    /// before it
+    pub revert_last: bool,
+    /// Wording of the leaves
+    pub labels: LabelConfig,
+}
+
+/// Leaf text for each way a path through the function can end
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelConfig {
+    /// Reverting path, followed by the error or message when known
+    pub revert: String,
+    /// Happy path
+    pub success: String,
+    /// Guard clause that returns without reverting
+    pub early_return: String,
+}
+
+impl LabelConfig {
+    /// Whether a leaf describes a revert, or the panic of a failed `assert`, rather
+    /// than a path that gets through
+    pub fn is_revert(&self, leaf: &str) -> bool {
+        leaf.contains(self.revert.as_str()) || leaf == PANIC
+    }
+}
+
+impl Default for LabelConfig {
+    fn default() -> Self {
+        Self {
+            revert: "it should revert".to_string(),
+            success: "it should succeed".to_string(),
+            early_return: "it should return early".to_string(),
+        }
+    }
+}
+
 /// Builds a BTT tree from branch points
 pub struct TreeBuilder;
 
@@ -38,10 +198,27 @@ impl TreeBuilder {
         function_name: &str,
         branch_points: Vec<BranchPoint>,
     ) -> Result<TreeNode, TreeError> {
-        let labeler = ConditionLabeler::new();
+        Self::build_with_options(function_name, branch_points, &TreeOptions::default())
+    }
+
+    /// Build a tree using custom labeling options
+    pub fn build_with_options(
+        function_name: &str,
+        branch_points: Vec<BranchPoint>,
+        options: &TreeOptions,
+    ) -> Result<TreeNode, TreeError> {
+        let labeler = ConditionLabeler::new()
+            .with_strip_prefixes(options.strip_prefixes.clone())
+            .with_demorgan(!options.no_demorgan)
+            .with_time_labels(options.time_labels);
+        let labeler = match &options.initialized_flags {
+            Some(flags) => labeler.with_initialized_flags(flags.clone()),
+            None => labeler,
+        };
 
         // Build tree recursively from branch points
-        let children = Self::build_branches(&branch_points, 0, &labeler);
+        let branch_points = Self::prepare(branch_points, options);
+        let children = Self::build_branches(&branch_points, 0, 0, &labeler, options);
 
         Ok(TreeNode::Root {
             name: function_name.to_string(),
@@ -49,23 +226,194 @@ impl TreeBuilder {
         })
     }
 
+    /// Split and dedup the checks, including the ones inside `if`/`else` arms
+    fn prepare(branch_points: Vec<BranchPoint>, options: &TreeOptions) -> Vec<BranchPoint> {
+        let branch_points = if options.split_logical {
+            Self::split_logical(branch_points)
+        } else {
+            branch_points
+        };
+        let branch_points = if options.stable_order {
+            Self::stable_order(branch_points)
+        } else {
+            branch_points
+        };
+
+        Self::dedup_consecutive(branch_points)
+            .into_iter()
+            .map(|mut bp| {
+                if let Some(arms) = bp.arms.take() {
+                    bp.arms = Some(Arms {
+                        then_checks: Self::prepare(arms.then_checks, options),
+                        else_checks: Self::prepare(arms.else_checks, options),
+                    });
+                }
+                bp
+            })
+            .collect()
+    }
+
+    /// Order runs of checks by their condition. If/else forks, external calls, early
+    /// returns, loop `continue`/`break` guards and unreachable asserts stay in place and
+    /// split the runs, and a check never moves ahead of an earlier one that shares an
+    /// identifier with it, since it may depend on it
+    fn stable_order(branch_points: Vec<BranchPoint>) -> Vec<BranchPoint> {
+        let mut ordered = Vec::with_capacity(branch_points.len());
+        let mut run = Vec::new();
+
+        for bp in branch_points {
+            let stops = matches!(
+                bp.kind,
+                CheckKind::EarlyReturn
+                    | CheckKind::Continue
+                    | CheckKind::Break
+                    | CheckKind::Unreachable
+            );
+            if bp.arms.is_some() || bp.is_external_call || stops {
+                ordered.extend(Self::sort_run(std::mem::take(&mut run)));
+                ordered.push(bp);
+            } else {
+                run.push(bp);
+            }
+        }
+        ordered.extend(Self::sort_run(run));
+
+        ordered
+    }
+
+    /// Repeatedly place the smallest condition among the checks whose earlier
+    /// dependencies are already placed
+    fn sort_run(run: Vec<BranchPoint>) -> Vec<BranchPoint> {
+        let identifiers: Vec<HashSet<String>> = run
+            .iter()
+            .map(|bp| condition_identifiers(&bp.condition))
+            .collect();
+        let keys: Vec<String> = run.iter().map(|bp| bp.condition.to_string()).collect();
+
+        let mut placed = vec![false; run.len()];
+        let mut order = Vec::with_capacity(run.len());
+        while order.len() < run.len() {
+            // The earliest unplaced check is always ready, so there is a candidate
+            let Some(next) = (0..run.len())
+                .filter(|&i| !placed[i])
+                .filter(|&i| {
+                    (0..i).all(|j| placed[j] || identifiers[i].is_disjoint(&identifiers[j]))
+                })
+                .min_by_key(|&i| &keys[i])
+            else {
+                break;
+            };
+            placed[next] = true;
+            order.push(next);
+        }
+
+        let mut slots: Vec<Option<BranchPoint>> = run.into_iter().map(Some).collect();
+        order.into_iter().filter_map(|i| slots[i].take()).collect()
+    }
+
+    /// Collapse adjacent checks of the same condition (e.g. a modifier and the function
+    /// body both requiring `msg.sender == owner`) into one branch level. Loop and
+    /// non-loop checks are kept apart since only one of them reads "any", and checks
+    /// that fail differently since their leaves differ.
+    fn dedup_consecutive(mut branch_points: Vec<BranchPoint>) -> Vec<BranchPoint> {
+        branch_points.dedup_by(|next, prev| {
+            next.condition.to_string() == prev.condition.to_string()
+                && next.is_if_revert == prev.is_if_revert
+                && next.context == prev.context
+                && next.is_loop == prev.is_loop
+                && next.is_external_call == prev.is_external_call
+                && next.kind == prev.kind
+                && next.revert_reason == prev.revert_reason
+                && next.revert_message == prev.revert_message
+                && next.arms.is_none()
+                && prev.arms.is_none()
+        });
+        branch_points
+    }
+
+    /// Expand each check on a compound condition into sequential checks on its parts
+    /// where that's equivalent: a guard failing when `a && b` is false fails exactly
+    /// when `a` or `b` is, and one triggered by `a || b` triggers when either does.
+    /// The parts keep the whole condition's overall given/when context.
+    fn split_logical(branch_points: Vec<BranchPoint>) -> Vec<BranchPoint> {
+        let mut split = Vec::new();
+        for bp in branch_points {
+            Self::split_into(bp, &mut split);
+        }
+        split
+    }
+
+    fn split_into(bp: BranchPoint, out: &mut Vec<BranchPoint>) {
+        let parts = match (&bp.condition, bp.is_if_revert) {
+            (ConditionExpr::And(left, right), false) | (ConditionExpr::Or(left, right), true)
+                if !bp.is_external_call && bp.arms.is_none() =>
+            {
+                Some(((**left).clone(), (**right).clone()))
+            }
+            _ => None,
+        };
+
+        match parts {
+            Some((left, right)) => {
+                for condition in [left, right] {
+                    Self::split_into(
+                        BranchPoint {
+                            condition,
+                            context: bp.context.overall(),
+                            ..bp.clone()
+                        },
+                        out,
+                    );
+                }
+            }
+            None => out.push(bp),
+        }
+    }
+
+    /// Branches for the checks from `index` on, `depth` levels below the root
     fn build_branches(
         branch_points: &[BranchPoint],
         index: usize,
+        depth: usize,
         labeler: &ConditionLabeler,
+        options: &TreeOptions,
     ) -> Vec<TreeNode> {
         if index >= branch_points.len() {
             // No more branch points - this is the success path
             return vec![TreeNode::Leaf {
-                label: "it should succeed".to_string(),
+                label: options.labels.success.clone(),
+            }];
+        }
+
+        // Past `--max-depth`, a single leaf stands in for the rest of the checks
+        if options.max_depth.is_some_and(|max| depth >= max) {
+            let remaining = branch_points.len() - index;
+            let noun = if remaining == 1 {
+                "condition"
+            } else {
+                "conditions"
+            };
+            return vec![TreeNode::Leaf {
+                label: format!("it should handle remaining {} {}", remaining, noun),
             }];
         }
 
         let bp = &branch_points[index];
 
+        // Nothing after a check that always fails runs, and there's no condition to split on
+        if bp.kind == CheckKind::Unreachable {
+            return vec![TreeNode::Leaf {
+                label: NEVER_REACHED.to_string(),
+            }];
+        }
+
         // Generate labels for both paths
-        let (fail_label, pass_label) =
+        let (mut fail_label, mut pass_label) =
             labeler.generate_labels(&bp.condition, bp.context.clone(), bp.is_loop);
+        if bp.is_unchecked && wraps_arithmetic(&bp.condition) {
+            fail_label.push_str(" (unchecked)");
+            pass_label.push_str(" (unchecked)");
+        }
 
         // Handle external calls specially
         if bp.is_external_call {
@@ -73,17 +421,80 @@ impl TreeBuilder {
                 crate::analysis::ConditionExpr::ExternalCall(name) => name.clone(),
                 _ => "external call".to_string(),
             };
+            // A checked conversion fails by overflowing
+            let (fails, succeeds) = match bp.kind {
+                CheckKind::Overflow => ("overflows", "does not overflow"),
+                _ => ("fails", "succeeds"),
+            };
+
+            // A `try` whose `catch` handles the failure continues on both sides
+            if let Some(arms) = &bp.arms {
+                let rest = &branch_points[index + 1..];
+                let side = |checks: &[BranchPoint]| {
+                    let path: Vec<BranchPoint> = checks.iter().chain(rest).cloned().collect();
+                    Self::build_branches(&path, 0, depth + 1, labeler, options)
+                };
+
+                return Self::order_siblings(
+                    TreeNode::Branch {
+                        label: format!("when {} {}", call_name, fails),
+                        source: bp.source.clone(),
+                        line: bp.span.map(|(line, _)| line),
+                        children: side(&arms.else_checks),
+                    },
+                    TreeNode::Branch {
+                        label: format!("when {} {}", call_name, succeeds),
+                        source: bp.source.clone(),
+                        line: bp.span.map(|(line, _)| line),
+                        children: side(&arms.then_checks),
+                    },
+                    options,
+                );
+            }
+
+            return Self::order_siblings(
+                TreeNode::Branch {
+                    label: format!("when {} {}", call_name, fails),
+                    source: bp.source.clone(),
+                    line: bp.span.map(|(line, _)| line),
+                    children: vec![Self::revert_leaf(bp, options)],
+                },
+                TreeNode::Branch {
+                    label: format!("when {} {}", call_name, succeeds),
+                    source: bp.source.clone(),
+                    line: bp.span.map(|(line, _)| line),
+                    children: Self::build_branches(
+                        branch_points,
+                        index + 1,
+                        depth + 1,
+                        labeler,
+                        options,
+                    ),
+                },
+                options,
+            );
+        }
+
+        // An if/else fork: each side runs its own arm's checks, then the rest
+        if let Some(arms) = bp.arms.as_ref().filter(|_| bp.kind == CheckKind::Require) {
+            let rest = &branch_points[index + 1..];
+            let side = |checks: &[BranchPoint]| {
+                let path: Vec<BranchPoint> = checks.iter().chain(rest).cloned().collect();
+                Self::build_branches(&path, 0, depth + 1, labeler, options)
+            };
 
             return vec![
                 TreeNode::Branch {
-                    label: format!("when {} fails", call_name),
-                    children: vec![TreeNode::Leaf {
-                        label: "it should revert".to_string(),
-                    }],
+                    label: pass_label,
+                    source: bp.source.clone(),
+                    line: bp.span.map(|(line, _)| line),
+                    children: side(&arms.then_checks),
                 },
                 TreeNode::Branch {
-                    label: format!("when {} succeeds", call_name),
-                    children: Self::build_branches(branch_points, index + 1, labeler),
+                    label: fail_label,
+                    source: bp.source.clone(),
+                    line: bp.span.map(|(line, _)| line),
+                    children: side(&arms.else_checks),
                 },
             ];
         }
@@ -96,18 +507,102 @@ impl TreeBuilder {
             (fail_label, pass_label) // FALSE → revert, TRUE → continue
         };
 
+        // A guard clause ends the function early, a loop guard skips the item or leaves
+        // the loop, and a failed assert panics, instead of reverting
+        let stop_leaf = match bp.kind {
+            CheckKind::EarlyReturn => TreeNode::Leaf {
+                label: options.labels.early_return.clone(),
+            },
+            CheckKind::Continue => TreeNode::Leaf {
+                label: "it should skip this item".to_string(),
+            },
+            CheckKind::Break => TreeNode::Leaf {
+                label: "it should stop early".to_string(),
+            },
+            CheckKind::Assert => TreeNode::Leaf {
+                label: PANIC.to_string(),
+            },
+            CheckKind::Unreachable => TreeNode::Leaf {
+                label: NEVER_REACHED.to_string(),
+            },
+            CheckKind::Require | CheckKind::Overflow => Self::revert_leaf(bp, options),
+        };
+
+        // Checks in a guard clause's body run before it stops
+        let stop_children = match &bp.arms {
+            Some(arms) => Self::ending_in(
+                Self::build_branches(&arms.then_checks, 0, depth + 1, labeler, options),
+                &stop_leaf,
+                options,
+            ),
+            None => vec![stop_leaf],
+        };
+
         // Normal branch point: create two paths
-        vec![
+        Self::order_siblings(
             TreeNode::Branch {
                 label: revert_label,
-                children: vec![TreeNode::Leaf {
-                    label: "it should revert".to_string(),
-                }],
+                source: bp.source.clone(),
+                line: bp.span.map(|(line, _)| line),
+                children: stop_children,
             },
             TreeNode::Branch {
                 label: continue_label,
-                children: Self::build_branches(branch_points, index + 1, labeler),
+                source: bp.source.clone(),
+                line: bp.span.map(|(line, _)| line),
+                children: Self::build_branches(
+                    branch_points,
+                    index + 1,
+                    depth + 1,
+                    labeler,
+                    options,
+                ),
             },
-        ]
+            options,
+        )
+    }
+
+    /// Branches of a guard clause's body, with the paths that get through it ending in
+    /// the guard's own leaf instead of succeeding
+    fn ending_in(nodes: Vec<TreeNode>, end: &TreeNode, options: &TreeOptions) -> Vec<TreeNode> {
+        nodes
+            .into_iter()
+            .map(|node| match node {
+                TreeNode::Leaf { label } if label == options.labels.success => end.clone(),
+                TreeNode::Branch {
+                    label,
+                    source,
+                    line,
+                    children,
+                } => TreeNode::Branch {
+                    label,
+                    source,
+                    line,
+                    children: Self::ending_in(children, end, options),
+                },
+                node => node,
+            })
+            .collect()
+    }
+
+    /// The two sides of a check, in the order `--revert-position` asks for
+    fn order_siblings(stop: TreeNode, rest: TreeNode, options: &TreeOptions) -> Vec<TreeNode> {
+        if options.revert_last {
+            vec![rest, stop]
+        } else {
+            vec![stop, rest]
+        }
+    }
+
+    /// Leaf for the failing side of a branch point, naming the custom error or
+    /// quoting the revert message when known
+    fn revert_leaf(bp: &BranchPoint, options: &TreeOptions) -> TreeNode {
+        let revert = &options.labels.revert;
+        let label = match (&bp.revert_reason, &bp.revert_message) {
+            (Some(reason), _) => format!("{} with {}", revert, reason),
+            (None, Some(message)) => format!("{} with \"{}\"", revert, message),
+            (None, None) => revert.to_string(),
+        };
+        TreeNode::Leaf { label }
     }
 }
@@ -1,13 +1,55 @@
 //! Condition to human-readable label conversion
 
+use std::collections::HashSet;
+
 use crate::analysis::{BinaryOp, ConditionContext, ConditionExpr};
 
 /// Converts condition expressions to human-readable labels
-pub struct ConditionLabeler;
+pub struct ConditionLabeler {
+    /// Leading prefixes stripped from identifiers (e.g. `s_`, `i_`, `_`)
+    strip_prefixes: Vec<String>,
+    /// Distribute negation over `and`/`or` (De Morgan) instead of rendering `not (...)`
+    demorgan: bool,
+    /// Read `block.timestamp <= deadline` as "the deadline has not passed"
+    time_labels: bool,
+    /// Flags set once a contract is initialized, read as "the contract is already initialized"
+    initialized_flags: Vec<String>,
+}
 
 impl ConditionLabeler {
     pub fn new() -> Self {
-        Self
+        Self {
+            strip_prefixes: Vec::new(),
+            demorgan: true,
+            time_labels: false,
+            initialized_flags: INITIALIZED_FLAGS.iter().map(|f| f.to_string()).collect(),
+        }
+    }
+
+    /// Boolean flags that record a one-shot initialization, instead of `initialized`
+    pub fn with_initialized_flags(mut self, flags: Vec<String>) -> Self {
+        self.initialized_flags = flags;
+        self
+    }
+
+    /// Label comparisons of `block.timestamp` or `block.number` against a deadline-like
+    /// name as whether the deadline has passed
+    pub fn with_time_labels(mut self, time_labels: bool) -> Self {
+        self.time_labels = time_labels;
+        self
+    }
+
+    /// Choose whether negated compound conditions are distributed with De Morgan's laws
+    /// (`a is zero or b is zero`) or rendered literally (`not (a is not zero and b is not zero)`)
+    pub fn with_demorgan(mut self, demorgan: bool) -> Self {
+        self.demorgan = demorgan;
+        self
+    }
+
+    /// Strip the given prefixes from identifiers when emitting labels
+    pub fn with_strip_prefixes(mut self, prefixes: Vec<String>) -> Self {
+        self.strip_prefixes = prefixes;
+        self
     }
 
     /// Generate both the "fail" and "pass" labels for a condition
@@ -21,27 +63,115 @@ impl ConditionLabeler {
         context: ConditionContext,
         is_loop: bool,
     ) -> (String, String) {
-        let prefix = match context {
-            ConditionContext::Storage => "given",
-            ConditionContext::External => "when",
-        };
-
         let loop_prefix = if is_loop { "any " } else { "" };
 
-        let (true_label, false_label) = self.expr_to_labels(condition);
+        let condition = self.strip_condition(condition);
+        let (true_label, false_label) = self.prefixed_labels(&condition, &context, loop_prefix);
 
         // For require(condition): if condition is false → revert
         // So fail_label is the negation (false case)
-        let fail_label = format!("{} {}{}", prefix, loop_prefix, false_label);
-        let pass_label = format!("{} {}{}", prefix, loop_prefix, true_label);
+        (false_label, true_label)
+    }
 
-        (fail_label, pass_label)
+    /// (true_case_label, false_case_label) starting with the context's "given"/"when".
+    /// The sides of an `and`/`or` mixing storage and external checks get their own
+    /// prefix: "given the contract is not paused and when amount is not zero"
+    fn prefixed_labels(
+        &self,
+        expr: &ConditionExpr,
+        context: &ConditionContext,
+        loop_prefix: &str,
+    ) -> (String, String) {
+        if let ConditionContext::Mixed(left_context, right_context) = context {
+            if let Some(labels) = self.mixed_labels(expr, left_context, right_context, loop_prefix)
+            {
+                return labels;
+            }
+        }
+
+        let prefix = match context.overall() {
+            ConditionContext::External => "when",
+            _ => "given",
+        };
+        let (true_label, false_label) = self.expr_to_labels(expr);
+        (
+            format!("{} {}{}", prefix, loop_prefix, true_label),
+            format!("{} {}{}", prefix, loop_prefix, false_label),
+        )
+    }
+
+    /// Labels of an `and`/`or` (or its negation) whose sides have different contexts.
+    /// `None` when the sides are read as one phrase instead: a range check such as
+    /// "amount is within (0, max]", or a literal `not (...)` without De Morgan
+    fn mixed_labels(
+        &self,
+        expr: &ConditionExpr,
+        left_context: &ConditionContext,
+        right_context: &ConditionContext,
+        loop_prefix: &str,
+    ) -> Option<(String, String)> {
+        if !self.demorgan {
+            return None;
+        }
+
+        let (left, right, joined, negated) = match expr {
+            ConditionExpr::Not(inner) => {
+                let (true_label, false_label) =
+                    self.mixed_labels(inner, left_context, right_context, loop_prefix)?;
+                return Some((false_label, true_label));
+            }
+            ConditionExpr::And(left, right) if range_check(left, right).is_none() => {
+                (left, right, "and", "or")
+            }
+            ConditionExpr::Or(left, right) => (left, right, "or", "and"),
+            _ => return None,
+        };
+
+        let (left_true, left_false) = self.prefixed_labels(left, left_context, loop_prefix);
+        let (right_true, right_false) = self.prefixed_labels(right, right_context, loop_prefix);
+        Some((
+            format!("{} {} {}", left_true, joined, right_true),
+            format!("{} {} {}", left_false, negated, right_false),
+        ))
     }
 
     /// Convert an expression to (true_case_label, false_case_label)
     fn expr_to_labels(&self, expr: &ConditionExpr) -> (String, String) {
         match expr {
             ConditionExpr::Binary { left, op, right } => {
+                // `paused == true` reads like `paused`, and `paused == false` like `!paused`
+                let flag = match (op, right.as_str()) {
+                    (BinaryOp::Eq, "true") | (BinaryOp::NotEq, "false") => Some(true),
+                    (BinaryOp::Eq, "false") | (BinaryOp::NotEq, "true") => Some(false),
+                    _ => None,
+                };
+                if let Some(holds) = flag {
+                    let (true_label, false_label) = self.ident_labels(left);
+                    return if holds {
+                        (true_label, false_label)
+                    } else {
+                        (false_label, true_label)
+                    };
+                }
+
+                let deadline = self
+                    .time_labels
+                    .then(|| deadline_check(left, op, right))
+                    .flatten();
+                if let Some((deadline, passed)) = deadline {
+                    let passed_label = format!("{} has passed", deadline);
+                    let open_label = format!("{} has not passed", deadline);
+                    return if passed {
+                        (passed_label, open_label)
+                    } else {
+                        (open_label, passed_label)
+                    };
+                }
+
+                if let Some(labels) = self.payment_labels(left, op, right) {
+                    return labels;
+                }
+
                 let (true_desc, false_desc) = match op {
                     BinaryOp::Eq => (
                         format!("{} is {}", left, self.humanize(right)),
@@ -78,15 +208,26 @@ impl ConditionLabeler {
             }
 
             ConditionExpr::And(left, right) => {
+                // `lo <= x && x <= hi` reads as "x is within [lo, hi]"
+                if let Some((value, interval)) = range_check(left, right) {
+                    return (
+                        format!("{} is within {}", value, interval),
+                        format!("{} is out of range", value),
+                    );
+                }
+
                 let (left_true, left_false) = self.expr_to_labels(left);
                 let (right_true, right_false) = self.expr_to_labels(right);
 
                 // a && b is true when both are true
                 // a && b is false when either is false (De Morgan: !(a && b) = !a || !b)
-                (
-                    format!("{} and {}", left_true, right_true),
-                    format!("{} or {}", left_false, right_false),
-                )
+                let true_label = format!("{} and {}", left_true, right_true);
+                let false_label = if self.demorgan {
+                    format!("{} or {}", left_false, right_false)
+                } else {
+                    format!("not ({})", true_label)
+                };
+                (true_label, false_label)
             }
 
             ConditionExpr::Or(left, right) => {
@@ -95,16 +236,16 @@ impl ConditionLabeler {
 
                 // a || b is true when either is true
                 // a || b is false when both are false (De Morgan)
-                (
-                    format!("{} or {}", left_true, right_true),
-                    format!("{} and {}", left_false, right_false),
-                )
+                let true_label = format!("{} or {}", left_true, right_true);
+                let false_label = if self.demorgan {
+                    format!("{} and {}", left_false, right_false)
+                } else {
+                    format!("not ({})", true_label)
+                };
+                (true_label, false_label)
             }
 
-            ConditionExpr::Ident(name) => {
-                // Boolean identifier
-                (format!("{} is true", name), format!("{} is false", name))
-            }
+            ConditionExpr::Ident(name) => self.ident_labels(name),
 
             ConditionExpr::ExternalCall(name) => {
                 (format!("{} succeeds", name), format!("{} fails", name))
@@ -112,22 +253,207 @@ impl ConditionLabeler {
         }
     }
 
+    /// Return a copy of the condition with configured identifier prefixes removed
+    ///
+    /// A prefix is only stripped when the shortened name does not already appear
+    /// in the same condition, so `_amount > amount` keeps both names intact.
+    fn strip_condition(&self, condition: &ConditionExpr) -> ConditionExpr {
+        if self.strip_prefixes.is_empty() {
+            return condition.clone();
+        }
+
+        let mut names = HashSet::new();
+        collect_identifiers(condition, &mut names);
+        self.strip_expr(condition, &names)
+    }
+
+    fn strip_expr(&self, expr: &ConditionExpr, names: &HashSet<String>) -> ConditionExpr {
+        match expr {
+            ConditionExpr::Binary { left, op, right } => ConditionExpr::Binary {
+                left: self.strip_text(left, names),
+                op: op.clone(),
+                right: self.strip_text(right, names),
+            },
+            ConditionExpr::Not(inner) => {
+                ConditionExpr::Not(Box::new(self.strip_expr(inner, names)))
+            }
+            ConditionExpr::And(left, right) => ConditionExpr::And(
+                Box::new(self.strip_expr(left, names)),
+                Box::new(self.strip_expr(right, names)),
+            ),
+            ConditionExpr::Or(left, right) => ConditionExpr::Or(
+                Box::new(self.strip_expr(left, names)),
+                Box::new(self.strip_expr(right, names)),
+            ),
+            ConditionExpr::Ident(name) => ConditionExpr::Ident(self.strip_text(name, names)),
+            ConditionExpr::ExternalCall(name) => {
+                ConditionExpr::ExternalCall(self.strip_text(name, names))
+            }
+        }
+    }
+
+    /// Strip prefixes from every identifier token in an expression string,
+    /// leaving string literals and numbers untouched
+    fn strip_text(&self, text: &str, names: &HashSet<String>) -> String {
+        let mut result = String::with_capacity(text.len());
+        for token in tokenize(text) {
+            match token {
+                Token::Ident(ident) => result.push_str(self.strip_ident(ident, names)),
+                Token::Other(other) => result.push_str(other),
+            }
+        }
+        result
+    }
+
+    fn strip_ident<'s>(&self, ident: &'s str, names: &HashSet<String>) -> &'s str {
+        for prefix in &self.strip_prefixes {
+            if let Some(stripped) = ident.strip_prefix(prefix.as_str()) {
+                let is_valid = stripped
+                    .chars()
+                    .next()
+                    .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$');
+                if is_valid && !names.contains(stripped) {
+                    return stripped;
+                }
+            }
+        }
+        ident
+    }
+
+    /// Labels for a boolean identifier; permission flags (`authorized`, `isWhitelisted`, ...)
+    /// describe the caller so `!authorized` reads as "the caller is not authorized",
+    /// initialization flags (`_initialized`, `$._initializing`) describe the contract, and
+    /// `isX(arg)` predicates describe their argument ("user is whitelisted")
+    fn ident_labels(&self, name: &str) -> (String, String) {
+        if let Some((subject, predicate)) = predicate_call(name) {
+            let subject = if subject == "msg.sender" {
+                "the caller"
+            } else {
+                subject
+            };
+            return (
+                format!("{} is {}", subject, predicate),
+                format!("{} is not {}", subject, predicate),
+            );
+        }
+
+        let member = name
+            .rsplit('.')
+            .next()
+            .unwrap_or(name)
+            .trim_start_matches('_');
+        if self
+            .initialized_flags
+            .iter()
+            .any(|flag| member.eq_ignore_ascii_case(flag.trim_start_matches('_')))
+        {
+            return (
+                "the contract is already initialized".to_string(),
+                "the contract is not initialized".to_string(),
+            );
+        }
+        if member.eq_ignore_ascii_case("initializing") {
+            return (
+                "the contract is initializing".to_string(),
+                "the contract is not initializing".to_string(),
+            );
+        }
+        if let Some(subject) = paused_subject(name) {
+            return (
+                format!("{} is paused", subject),
+                format!("{} is not paused", subject),
+            );
+        }
+
+        let flag = name.trim_start_matches('_');
+        let flag = flag
+            .strip_prefix("is")
+            .filter(|rest| rest.starts_with(|c: char| c.is_ascii_uppercase()))
+            .unwrap_or(flag);
+
+        if CALLER_PERMISSIONS
+            .iter()
+            .any(|permission| flag.eq_ignore_ascii_case(permission))
+        {
+            let permission = flag.to_ascii_lowercase();
+            return (
+                format!("the caller is {}", permission),
+                format!("the caller is not {}", permission),
+            );
+        }
+
+        (format!("{} is true", name), format!("{} is false", name))
+    }
+
+    /// Labels for `msg.value` compared to an amount, as the payment sent with the call:
+    /// `msg.value >= price` reads as "the payment is below the price" when it fails
+    fn payment_labels(&self, left: &str, op: &BinaryOp, right: &str) -> Option<(String, String)> {
+        let (op, amount) = if left == "msg.value" {
+            (op.clone(), right)
+        } else if right == "msg.value" {
+            (op.swapped(), left)
+        } else {
+            return None;
+        };
+
+        let payment = |text: &str| format!("the payment is {}", text);
+        let sent = || {
+            (
+                "a payment is sent".to_string(),
+                "no payment is sent".to_string(),
+            )
+        };
+        let labels = match (op, self.humanize(amount).as_str()) {
+            (BinaryOp::NotEq | BinaryOp::Gt, "zero") => sent(),
+            (BinaryOp::Eq | BinaryOp::Lte, "zero") => {
+                let (sent, unpaid) = sent();
+                (unpaid, sent)
+            }
+            (BinaryOp::Gte | BinaryOp::Lt, "zero") => return None,
+            (op, amount) => {
+                // `price` reads as "the price"; literals and expressions stay as they are
+                let amount = if amount
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_')
+                    && !amount.starts_with(|c: char| c.is_ascii_digit())
+                {
+                    format!("the {}", amount)
+                } else {
+                    amount.to_string()
+                };
+                let (holds, fails) = match op {
+                    BinaryOp::Eq => ("", "not "),
+                    BinaryOp::NotEq => ("not ", ""),
+                    BinaryOp::Gt => ("above ", "at most "),
+                    BinaryOp::Gte => ("at least ", "below "),
+                    BinaryOp::Lt => ("below ", "at least "),
+                    BinaryOp::Lte => ("at most ", "above "),
+                };
+                (
+                    payment(&format!("{}{}", holds, amount)),
+                    payment(&format!("{}{}", fails, amount)),
+                )
+            }
+        };
+        Some(labels)
+    }
+
     /// Make a value more human-readable
     fn humanize(&self, value: &str) -> String {
+        // `type(uint256).max` -> "the max uint256"
+        if let Some(rest) = value.strip_prefix("type(") {
+            if let Some((ty, bound)) = rest.split_once(").") {
+                if bound == "max" || bound == "min" {
+                    return format!("the {} {}", bound, ty);
+                }
+            }
+        }
+
         // Handle common cases
         match value {
             "0" => "zero".to_string(),
             "address(0)" => "zero address".to_string(),
-            s if s.starts_with("Number(") => {
-                // Extract number from debug format
-                if let Some(num) = s.strip_prefix("Number(").and_then(|s| s.strip_suffix(")")) {
-                    if num == "0" {
-                        return "zero".to_string();
-                    }
-                    return num.to_string();
-                }
-                s.to_string()
-            }
+            "address(this)" => "this contract".to_string(),
             _ => value.to_string(),
         }
     }
@@ -138,3 +464,661 @@ impl Default for ConditionLabeler {
         Self::new()
     }
 }
+
+/// Name fragments that mark a value as a deadline
+const DEADLINE_WORDS: &[&str] = &["deadline", "expiry", "expiration", "expires"];
+
+/// The deadline a comparison checks against the current block, and whether the
+/// comparison holds once it has passed: `block.timestamp <= deadline` gives
+/// `("the deadline", false)` and `block.number > expiry` gives `("expiry", true)`
+fn deadline_check(left: &str, op: &BinaryOp, right: &str) -> Option<(String, bool)> {
+    let is_now = |s: &str| s == "block.timestamp" || s == "block.number";
+    let is_deadline = |s: &str| {
+        let lower = s.to_ascii_lowercase();
+        DEADLINE_WORDS.iter().any(|word| lower.contains(word))
+    };
+
+    // Whether `now <op> deadline` holds after the deadline
+    let now_above = match op {
+        BinaryOp::Gt | BinaryOp::Gte => true,
+        BinaryOp::Lt | BinaryOp::Lte => false,
+        BinaryOp::Eq | BinaryOp::NotEq => return None,
+    };
+    let (deadline, passed) = if is_now(left) && is_deadline(right) {
+        (right, now_above)
+    } else if is_now(right) && is_deadline(left) {
+        (left, !now_above)
+    } else {
+        return None;
+    };
+    let name = if deadline == "deadline" {
+        "the deadline".to_string()
+    } else {
+        deadline.to_string()
+    };
+    Some((name, passed))
+}
+
+/// A comparison read as a bound on one of its operands
+struct Bound<'c> {
+    value: &'c str,
+    bound: &'c str,
+    inclusive: bool,
+    lower: bool,
+}
+
+/// Both ways to read a comparison as a bound: `lo <= x` bounds `x` from below and `lo`
+/// from above
+fn bounds(condition: &ConditionExpr) -> Vec<Bound<'_>> {
+    let ConditionExpr::Binary { left, op, right } = condition else {
+        return Vec::new();
+    };
+    let (small, big, inclusive) = match op {
+        BinaryOp::Lte => (left, right, true),
+        BinaryOp::Lt => (left, right, false),
+        BinaryOp::Gte => (right, left, true),
+        BinaryOp::Gt => (right, left, false),
+        BinaryOp::Eq | BinaryOp::NotEq => return Vec::new(),
+    };
+
+    vec![
+        Bound {
+            value: big,
+            bound: small,
+            inclusive,
+            lower: true,
+        },
+        Bound {
+            value: small,
+            bound: big,
+            inclusive,
+            lower: false,
+        },
+    ]
+}
+
+/// The value and interval of a range check: a lower and an upper bound on the same
+/// (non-literal) value, in any spelling. `lo <= x && x < hi` gives `x` and `[lo, hi)`
+fn range_check<'c>(left: &'c ConditionExpr, right: &'c ConditionExpr) -> Option<(&'c str, String)> {
+    let right_bounds = bounds(right);
+
+    for a in bounds(left) {
+        for b in &right_bounds {
+            if a.value != b.value
+                || a.lower == b.lower
+                || a.value.starts_with(|c: char| c.is_ascii_digit())
+            {
+                continue;
+            }
+
+            let (lower, upper) = if a.lower { (&a, b) } else { (b, &a) };
+            let interval = format!(
+                "{}{}, {}{}",
+                if lower.inclusive { '[' } else { '(' },
+                lower.bound,
+                upper.bound,
+                if upper.inclusive { ']' } else { ')' }
+            );
+            return Some((a.value, interval));
+        }
+    }
+    None
+}
+
+/// Flags that `initializer`-style modifiers check before a one-shot setup
+const INITIALIZED_FLAGS: &[&str] = &["initialized", "initialised"];
+
+/// Boolean flags that state whether the caller may perform the call
+const CALLER_PERMISSIONS: &[&str] = &[
+    "authorized",
+    "allowed",
+    "approved",
+    "permitted",
+    "whitelisted",
+];
+
+/// What a Pausable flag (`paused`, `_paused`, `paused()`) pauses: this contract, or the
+/// contract it's read from, as in `vault.paused()`
+fn paused_subject(name: &str) -> Option<String> {
+    let name = name.strip_suffix("()").unwrap_or(name);
+    let (owner, flag) = match name.rsplit_once('.') {
+        Some((owner, flag)) => (Some(owner), flag),
+        None => (None, name),
+    };
+    if flag.trim_start_matches('_') != "paused" {
+        return None;
+    }
+
+    Some(match owner {
+        None | Some("this") | Some("$") => "the contract".to_string(),
+        Some(owner) => owner.to_string(),
+    })
+}
+
+/// Split a one-argument `isX(arg)` call into its argument and `X` in words:
+/// `registry.isApprovedForAll(user)` -> ("user", "approved for all")
+fn predicate_call(text: &str) -> Option<(&str, String)> {
+    let (callee, arg) = text.strip_suffix(')')?.rsplit_once('(')?;
+    let function = callee.rsplit('.').next().unwrap_or(callee);
+    let predicate = function
+        .strip_prefix("is")
+        .filter(|rest| rest.starts_with(|c: char| c.is_ascii_uppercase()))?;
+    if !predicate
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        || arg.is_empty()
+        || arg.contains(',')
+    {
+        return None;
+    }
+
+    Some((arg, decamel(predicate)))
+}
+
+/// Lowercase words of a camelCase name, keeping acronyms together:
+/// `KYCVerified` -> "kyc verified"
+fn decamel(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut words = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        let starts_word = i > 0
+            && c.is_ascii_uppercase()
+            && (chars[i - 1].is_ascii_lowercase()
+                || chars
+                    .get(i + 1)
+                    .is_some_and(|next| next.is_ascii_lowercase()));
+        if starts_word {
+            words.push(' ');
+        }
+        words.push(c.to_ascii_lowercase());
+    }
+    words
+}
+
+/// A lexical piece of an expression string
+enum Token<'s> {
+    Ident(&'s str),
+    Other(&'s str),
+}
+
+/// Split an expression string into identifiers and everything else.
+/// Quoted strings and numeric literals are never reported as identifiers.
+fn tokenize(text: &str) -> Vec<Token<'_>> {
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let start = i;
+        let c = bytes[i];
+
+        if c == b'"' || c == b'\'' {
+            // String literal - copy verbatim up to the closing quote
+            i += 1;
+            while i < bytes.len() && bytes[i] != c {
+                i += 1;
+            }
+            i = (i + 1).min(bytes.len());
+            tokens.push(Token::Other(&text[start..i]));
+        } else if c.is_ascii_alphabetic() || c == b'_' || c == b'$' {
+            while i < bytes.len()
+                && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_' || bytes[i] == b'$')
+            {
+                i += 1;
+            }
+            tokens.push(Token::Ident(&text[start..i]));
+        } else if c.is_ascii_digit() {
+            // Numbers (including hex like 0xff and 1e18) stay intact
+            while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                i += 1;
+            }
+            tokens.push(Token::Other(&text[start..i]));
+        } else {
+            // Advance by a full UTF-8 character
+            i += text[start..].chars().next().map_or(1, char::len_utf8);
+            tokens.push(Token::Other(&text[start..i]));
+        }
+    }
+
+    tokens
+}
+
+/// Collect every identifier that appears anywhere in a condition
+fn collect_identifiers(expr: &ConditionExpr, names: &mut HashSet<String>) {
+    let mut add = |text: &str| {
+        for token in tokenize(text) {
+            if let Token::Ident(ident) = token {
+                names.insert(ident.to_string());
+            }
+        }
+    };
+
+    match expr {
+        ConditionExpr::Binary { left, right, .. } => {
+            add(left);
+            add(right);
+        }
+        ConditionExpr::Not(inner) => collect_identifiers(inner, names),
+        ConditionExpr::And(left, right) | ConditionExpr::Or(left, right) => {
+            collect_identifiers(left, names);
+            collect_identifiers(right, names);
+        }
+        ConditionExpr::Ident(name) | ConditionExpr::ExternalCall(name) => add(name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn binary(left: &str, op: BinaryOp, right: &str) -> ConditionExpr {
+        ConditionExpr::Binary {
+            left: left.to_string(),
+            op,
+            right: right.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_strip_prefix() {
+        let labeler = ConditionLabeler::new().with_strip_prefixes(vec!["s_".to_string()]);
+        let condition = binary("s_totalSupply", BinaryOp::Lte, "s_maxSupply");
+
+        let (fail, pass) = labeler.generate_labels(&condition, ConditionContext::Storage, false);
+
+        assert_eq!(fail, "given totalSupply is greater than maxSupply");
+        assert_eq!(pass, "given totalSupply is at most maxSupply");
+    }
+
+    #[test]
+    fn test_strip_prefix_keeps_colliding_names() {
+        let labeler = ConditionLabeler::new().with_strip_prefixes(vec!["_".to_string()]);
+        let condition = binary("_amount", BinaryOp::Gt, "amount");
+
+        let (_, pass) = labeler.generate_labels(&condition, ConditionContext::External, false);
+
+        assert_eq!(pass, "when _amount is greater than amount");
+    }
+
+    #[test]
+    fn test_humanize_type_bounds() {
+        let labeler = ConditionLabeler::new();
+
+        let (fail, pass) = labeler.generate_labels(
+            &binary("cap", BinaryOp::Eq, "type(uint256).max"),
+            ConditionContext::Storage,
+            false,
+        );
+        assert_eq!(fail, "given cap is not the max uint256");
+        assert_eq!(pass, "given cap is the max uint256");
+
+        let (_, pass) = labeler.generate_labels(
+            &binary("delta", BinaryOp::Gt, "type(int128).min"),
+            ConditionContext::External,
+            false,
+        );
+        assert_eq!(pass, "when delta is greater than the min int128");
+    }
+
+    #[test]
+    fn test_humanize_this_contract() {
+        let labeler = ConditionLabeler::new();
+
+        let (fail, pass) = labeler.generate_labels(
+            &binary("to", BinaryOp::NotEq, "address(this)"),
+            ConditionContext::External,
+            false,
+        );
+        assert_eq!(fail, "when to is this contract");
+        assert_eq!(pass, "when to is not this contract");
+    }
+
+    #[test]
+    fn test_humanize_msg_sender_unchanged() {
+        let labeler = ConditionLabeler::new();
+
+        let (_, pass) = labeler.generate_labels(
+            &binary("owner", BinaryOp::Eq, "msg.sender"),
+            ConditionContext::Storage,
+            false,
+        );
+        assert_eq!(pass, "given owner is msg.sender");
+    }
+
+    #[test]
+    fn test_humanize_boolean_literals() {
+        let labeler = ConditionLabeler::new();
+
+        for (op, literal) in [(BinaryOp::Eq, "true"), (BinaryOp::NotEq, "false")] {
+            let (fail, pass) = labeler.generate_labels(
+                &binary("paused", op, literal),
+                ConditionContext::Storage,
+                false,
+            );
+            assert_eq!(fail, "given the contract is not paused");
+            assert_eq!(pass, "given the contract is paused");
+        }
+
+        for (op, literal) in [(BinaryOp::Eq, "false"), (BinaryOp::NotEq, "true")] {
+            let (fail, pass) = labeler.generate_labels(
+                &binary("paused", op, literal),
+                ConditionContext::Storage,
+                false,
+            );
+            assert_eq!(fail, "given the contract is paused");
+            assert_eq!(pass, "given the contract is not paused");
+        }
+    }
+
+    fn amount_and_recipient() -> ConditionExpr {
+        ConditionExpr::And(
+            Box::new(binary("amount", BinaryOp::Gt, "0")),
+            Box::new(binary("to", BinaryOp::NotEq, "address(0)")),
+        )
+    }
+
+    #[test]
+    fn test_deadline_not_passed() {
+        let labeler = ConditionLabeler::new().with_time_labels(true);
+        let condition = binary("block.timestamp", BinaryOp::Lte, "deadline");
+
+        let (fail, pass) = labeler.generate_labels(&condition, ConditionContext::External, false);
+
+        assert_eq!(fail, "when the deadline has passed");
+        assert_eq!(pass, "when the deadline has not passed");
+    }
+
+    #[test]
+    fn test_deadline_passed() {
+        let labeler = ConditionLabeler::new().with_time_labels(true);
+
+        let condition = binary("block.timestamp", BinaryOp::Gt, "deadline");
+        let (fail, pass) = labeler.generate_labels(&condition, ConditionContext::External, false);
+        assert_eq!(fail, "when the deadline has not passed");
+        assert_eq!(pass, "when the deadline has passed");
+
+        // Deadline on the left, compared against the block number
+        let condition = binary("s_auctionExpiry", BinaryOp::Lt, "block.number");
+        let labeler = labeler.with_strip_prefixes(vec!["s_".to_string()]);
+        let (_, pass) = labeler.generate_labels(&condition, ConditionContext::Storage, false);
+        assert_eq!(pass, "given auctionExpiry has passed");
+    }
+
+    #[test]
+    fn test_time_labels_are_opt_in() {
+        let condition = binary("block.timestamp", BinaryOp::Lte, "deadline");
+
+        let (_, pass) =
+            ConditionLabeler::new().generate_labels(&condition, ConditionContext::External, false);
+        assert_eq!(pass, "when block.timestamp is at most deadline");
+
+        // Only deadline-like names get time phrasing
+        let condition = binary("block.timestamp", BinaryOp::Lte, "lastUpdate");
+        let (_, pass) = ConditionLabeler::new()
+            .with_time_labels(true)
+            .generate_labels(&condition, ConditionContext::Storage, false);
+        assert_eq!(pass, "given block.timestamp is at most lastUpdate");
+    }
+
+    fn and(left: ConditionExpr, right: ConditionExpr) -> ConditionExpr {
+        ConditionExpr::And(Box::new(left), Box::new(right))
+    }
+
+    #[test]
+    fn test_inclusive_range() {
+        let labeler = ConditionLabeler::new();
+        let condition = and(
+            binary("minFee", BinaryOp::Lte, "fee"),
+            binary("fee", BinaryOp::Lte, "maxFee"),
+        );
+
+        let (fail, pass) = labeler.generate_labels(&condition, ConditionContext::Storage, false);
+
+        assert_eq!(fail, "given fee is out of range");
+        assert_eq!(pass, "given fee is within [minFee, maxFee]");
+    }
+
+    #[test]
+    fn test_exclusive_range() {
+        let labeler = ConditionLabeler::new();
+        let condition = and(
+            binary("start", BinaryOp::Lt, "block.timestamp"),
+            binary("block.timestamp", BinaryOp::Lt, "end"),
+        );
+
+        let (fail, pass) = labeler.generate_labels(&condition, ConditionContext::Storage, false);
+
+        assert_eq!(fail, "given block.timestamp is out of range");
+        assert_eq!(pass, "given block.timestamp is within (start, end)");
+    }
+
+    #[test]
+    fn test_range_in_any_spelling() {
+        let labeler = ConditionLabeler::new();
+
+        // Upper bound first, with the value on the right of `>`
+        let condition = and(
+            binary("cap", BinaryOp::Gt, "amount"),
+            binary("amount", BinaryOp::Gte, "1"),
+        );
+        let (_, pass) = labeler.generate_labels(&condition, ConditionContext::Storage, false);
+        assert_eq!(pass, "given amount is within [1, cap)");
+
+        let condition = and(
+            binary("amount", BinaryOp::Gt, "0"),
+            binary("amount", BinaryOp::Lte, "maxAmount"),
+        );
+        let (fail, pass) = labeler.generate_labels(&condition, ConditionContext::Storage, false);
+        assert_eq!(fail, "given amount is out of range");
+        assert_eq!(pass, "given amount is within (0, maxAmount]");
+    }
+
+    #[test]
+    fn test_bounds_on_different_values_are_not_a_range() {
+        let labeler = ConditionLabeler::new();
+
+        // Two lower bounds on the same value
+        let condition = and(
+            binary("amount", BinaryOp::Gt, "0"),
+            binary("amount", BinaryOp::Gte, "minAmount"),
+        );
+        let (_, pass) = labeler.generate_labels(&condition, ConditionContext::Storage, false);
+        assert_eq!(
+            pass,
+            "given amount is greater than zero and amount is at least minAmount"
+        );
+
+        // Bounds on different values
+        let condition = and(
+            binary("a", BinaryOp::Lte, "b"),
+            binary("c", BinaryOp::Lte, "d"),
+        );
+        let (_, pass) = labeler.generate_labels(&condition, ConditionContext::External, false);
+        assert_eq!(pass, "when a is at most b and c is at most d");
+    }
+
+    #[test]
+    fn test_negated_conjunction_with_demorgan() {
+        let labeler = ConditionLabeler::new();
+
+        let (fail, pass) =
+            labeler.generate_labels(&amount_and_recipient(), ConditionContext::External, false);
+
+        assert_eq!(fail, "when amount is at most zero or to is zero address");
+        assert_eq!(
+            pass,
+            "when amount is greater than zero and to is not zero address"
+        );
+    }
+
+    #[test]
+    fn test_mixed_conjunction_prefixes_each_side() {
+        let labeler = ConditionLabeler::new();
+        let condition = ConditionExpr::And(
+            Box::new(ConditionExpr::Not(Box::new(ConditionExpr::Ident(
+                "paused".to_string(),
+            )))),
+            Box::new(binary("amount", BinaryOp::Gt, "0")),
+        );
+        let context = ConditionContext::Mixed(
+            Box::new(ConditionContext::Storage),
+            Box::new(ConditionContext::External),
+        );
+
+        let (fail, pass) = labeler.generate_labels(&condition, context.clone(), false);
+        assert_eq!(
+            fail,
+            "given the contract is paused or when amount is at most zero"
+        );
+        assert_eq!(
+            pass,
+            "given the contract is not paused and when amount is greater than zero"
+        );
+
+        // Without De Morgan the negation can't be split, so the storage prefix leads
+        let labeler = ConditionLabeler::new().with_demorgan(false);
+        let (fail, _) = labeler.generate_labels(&condition, context, false);
+        assert_eq!(
+            fail,
+            "given not (the contract is not paused and amount is greater than zero)"
+        );
+    }
+
+    #[test]
+    fn test_negated_conjunction_without_demorgan() {
+        let labeler = ConditionLabeler::new().with_demorgan(false);
+
+        let (fail, pass) =
+            labeler.generate_labels(&amount_and_recipient(), ConditionContext::External, false);
+
+        assert_eq!(
+            fail,
+            "when not (amount is greater than zero and to is not zero address)"
+        );
+        assert_eq!(
+            pass,
+            "when amount is greater than zero and to is not zero address"
+        );
+    }
+
+    #[test]
+    fn test_permission_flag_describes_caller() {
+        let labeler = ConditionLabeler::new();
+        let condition = ConditionExpr::Ident("isAuthorized".to_string());
+
+        let (fail, pass) = labeler.generate_labels(&condition, ConditionContext::External, false);
+
+        assert_eq!(fail, "when the caller is not authorized");
+        assert_eq!(pass, "when the caller is authorized");
+    }
+
+    #[test]
+    fn test_initialized_flag_describes_contract() {
+        let labeler = ConditionLabeler::new();
+        let condition =
+            ConditionExpr::Not(Box::new(ConditionExpr::Ident("$._initialized".to_string())));
+
+        let (fail, pass) = labeler.generate_labels(&condition, ConditionContext::Storage, false);
+
+        assert_eq!(fail, "given the contract is already initialized");
+        assert_eq!(pass, "given the contract is not initialized");
+    }
+
+    #[test]
+    fn test_configured_initialized_flags() {
+        let labeler = ConditionLabeler::new().with_initialized_flags(vec!["setUpDone".to_string()]);
+        let setup = ConditionExpr::Not(Box::new(ConditionExpr::Ident("_setUpDone".to_string())));
+        let initialized =
+            ConditionExpr::Not(Box::new(ConditionExpr::Ident("initialized".to_string())));
+
+        let (fail, _) = labeler.generate_labels(&setup, ConditionContext::Storage, false);
+        assert_eq!(fail, "given the contract is already initialized");
+        // The configured names replace the defaults
+        let (fail, _) = labeler.generate_labels(&initialized, ConditionContext::Storage, false);
+        assert_eq!(fail, "given initialized is true");
+    }
+
+    #[test]
+    fn test_is_predicate_call_describes_argument() {
+        let labeler = ConditionLabeler::new();
+        let condition = ConditionExpr::Ident("isWhitelisted(user)".to_string());
+
+        let (fail, pass) = labeler.generate_labels(&condition, ConditionContext::External, false);
+
+        assert_eq!(fail, "when user is not whitelisted");
+        assert_eq!(pass, "when user is whitelisted");
+
+        let condition = ConditionExpr::Not(Box::new(ConditionExpr::Ident(
+            "registry.isKYCVerified(msg.sender)".to_string(),
+        )));
+        let (fail, _) = labeler.generate_labels(&condition, ConditionContext::External, false);
+        assert_eq!(fail, "when the caller is kyc verified");
+    }
+
+    #[test]
+    fn test_other_call_keeps_true_false() {
+        let labeler = ConditionLabeler::new();
+        let condition = ConditionExpr::Ident("check(x)".to_string());
+
+        let (fail, pass) = labeler.generate_labels(&condition, ConditionContext::External, false);
+
+        assert_eq!(fail, "when check(x) is false");
+        assert_eq!(pass, "when check(x) is true");
+    }
+
+    #[test]
+    fn test_payment_below_price() {
+        let labeler = ConditionLabeler::new();
+        let condition = binary("msg.value", BinaryOp::Gte, "price");
+
+        let (fail, pass) = labeler.generate_labels(&condition, ConditionContext::External, false);
+
+        assert_eq!(fail, "when the payment is below the price");
+        assert_eq!(pass, "when the payment is at least the price");
+
+        // Either side of the comparison
+        let condition = binary("price", BinaryOp::Lte, "msg.value");
+        let (fail, _) = labeler.generate_labels(&condition, ConditionContext::External, false);
+        assert_eq!(fail, "when the payment is below the price");
+    }
+
+    #[test]
+    fn test_payment_of_zero() {
+        let labeler = ConditionLabeler::new();
+        let condition = binary("msg.value", BinaryOp::Eq, "0");
+
+        let (fail, pass) = labeler.generate_labels(&condition, ConditionContext::External, false);
+
+        assert_eq!(fail, "when a payment is sent");
+        assert_eq!(pass, "when no payment is sent");
+    }
+
+    #[test]
+    fn test_paused_flag_describes_contract() {
+        let labeler = ConditionLabeler::new();
+
+        for flag in ["paused", "_paused", "paused()", "this.paused()"] {
+            let condition = ConditionExpr::Not(Box::new(ConditionExpr::Ident(flag.to_string())));
+            let (fail, pass) =
+                labeler.generate_labels(&condition, ConditionContext::Storage, false);
+            assert_eq!(fail, "given the contract is paused");
+            assert_eq!(pass, "given the contract is not paused");
+        }
+
+        // Another contract's flag names that contract
+        let condition = ConditionExpr::Ident("otherVault.paused()".to_string());
+        let (fail, _) = labeler.generate_labels(&condition, ConditionContext::External, false);
+        assert_eq!(fail, "when otherVault is not paused");
+    }
+
+    #[test]
+    fn test_plain_flag_keeps_true_false() {
+        let labeler = ConditionLabeler::new();
+        let condition = ConditionExpr::Ident("locked".to_string());
+
+        let (fail, pass) = labeler.generate_labels(&condition, ConditionContext::Storage, false);
+
+        assert_eq!(fail, "given locked is false");
+        assert_eq!(pass, "given locked is true");
+    }
+}
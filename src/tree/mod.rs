@@ -3,4 +3,4 @@
 mod builder;
 mod labeler;
 
-pub use builder::{TreeBuilder, TreeNode};
+pub use builder::{LabelConfig, TreeBuilder, TreeError, TreeNode, TreeOptions};
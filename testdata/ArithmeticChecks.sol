@@ -0,0 +1,15 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Contract whose checks compare arithmetic expressions and unit literals
+contract ArithmeticChecks {
+    uint256 public totalDeposits;
+    uint256 public cap;
+
+    function deposit(uint256 amount, uint256 fee) external {
+        require(amount >= 1 ether, "Too small");
+        require(fee * 100 <= amount, "Fee too high");
+        require(totalDeposits + amount <= cap, "Cap exceeded");
+        totalDeposits += amount;
+    }
+}
@@ -0,0 +1,15 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Contract that validates its inputs in inline assembly
+contract AssemblyGuard {
+    function transfer(address to, uint256 amount) external {
+        assembly {
+            if iszero(to) { revert(0, 0) }
+            if gt(amount, 100) {
+                mstore(0x00, 0x1234)
+                revert(0x1c, 0x04)
+            }
+        }
+    }
+}
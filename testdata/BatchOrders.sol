@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Contract iterating a calldata struct array with a per-element check
+contract BatchOrders {
+    struct Order {
+        address maker;
+        uint256 amount;
+    }
+
+    uint256 public totalVolume;
+
+    function fillOrders(Order[] calldata orders) external {
+        for (uint256 i = 0; i < orders.length; i++) {
+            require(orders[i].amount > 0, "Invalid amount");
+            totalVolume += orders[i].amount;
+        }
+    }
+}
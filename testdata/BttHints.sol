@@ -0,0 +1,15 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Contract whose NatSpec carries test hints
+contract BttHints {
+    uint256 public totalAssets;
+
+    /// @notice Deposit assets into the vault
+    /// @custom:btt given the vault is empty
+    /// @custom:btt given the vault has assets
+    function deposit(uint256 amount) external {
+        require(amount > 0, "Zero amount");
+        totalAssets += amount;
+    }
+}
@@ -0,0 +1,18 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Contract with a checked addition that reverts on overflow, next to one that wraps
+contract CheckedMath {
+    uint256 public totalSupply;
+    uint256 public cap;
+    mapping(address => uint256) public balanceOf;
+
+    function mint(address to, uint256 amount) external {
+        uint256 newSupply = totalSupply + amount;
+        require(newSupply <= cap, "Over cap");
+        totalSupply = newSupply;
+        unchecked {
+            balanceOf[to] += amount;
+        }
+    }
+}
@@ -0,0 +1,32 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+uint256 constant MIN_WITHDRAWAL = 10;
+
+/// Base contract declaring a constant its children compare against
+contract FeeLimits {
+    uint256 internal constant MAX_FEE = 500;
+}
+
+/// Contract whose guards compare against named constants: its own, one declared at
+/// file level and one inherited
+contract ConstantLimits is FeeLimits {
+    uint256 public constant MAX_DEPOSIT = 100;
+    uint256 public total;
+    uint256 public fee;
+
+    function deposit(uint256 amount) external {
+        require(amount <= MAX_DEPOSIT, "Too much");
+        total += amount;
+    }
+
+    function withdraw(uint256 amount) external {
+        require(amount >= MIN_WITHDRAWAL, "Too little");
+        total -= amount;
+    }
+
+    function setFee(uint256 newFee) external {
+        require(newFee <= MAX_FEE, "Fee too high");
+        fee = newFee;
+    }
+}
@@ -0,0 +1,24 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+interface IVault {
+    function paused() external view returns (bool);
+}
+
+/// Contract checking both its own pause flag and that of another contract
+contract VaultRouter {
+    struct Route {
+        address vault;
+        uint256 amount;
+    }
+
+    bool public paused;
+    IVault public otherVault;
+    Route public lastRoute;
+
+    function route(uint256 amount) external {
+        require(!this.paused(), "Router paused");
+        require(!otherVault.paused(), "Vault paused");
+        require(lastRoute.amount < amount, "Stale route");
+    }
+}
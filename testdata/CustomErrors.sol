@@ -0,0 +1,18 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.26;
+
+/// Contract reverting with custom errors
+contract CustomErrors {
+    error NotOwner();
+    error InvalidAmount(uint256 amount);
+
+    address public owner;
+    uint256 public total;
+
+    function deposit(uint256 amount) external {
+        if (msg.sender != owner) revert NotOwner();
+        require(amount > 0, InvalidAmount(amount));
+        require(total < 1000, "Cap reached");
+        total += amount;
+    }
+}
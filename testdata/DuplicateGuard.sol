@@ -0,0 +1,24 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Contract whose functions repeat the check their modifier already makes
+contract DuplicateGuard {
+    address public owner;
+    uint256 public value;
+
+    modifier onlyOwner() {
+        require(msg.sender == owner, "Not owner");
+        _;
+    }
+
+    function setValue(uint256 newValue) external onlyOwner {
+        require(msg.sender == owner, "Not owner");
+        require(newValue > 0, "Invalid value");
+        value = newValue;
+    }
+
+    function setOwner(address newOwner) external onlyOwner {
+        require(msg.sender == owner, "Only the owner");
+        owner = newOwner;
+    }
+}
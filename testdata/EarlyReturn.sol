@@ -0,0 +1,24 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Contract using a guard clause that returns early instead of reverting
+contract EarlyReturn {
+    uint256 public lastUpdate;
+    uint256 public rate;
+
+    function accrue(uint256 newRate) external {
+        require(newRate > 0, "Invalid rate");
+        if (lastUpdate == block.timestamp) return;
+        rate = newRate;
+        lastUpdate = block.timestamp;
+    }
+
+    function sync(uint256 newRate) external {
+        if (lastUpdate == block.timestamp) {
+            require(newRate == rate, "Rate already set");
+            return;
+        }
+        rate = newRate;
+        lastUpdate = block.timestamp;
+    }
+}
@@ -0,0 +1,27 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Contract whose guards revert in the else-branch
+contract ElseRevert {
+    uint256 public cap;
+    uint256 public total;
+
+    error OverCap();
+
+    function join(uint256 amount) external {
+        if (amount <= cap) {
+            require(amount > 0, "Zero amount");
+            total += amount;
+        } else {
+            revert OverCap();
+        }
+    }
+
+    function settle(bool early) external {
+        if (early) {
+            revert("Too early");
+        } else {
+            revert("Closed");
+        }
+    }
+}
@@ -0,0 +1,25 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Auction driven by an enum state machine
+contract EnumStateMachine {
+    enum State {
+        Pending,
+        Active,
+        Ended
+    }
+
+    State public state;
+    uint256 public highestBid;
+
+    function bid() external payable {
+        require(state == State.Active, "Not active");
+        require(msg.value > highestBid, "Bid too low");
+        highestBid = msg.value;
+    }
+
+    function settle() external {
+        if (state != State.Ended) revert("Not ended");
+        state = State.Pending;
+    }
+}
@@ -0,0 +1,33 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Contract whose guards live in internal helpers
+contract HelperGuard {
+    mapping(address => uint256) public balances;
+    bool public paused;
+
+    function deposit(uint256 value) external {
+        _validate(value);
+        balances[msg.sender] += value;
+    }
+
+    function withdraw(uint256 value) external {
+        _checkWithdrawal(value);
+        require(balances[msg.sender] >= value, "Insufficient balance");
+        balances[msg.sender] -= value;
+    }
+
+    function _validate(uint256 amount) internal view {
+        _whenNotPaused();
+        require(amount > 0, "Zero amount");
+    }
+
+    function _checkWithdrawal(uint256 amount) internal pure {
+        if (amount == 0) return;
+        require(amount > 10, "Too small");
+    }
+
+    function _whenNotPaused() private view {
+        if (paused) revert("Paused");
+    }
+}
@@ -0,0 +1,18 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Contract whose if/else arms each run their own checks
+contract IfElseArms {
+    uint256 public vaultBalance;
+    uint256 public limit;
+
+    function fund(bool useVault, uint256 amount) external payable {
+        if (useVault) {
+            require(vaultBalance >= amount, "Vault too low");
+            vaultBalance -= amount;
+        } else {
+            require(msg.value >= amount, "Not enough ETH");
+        }
+        require(amount <= limit, "Over limit");
+    }
+}
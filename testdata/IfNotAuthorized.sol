@@ -0,0 +1,14 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Contract reverting on a negated permission flag
+contract IfNotAuthorized {
+    mapping(address => bool) public operators;
+
+    error Unauthorized();
+
+    function execute() external {
+        bool authorized = operators[msg.sender];
+        if (!authorized) revert Unauthorized();
+    }
+}
@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Upgradeable-style contract set up once through an `initializer` modifier
+contract InitializerGuard {
+    bool private _initialized;
+    address public owner;
+
+    modifier initializer() {
+        require(!_initialized, "Already initialized");
+        _initialized = true;
+        _;
+    }
+
+    function initialize(address owner_) external initializer {
+        require(owner_ != address(0), "Zero owner");
+        owner = owner_;
+    }
+}
@@ -0,0 +1,20 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Contract mixing require guards with assert invariants
+contract Invariants {
+    uint256 public totalSupply;
+    uint256 public sumOfBalances;
+    mapping(address => uint256) public balanceOf;
+
+    function mint(address to, uint256 amount) external {
+        require(amount > 0, "Invalid amount");
+
+        totalSupply += amount;
+        sumOfBalances += amount;
+        balanceOf[to] += amount;
+
+        assert(totalSupply == sumOfBalances);
+        assert(totalSupply >= amount);
+    }
+}
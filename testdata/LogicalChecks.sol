@@ -0,0 +1,16 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Contract whose checks combine conditions with && and ||
+contract LogicalChecks {
+    bool public locked;
+    bool public frozen;
+    uint256 public maxAmount;
+
+    error Halted();
+
+    function deposit(uint256 amount) external {
+        if (locked || frozen) revert Halted();
+        require(amount > 0 && amount <= maxAmount, "Invalid amount");
+    }
+}
@@ -0,0 +1,14 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Contract with a loop mixing counter-bound and per-element checks
+contract LoopCounter {
+    uint256 public maxIterations = 50;
+
+    function process(uint256[] calldata amounts) external {
+        for (uint256 i = 0; i < amounts.length; i++) {
+            require(i < maxIterations, "Too many items");
+            require(amounts[i] > 0, "Invalid amount");
+        }
+    }
+}
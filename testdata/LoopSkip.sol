@@ -0,0 +1,16 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Contract whose loop skips items with `continue` and stops with `break`
+contract LoopSkip {
+    mapping(address => uint256) public balances;
+
+    function airdrop(address[] calldata recipients, uint256[] calldata amounts, uint256 limit) external {
+        for (uint256 i = 0; i < recipients.length; i++) {
+            if (i >= limit) break;
+            if (amounts[i] == 0) continue;
+            require(balances[recipients[i]] == 0, "Already funded");
+            balances[recipients[i]] = amounts[i];
+        }
+    }
+}
@@ -0,0 +1,20 @@
+
+    contract TestLowLevel {
+        error CallFailed();
+
+        function testCall(address target, bytes calldata data) public payable {
+            (bool ok, ) = target.call{value: msg.value}(data);
+            require(ok, "Call failed");
+        }
+
+        function testDelegatecall(address implementation, bytes calldata data) public {
+            (bool success, bytes memory result) = implementation.delegatecall(data);
+            if (!success) revert CallFailed();
+        }
+
+        function testStaticcall(address target, bytes calldata data) public view {
+            (bool ok, ) = target.staticcall(data);
+            require(ok);
+        }
+    }
+    
\ No newline at end of file
@@ -0,0 +1,16 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Contract with a long run of sequential checks
+contract ManyChecks {
+    uint256 public cap;
+    bool public paused;
+
+    function configure(uint256 fee, uint256 delay, address admin) external {
+        require(!paused, "Paused");
+        require(fee <= 1000, "Fee too high");
+        require(delay > 0, "Zero delay");
+        require(admin != address(0), "Zero admin");
+        require(fee < cap, "Over cap");
+    }
+}
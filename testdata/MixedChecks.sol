@@ -0,0 +1,11 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Contract whose checks combine a storage flag with a parameter check
+contract MixedChecks {
+    bool public paused;
+
+    function deposit(uint256 amount) external {
+        require(!paused && amount > 0, "Unavailable");
+    }
+}
@@ -0,0 +1,24 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Contract whose modifier iterates over a roles array
+contract ModifierLoop {
+    address[] public admins;
+    uint256 public value;
+
+    modifier onlyAdmin() {
+        bool found;
+        for (uint256 i = 0; i < admins.length; i++) {
+            require(admins[i] != address(0), "Invalid admin");
+            if (admins[i] == msg.sender) {
+                found = true;
+            }
+        }
+        require(found, "Not admin");
+        _;
+    }
+
+    function setValue(uint256 newValue) external onlyAdmin {
+        value = newValue;
+    }
+}
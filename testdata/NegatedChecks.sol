@@ -0,0 +1,12 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Contract whose checks use negations and redundant parentheses
+contract NegatedChecks {
+    uint256 public cap;
+
+    function mint(uint256 amount) external {
+        require(!(amount == 0), "Zero amount");
+        require(((amount < cap)), "Over cap");
+    }
+}
@@ -0,0 +1,15 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Contract whose check reads a nested mapping
+contract NestedMapping {
+    mapping(address => mapping(address => uint256)) public allowance;
+    mapping(address => uint256) public balanceOf;
+
+    function transferFrom(address owner, address spender, uint256 amount) external {
+        require(allowance[owner][spender] >= amount, "Insufficient allowance");
+        allowance[owner][spender] -= amount;
+        balanceOf[owner] -= amount;
+        balanceOf[spender] += amount;
+    }
+}
@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Contract whose modifier checks state both before and after the function body
+contract PostCheckModifier {
+    uint256 public balance;
+    bool public locked;
+
+    modifier keepsReserve() {
+        require(!locked, "Locked");
+        _;
+        require(balance >= 100, "Reserve too low");
+    }
+
+    function withdraw(uint256 amount) external keepsReserve {
+        require(amount > 0, "Invalid amount");
+        balance -= amount;
+    }
+}
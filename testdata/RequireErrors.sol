@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.26;
+
+library Errors {
+    error Unauthorized(address caller);
+}
+
+/// Contract using `require(condition, CustomError(...))`
+contract RequireErrors {
+    error SameOwner();
+
+    address public owner;
+
+    function setOwner(address newOwner) external {
+        require(msg.sender == owner, Errors.Unauthorized(msg.sender));
+        require(newOwner != owner, SameOwner());
+        owner = newOwner;
+    }
+}
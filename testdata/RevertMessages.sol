@@ -0,0 +1,17 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Contract mixing checks with and without revert messages
+contract RevertMessages {
+    uint256 public balance;
+    bool public paused;
+
+    function withdraw(uint256 amount) external {
+        require(amount > 0, "amount must be positive");
+        require(amount <= balance);
+        if (paused) {
+            revert("paused");
+        }
+        balance -= amount;
+    }
+}
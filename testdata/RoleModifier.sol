@@ -0,0 +1,18 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Contract with an access-control modifier that takes the role as an argument
+contract RoleModifier {
+    bytes32 public constant ADMIN = keccak256("ADMIN");
+
+    mapping(bytes32 => mapping(address => bool)) public roles;
+
+    modifier onlyRole(bytes32 role) {
+        require(roles[role][msg.sender], "Missing role");
+        _;
+    }
+
+    function setFee(uint256 fee) external onlyRole(ADMIN) {
+        require(fee <= 1000, "Fee too high");
+    }
+}
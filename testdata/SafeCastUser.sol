@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+library SafeCast {
+    function toUint128(uint256 value) internal pure returns (uint128) {
+        require(value <= type(uint128).max, "SafeCast: value doesn't fit in 128 bits");
+        return uint128(value);
+    }
+}
+
+/// Contract that narrows amounts with SafeCast
+contract SafeCastUser {
+    using SafeCast for uint256;
+
+    uint128 public total;
+
+    function deposit(uint256 amount) external {
+        require(amount > 0, "Zero amount");
+        total += amount.toUint128();
+    }
+}
@@ -0,0 +1,22 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Contract with the same independent checks in different orders
+contract StableOrder {
+    mapping(address => uint256) public balances;
+
+    function recipientFirst(address to, uint256 amount) external {
+        require(to != address(0), "Zero address");
+        require(amount > 0, "Zero amount");
+    }
+
+    function amountFirst(address to, uint256 amount) external {
+        require(amount > 0, "Zero amount");
+        require(to != address(0), "Zero address");
+    }
+
+    function dependent(address to, uint256 amount) external {
+        require(to != address(0), "Zero address");
+        require(balances[to] >= amount, "Insufficient balance");
+    }
+}
@@ -0,0 +1,15 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Contract checking locals copied from storage
+contract StorageLocal {
+    mapping(address => uint256) public balances;
+
+    function withdraw(uint256 amount) external {
+        uint256 bal = balances[msg.sender];
+        require(bal >= amount, "Insufficient balance");
+        uint256 limit = amount * 2;
+        require(limit <= 1000, "Over limit");
+        balances[msg.sender] = bal - amount;
+    }
+}
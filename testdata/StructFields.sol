@@ -0,0 +1,26 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Contract checking struct fields both in storage and in memory
+contract OrderBook {
+    struct Order {
+        address maker;
+        uint256 amount;
+        bool filled;
+    }
+
+    mapping(uint256 => Order) public orders;
+    /// Shadowed by the parameter of `submit`
+    Order public order;
+
+    function fill(uint256 id) external {
+        require(orders[id].filled == false, "Already filled");
+        require(orders[id].amount > 0, "Empty order");
+        orders[id].filled = true;
+    }
+
+    function submit(Order memory order) external {
+        require(order.filled == false, "Already filled");
+        require(order.amount > 0, "Zero amount");
+    }
+}
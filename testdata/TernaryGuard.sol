@@ -0,0 +1,20 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Contract with checks written as conditional expressions
+contract TernaryGuard {
+    uint256 public limit;
+    uint256 public claimed;
+
+    function deposit(uint256 amount, bool boosted) external {
+        require(boosted ? amount <= limit * 2 : amount <= limit, "Over limit");
+    }
+
+    function claim(bool early) external {
+        early ? revert("Too early") : _claim();
+    }
+
+    function _claim() internal {
+        claimed += 1;
+    }
+}
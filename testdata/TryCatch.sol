@@ -0,0 +1,33 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+interface IOracle {
+    function price() external returns (uint256);
+}
+
+/// Contract reading an oracle with try/catch
+contract TryCatch {
+    IOracle public oracle;
+    uint256 public minPrice;
+    uint256 public lastPrice;
+
+    /// The catch rethrows, and the success block has its own guard
+    function update() external {
+        try oracle.price() returns (uint256 p) {
+            require(p >= minPrice, "Price too low");
+            lastPrice = p;
+        } catch {
+            revert("Oracle failed");
+        }
+    }
+
+    /// The catch handles the failure by falling back to the last price
+    function updateOrKeep(uint256 fallbackPrice) external {
+        try oracle.price() returns (uint256 p) {
+            lastPrice = p;
+        } catch {
+            require(fallbackPrice > 0, "No fallback");
+            lastPrice = fallbackPrice;
+        }
+    }
+}
@@ -0,0 +1,16 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Contract with a check on arithmetic that wraps inside an `unchecked` block
+contract UncheckedMath {
+    uint256 public total;
+    uint256 public cap;
+
+    function add(uint256 amount) external {
+        require(total + amount <= cap, "Over cap");
+        unchecked {
+            require(total - amount < cap, "Wrapped");
+        }
+        total += amount;
+    }
+}
@@ -0,0 +1,30 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Contract whose if/else chains end in an `assert(false)` or `revert("unreachable")` default
+contract Unreachable {
+    uint256 public total;
+
+    function execute(uint8 kind, uint256 amount) external {
+        if (kind == 0) {
+            total += amount;
+        } else if (kind == 1) {
+            require(total >= amount, "Insufficient");
+            total -= amount;
+        } else {
+            assert(false);
+        }
+    }
+
+    function settle(uint8 kind, uint256 amount) external {
+        if (kind == 0) {
+            total += amount;
+        } else {
+            revert("unreachable");
+        }
+    }
+
+    function retire() external {
+        require(false, "Retired");
+    }
+}
@@ -0,0 +1,12 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Contract using a modifier that is defined nowhere in the project
+contract UnresolvedModifier {
+    uint256 public value;
+
+    function setValue(uint256 newValue) external onlyRole {
+        require(newValue > 0, "Invalid value");
+        value = newValue;
+    }
+}
@@ -0,0 +1,24 @@
+
+    interface IERC20 {
+        function transfer(address to, uint256 amount) external returns (bool);
+    }
+
+    library SafeERC20 {
+        function safeTransfer(IERC20 token, address to, uint256 value) internal {
+            require(token.transfer(to, value), "SafeERC20: transfer failed");
+        }
+    }
+
+    contract TestToken {
+        using SafeERC20 for IERC20;
+        IERC20 token;
+        
+        function testSafeTransfer(address to, uint256 amount) public {
+            token.safeTransfer(to, amount);
+        }
+        
+        function testTransfer(address to, uint256 amount) public {
+            token.transfer(to, amount);
+        }
+    }
+    
\ No newline at end of file
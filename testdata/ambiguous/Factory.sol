@@ -0,0 +1,5 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Its name starts with "Vault" but it is not a Vault definition
+contract VaultFactory {}
@@ -0,0 +1,5 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Named after its contract, so it wins over other files defining Registry
+contract Registry {}
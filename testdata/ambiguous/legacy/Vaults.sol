@@ -0,0 +1,12 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// First of two files defining a contract named Vault
+contract Vault {
+    function deposit(uint256 amount) external {
+        require(amount > 0, "Invalid amount");
+    }
+}
+
+/// Shares its name with Registry.sol, which takes precedence
+contract Registry {}
@@ -0,0 +1,9 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Second of two files defining a contract named Vault
+contract Vault {
+    function deposit(uint256 amount) external {
+        require(amount >= 1 ether, "Deposit too small");
+    }
+}
@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+import "./Base.sol";
+
+/// @title Contract using a modifier inherited from Base
+contract Child is Base {
+    uint256 public fee;
+
+    function setFee(uint256 newFee) external onlyOwner {
+        require(newFee <= 100, "Fee too high");
+        fee = newFee;
+    }
+
+    function setFee(uint256 newFee, address recipient) external onlyOwner {
+        require(recipient != address(0), "Invalid recipient");
+        fee = newFee;
+    }
+}
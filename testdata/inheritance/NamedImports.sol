@@ -0,0 +1,15 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+import {Ownable} from "./Ownable.sol";
+import {Pausable as PausableBase} from "./Pausable.sol";
+
+/// @title Child contract that inherits through named and aliased imports
+contract NamedImports is Ownable, PausableBase {
+    uint256 public value;
+
+    function setValue(uint256 newValue) external onlyOwner whenNotPaused {
+        require(newValue > 0, "Value must be positive");
+        value = newValue;
+    }
+}
@@ -0,0 +1,29 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// @title Base contract with a virtual ownership modifier
+abstract contract Guarded {
+    address public owner;
+
+    modifier onlyOwner() virtual {
+        require(msg.sender == owner, "Not owner");
+        _;
+    }
+}
+
+/// @title Middle contract overriding the modifier with a stricter check
+abstract contract AdminGuarded is Guarded {
+    address public admin;
+
+    modifier onlyOwner() override {
+        require(msg.sender == admin, "Not admin");
+        _;
+    }
+}
+
+/// @title Contract using the overridden modifier without redefining it
+contract Treasury is AdminGuarded {
+    function sweep(uint256 amount) external onlyOwner {
+        require(amount > 0, "Zero amount");
+    }
+}
@@ -0,0 +1,12 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// @title Base contract with pause modifier
+abstract contract Pausable {
+    bool public paused;
+
+    modifier whenNotPaused() {
+        require(!paused, "Paused");
+        _;
+    }
+}
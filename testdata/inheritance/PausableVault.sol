@@ -0,0 +1,18 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+import "./Pausable.sol";
+
+/// @title Contract checking a state variable inherited from Pausable
+contract PausableVault is Pausable {
+    uint256 public deposits;
+
+    function deposit(uint256 amount) external {
+        require(!paused, "Paused");
+        deposits += amount;
+    }
+
+    function withdraw(uint256 amount) external whenNotPaused {
+        deposits -= amount;
+    }
+}
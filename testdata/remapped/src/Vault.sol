@@ -0,0 +1,8 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+import "@openzeppelin/access/Ownable.sol";
+
+contract Vault is Ownable {
+    function sweep() external onlyOwner {}
+}
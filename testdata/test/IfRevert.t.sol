@@ -0,0 +1,11 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+/// Partial BTT test suite for IfRevert::mint
+contract IfRevertTest {
+    function setUp() public {}
+
+    function test_RevertWhen_AmountIsZero() external {}
+
+    function test_GivenTotalSupplyPlusAmountIsAtMostMaxSupply() external {}
+}
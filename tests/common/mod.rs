@@ -12,6 +12,79 @@ pub fn testdata_dir() -> PathBuf {
     PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("testdata")
 }
 
+/// Project rooted at the testdata directory, for tests that go through the library parser
+pub fn test_project() -> acacia::foundry::FoundryProject {
+    acacia::foundry::FoundryProject::new(testdata_dir(), testdata_dir(), vec![], vec![])
+}
+
+/// Generate a BTT tree using the library's parser and tree builder
+/// Path is relative to testdata directory (e.g. "inheritance/ChildContract")
+pub fn generate_tree_with_library(
+    relative_path: &str,
+    contract_name: &str,
+    function_name: &str,
+) -> String {
+    let tree = build_tree_with_library(relative_path, contract_name, function_name);
+    acacia::output::render_to_string(&tree)
+}
+
+/// Like `generate_tree_with_library`, rendered with source annotations
+pub fn generate_annotated_tree_with_library(
+    relative_path: &str,
+    contract_name: &str,
+    function_name: &str,
+) -> String {
+    let tree = build_tree_with_library(relative_path, contract_name, function_name);
+    acacia::output::render_annotated_to_string(&tree)
+}
+
+/// List the `assert`ed invariants of a function using the library's parser
+pub fn generate_invariants_with_library(
+    relative_path: &str,
+    contract_name: &str,
+    function_name: &str,
+) -> String {
+    let project = test_project();
+    let parser = acacia::parser::SolarParser::new(&project);
+    let file_path = testdata_dir().join(format!("{}.sol", relative_path));
+
+    let function_ctx = parser
+        .parse_function_with_inheritance(&file_path, contract_name, function_name)
+        .expect("Failed to parse function");
+
+    acacia::output::render_invariants(
+        function_name,
+        &function_ctx.branch_points,
+        acacia::output::Connectors::UNICODE,
+    )
+}
+
+fn build_tree_with_library(
+    relative_path: &str,
+    contract_name: &str,
+    function_name: &str,
+) -> acacia::tree::TreeNode {
+    let function_ctx = parse_with_library(relative_path, contract_name, function_name);
+
+    acacia::tree::TreeBuilder::build(function_name, function_ctx.branch_points)
+        .expect("Failed to build tree")
+}
+
+/// Parse a function with the library's parser and its default options.
+/// Path is relative to testdata directory, without the `.sol` extension
+pub fn parse_with_library(
+    relative_path: &str,
+    contract_name: &str,
+    function_name: &str,
+) -> acacia::FunctionContext {
+    let project = test_project();
+    let file_path = testdata_dir().join(format!("{}.sol", relative_path));
+
+    acacia::parser::SolarParser::new(&project)
+        .parse_function(&file_path, contract_name, function_name)
+        .expect("Failed to parse function")
+}
+
 /// Generate a BTT tree for a function in a test contract
 pub fn generate_tree_for_function(contract_name: &str, function_name: &str) -> String {
     let file_path = testdata_dir().join(format!("{}.sol", contract_name));
@@ -698,20 +771,68 @@ fn expr_to_string(expr: &ast::Expr<'_>) -> String {
 
     match &expr.kind {
         Ident(ident) => ident.to_string(),
-        Lit(lit, _) => format!("{:?}", lit.kind),
+        Lit(lit, sub_denomination) => {
+            let value = match lit.kind {
+                ast::LitKind::Str(..) => format!("\"{}\"", lit.symbol),
+                _ => lit.symbol.to_string(),
+            };
+            match sub_denomination {
+                Some(unit) => format!("{} {}", value, unit),
+                None => value,
+            }
+        }
         Member(base, member) => format!("{}.{}", expr_to_string(base), member.as_str()),
-        Index(base, _) => format!("{}[...]", expr_to_string(base)),
+        Index(base, kind) => {
+            let key = match kind {
+                ast::IndexKind::Index(index) => {
+                    index.as_deref().map(expr_to_string).unwrap_or_default()
+                }
+                ast::IndexKind::Range(start, end) => format!(
+                    "{}:{}",
+                    start.as_deref().map(expr_to_string).unwrap_or_default(),
+                    end.as_deref().map(expr_to_string).unwrap_or_default()
+                ),
+            };
+            format!("{}[{}]", expr_to_string(base), key)
+        }
         Call(callee, _) => format!("{}(...)", expr_to_string(callee)),
         Binary(left, op, right) => format!(
-            "{} {:?} {}",
+            "{} {} {}",
             expr_to_string(left),
-            op.kind,
+            binary_op_symbol(op.kind),
             expr_to_string(right)
         ),
         _ => "expr".to_string(),
     }
 }
 
+fn binary_op_symbol(op: ast::BinOpKind) -> &'static str {
+    use ast::BinOpKind::*;
+
+    match op {
+        Lt => "<",
+        Le => "<=",
+        Gt => ">",
+        Ge => ">=",
+        Eq => "==",
+        Ne => "!=",
+        Or => "||",
+        And => "&&",
+        Shr => ">>",
+        Shl => "<<",
+        Sar => ">>>",
+        BitAnd => "&",
+        BitOr => "|",
+        BitXor => "^",
+        Add => "+",
+        Sub => "-",
+        Pow => "**",
+        Mul => "*",
+        Div => "/",
+        Mod => "%",
+    }
+}
+
 fn classify_condition(
     condition: &ConditionExpr,
     state_vars: &[String],
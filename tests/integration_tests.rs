@@ -2,99 +2,1170 @@
 
 mod common;
 
-use common::generate_tree_for_function;
+use acacia::analysis::{BinaryOp, CheckKind, ConditionExpr, WarningKind};
+use acacia::foundry::{FoundryError, FoundryProject, SourceFilter};
+use acacia::output::{parse_tree, render_scaffold, render_to_string, render_with_lines};
+use acacia::parser::SolarParser;
+use acacia::tree::{LabelConfig, TreeBuilder, TreeNode, TreeOptions};
+use acacia::ConditionContext;
+
+use common::{
+    generate_annotated_tree_with_library, generate_invariants_with_library,
+    generate_tree_for_function, generate_tree_for_function_at_path,
+    generate_tree_for_function_with_signature, generate_tree_with_library,
+    generate_trees_for_all_overloads, generate_trees_for_contract, parse_with_library,
+    test_project, testdata_dir,
+};
+
+#[test]
+fn test_simple_require() {
+    let tree = generate_tree_for_function("SimpleRequire", "transfer");
+
+    // Simple require(amount > 0)
+    let expected = r#"transfer
+├── when amount is at most zero
+│   └── it should revert
+└── when amount is greater than zero
+    └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_with_modifier() {
+    let tree = generate_tree_for_function("WithModifier", "mint");
+
+    // "owner" is a state variable, so msg.sender == owner uses "given"
+    let expected = r#"mint
+├── given msg.sender is not owner
+│   └── it should revert
+└── given msg.sender is owner
+    ├── when amount is at most zero
+    │   └── it should revert
+    └── when amount is greater than zero
+        └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_multiple_requires() {
+    let tree = generate_tree_for_function("MultipleRequires", "withdraw");
+
+    // balances is a state variable, so uses "given"
+    // address(this).balance is an expression that gets simplified
+    let expected = r#"withdraw
+├── when amount is at most zero
+│   └── it should revert
+└── when amount is greater than zero
+    ├── given balances[msg.sender] is less than amount
+    │   └── it should revert
+    └── given balances[msg.sender] is at least amount
+        ├── when expr(...).balance is less than amount
+        │   └── it should revert
+        └── when expr(...).balance is at least amount
+            └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_if_revert() {
+    let tree = generate_tree_for_function("IfRevert", "mint");
+
+    // if (condition) revert pattern - condition being TRUE causes revert
+    // So for: if (amount == 0) revert -> "when amount is zero" causes revert
+    // totalSupply and maxSupply are state variables
+    let expected = r#"mint
+├── when amount is zero
+│   └── it should revert
+└── when amount is not zero
+    ├── given totalSupply + amount is greater than maxSupply
+    │   └── it should revert
+    └── given totalSupply + amount is at most maxSupply
+        └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_if_not_authorized_revert_reads_naturally() {
+    let tree = generate_tree_with_library("IfNotAuthorized", "IfNotAuthorized", "execute");
+
+    let expected = r#"execute
+├── when the caller is not authorized
+│   └── it should revert with Unauthorized
+└── when the caller is authorized
+    └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_custom_error_named_on_revert_leaf() {
+    let tree = generate_tree_with_library("CustomErrors", "CustomErrors", "deposit");
+
+    let expected = r#"deposit
+├── given msg.sender is not owner
+│   └── it should revert with NotOwner
+└── given msg.sender is owner
+    ├── when amount is at most zero
+    │   └── it should revert with InvalidAmount
+    └── when amount is greater than zero
+        ├── given total is at least 1000
+        │   └── it should revert with "Cap reached"
+        └── given total is less than 1000
+            └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_require_with_custom_error_argument() {
+    let function_ctx = parse_with_library("RequireErrors", "RequireErrors", "setOwner");
+
+    // The error argument doesn't leak into the condition
+    let first = &function_ctx.branch_points[0];
+    assert_eq!(
+        first.condition,
+        ConditionExpr::Binary {
+            left: "msg.sender".to_string(),
+            op: BinaryOp::Eq,
+            right: "owner".to_string(),
+        }
+    );
+
+    // Both bare and library-qualified errors are named
+    let reasons: Vec<Option<&str>> = function_ctx
+        .branch_points
+        .iter()
+        .map(|bp| bp.revert_reason.as_deref())
+        .collect();
+    assert_eq!(reasons, vec![Some("Unauthorized"), Some("SameOwner")]);
+}
+
+#[test]
+fn test_revert_message_quoted_on_revert_leaf() {
+    let tree = generate_tree_with_library("RevertMessages", "RevertMessages", "withdraw");
+
+    // The bare require keeps the generic leaf
+    let expected = r#"withdraw
+├── when amount is at most zero
+│   └── it should revert with "amount must be positive"
+└── when amount is greater than zero
+    ├── given amount is greater than balance
+    │   └── it should revert
+    └── given amount is at most balance
+        ├── given the contract is paused
+        │   └── it should revert with "paused"
+        └── given the contract is not paused
+            └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_with_loop() {
+    let tree = generate_tree_for_function("WithLoop", "batchTransfer");
+
+    // Loop conditions should use "any" prefix
+    // address(0) gets parsed as an expression
+    let expected = r#"batchTransfer
+├── when recipients.length is not amounts.length
+│   └── it should revert
+└── when recipients.length is amounts.length
+    ├── when any recipients[i] is expr(...)
+    │   └── it should revert
+    └── when any recipients[i] is not expr(...)
+        ├── when any amounts[i] is at most zero
+        │   └── it should revert
+        └── when any amounts[i] is greater than zero
+            └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_storage_condition() {
+    let tree = generate_tree_for_function("StorageCondition", "doSomething");
+
+    // Storage-based condition should use "given" prefix
+    let expected = r#"doSomething
+├── given the contract is paused
+│   └── it should revert
+└── given the contract is not paused
+    └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_local_copied_from_storage_is_given() {
+    let tree = generate_tree_with_library("StorageLocal", "StorageLocal", "withdraw");
+
+    // `bal` holds `balances[msg.sender]`; `limit` only derives from a parameter
+    let expected = r#"withdraw
+├── given bal is less than amount
+│   └── it should revert with "Insufficient balance"
+└── given bal is at least amount
+    ├── when limit is greater than 1000
+    │   └── it should revert with "Over limit"
+    └── when limit is at most 1000
+        └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_negations_and_parentheses_are_normalized() {
+    let tree = generate_tree_with_library("NegatedChecks", "NegatedChecks", "mint");
+
+    // `!(amount == 0)` reads as `amount != 0`, and `((amount < cap))` as `amount < cap`
+    let expected = r#"mint
+├── when amount is zero
+│   └── it should revert with "Zero amount"
+└── when amount is not zero
+    ├── given amount is at least cap
+    │   └── it should revert with "Over cap"
+    └── given amount is less than cap
+        └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_assembly_revert_guards_become_branches() {
+    let tree = generate_tree_with_library("AssemblyGuard", "AssemblyGuard", "transfer");
+
+    // `if iszero(to) { revert(0, 0) }` reverts when `to` is zero
+    let expected = r#"transfer
+├── when to is zero
+│   └── it should revert
+└── when to is not zero
+    ├── when amount is greater than 100
+    │   └── it should revert
+    └── when amount is at most 100
+        └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_modifier_arguments_replace_parameters() {
+    let tree = generate_tree_with_library("RoleModifier", "RoleModifier", "setFee");
+
+    // `onlyRole(ADMIN)` checks `roles[role][msg.sender]` with `role` bound to `ADMIN`
+    let expected = r#"setFee
+├── given roles[ADMIN][msg.sender] is false
+│   └── it should revert with "Missing role"
+└── given roles[ADMIN][msg.sender] is true
+    ├── when fee is greater than 1000
+    │   └── it should revert with "Fee too high"
+    └── when fee is at most 1000
+        └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_safe_cast_overflow_branch() {
+    let tree = generate_tree_with_library("SafeCastUser", "SafeCastUser", "deposit");
+
+    let expected = r#"deposit
+├── when amount is at most zero
+│   └── it should revert with "Zero amount"
+└── when amount is greater than zero
+    ├── when amount.toUint128 overflows
+    │   └── it should revert
+    └── when amount.toUint128 does not overflow
+        └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_annotated_shows_source_condition() {
+    let tree = generate_annotated_tree_with_library("SimpleRequire", "SimpleRequire", "transfer");
+
+    let expected = r#"transfer
+├── when amount is at most zero       // require(amount > 0)
+│   └── it should revert with "Amount must be positive"
+└── when amount is greater than zero  // require(amount > 0)
+    └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_invariants_list_asserts_only() {
+    let invariants = generate_invariants_with_library("Invariants", "Invariants", "mint");
+
+    // The require guard is not an invariant
+    let expected = r#"mint
+├── invariant: totalSupply == sumOfBalances
+└── invariant: totalSupply >= amount
+"#;
+
+    assert_eq!(invariants, expected);
+}
+
+#[test]
+fn test_loop_counter_check_has_no_any_prefix() {
+    let tree = generate_tree_with_library("LoopCounter", "LoopCounter", "process");
+
+    // `i < maxIterations` bounds the loop itself, so it is not a per-item check
+    assert!(tree.contains("── given i is at least maxIterations\n"));
+    assert!(tree.contains("── given i is less than maxIterations\n"));
+    assert!(!tree.contains("any i "));
+
+    // `amounts[i] > 0` is checked for each element
+    assert!(tree.contains("── when any amounts["));
+}
+
+#[test]
+fn test_calldata_struct_array_field_in_loop() {
+    let tree = generate_tree_with_library("BatchOrders", "BatchOrders", "fillOrders");
+
+    // `orders` is a parameter, so the whole index/member chain is external ("when")
+    let expected = r#"fillOrders
+├── when any orders[i].amount is at most zero
+│   └── it should revert with "Invalid amount"
+└── when any orders[i].amount is greater than zero
+    └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_loop_inside_modifier_has_any_prefix() {
+    let tree = generate_tree_with_library("ModifierLoop", "ModifierLoop", "setValue");
+
+    // The require inside the modifier's loop is checked for each admin
+    assert!(tree.contains("── given any admins["));
+
+    // The require after the loop runs once
+    assert!(tree.contains("── when found is false\n"));
+    assert!(!tree.contains("any found"));
+}
+
+#[test]
+fn test_modifier_checks_after_placeholder_follow_function_body() {
+    let tree = generate_tree_with_library("PostCheckModifier", "PostCheckModifier", "withdraw");
+
+    // `require(balance >= 100)` sits after `_`, so it runs after the function's own check
+    let expected = r#"withdraw
+├── given locked is true
+│   └── it should revert with "Locked"
+└── given locked is false
+    ├── when amount is at most zero
+    │   └── it should revert with "Invalid amount"
+    └── when amount is greater than zero
+        ├── given balance is less than 100
+        │   └── it should revert with "Reserve too low"
+        └── given balance is at least 100
+            └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_guard_repeated_in_modifier_and_body_appears_once() {
+    let tree = generate_tree_with_library("DuplicateGuard", "DuplicateGuard", "setValue");
+
+    let expected = r#"setValue
+├── given msg.sender is not owner
+│   └── it should revert with "Not owner"
+└── given msg.sender is owner
+    ├── when newValue is at most zero
+    │   └── it should revert with "Invalid value"
+    └── when newValue is greater than zero
+        └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_repeated_guard_with_another_message_is_kept() {
+    let tree = generate_tree_with_library("DuplicateGuard", "DuplicateGuard", "setOwner");
+
+    let expected = r#"setOwner
+├── given msg.sender is not owner
+│   └── it should revert with "Not owner"
+└── given msg.sender is owner
+    ├── given msg.sender is not owner
+    │   └── it should revert with "Only the owner"
+    └── given msg.sender is owner
+        └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_arithmetic_and_unit_literals_in_conditions() {
+    let tree = generate_tree_with_library("ArithmeticChecks", "ArithmeticChecks", "deposit");
+
+    let expected = r#"deposit
+├── when amount is less than 1 ether
+│   └── it should revert with "Too small"
+└── when amount is at least 1 ether
+    ├── when fee * 100 is greater than amount
+    │   └── it should revert with "Fee too high"
+    └── when fee * 100 is at most amount
+        ├── given totalDeposits + amount is greater than cap
+        │   └── it should revert with "Cap exceeded"
+        └── given totalDeposits + amount is at most cap
+            └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_nested_mapping_label_keeps_every_key() {
+    let tree = generate_tree_with_library("NestedMapping", "NestedMapping", "transferFrom");
+
+    let expected = r#"transferFrom
+├── given allowance[owner][spender] is less than amount
+│   └── it should revert with "Insufficient allowance"
+└── given allowance[owner][spender] is at least amount
+    └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_ternary_condition_in_require() {
+    let tree = generate_tree_with_library("TernaryGuard", "TernaryGuard", "deposit");
+
+    let expected = r#"deposit
+├── when boosted is false or amount is greater than limit * 2 and when boosted is true or given amount is greater than limit
+│   └── it should revert with "Over limit"
+└── when boosted is true and amount is at most limit * 2 or when boosted is false and given amount is at most limit
+    └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_ternary_with_revert_branch() {
+    let tree = generate_tree_with_library("TernaryGuard", "TernaryGuard", "claim");
+
+    let expected = r#"claim
+├── when early is true
+│   └── it should revert with "Too early"
+└── when early is false
+    └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_early_return_guard_clause() {
+    let tree = generate_tree_with_library("EarlyReturn", "EarlyReturn", "accrue");
+
+    let expected = r#"accrue
+├── when newRate is at most zero
+│   └── it should revert with "Invalid rate"
+└── when newRate is greater than zero
+    ├── given lastUpdate is block.timestamp
+    │   └── it should return early
+    └── given lastUpdate is not block.timestamp
+        └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_early_return_guard_keeps_checks_in_its_body() {
+    let tree = generate_tree_with_library("EarlyReturn", "EarlyReturn", "sync");
+
+    let expected = r#"sync
+├── given lastUpdate is block.timestamp
+│   ├── given newRate is not rate
+│   │   └── it should revert with "Rate already set"
+│   └── given newRate is rate
+│       └── it should return early
+└── given lastUpdate is not block.timestamp
+    └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_loop_continue_and_break_guards() {
+    let tree = generate_tree_with_library("LoopSkip", "LoopSkip", "airdrop");
+
+    let expected = r#"airdrop
+├── when i is at least limit
+│   └── it should stop early
+└── when i is less than limit
+    ├── when any amounts[i] is zero
+    │   └── it should skip this item
+    └── when any amounts[i] is not zero
+        ├── given any balances[recipients[i]] is not zero
+        │   └── it should revert with "Already funded"
+        └── given any balances[recipients[i]] is zero
+            └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_unchecked_arithmetic_is_annotated() {
+    let tree = generate_tree_with_library("UncheckedMath", "UncheckedMath", "add");
+
+    let expected = r#"add
+├── given total + amount is greater than cap
+│   └── it should revert with "Over cap"
+└── given total + amount is at most cap
+    ├── given total - amount is at least cap (unchecked)
+    │   └── it should revert with "Wrapped"
+    └── given total - amount is less than cap (unchecked)
+        └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_checked_addition_gets_overflow_branch() {
+    let project = test_project();
+    let path = testdata_dir().join("CheckedMath.sol");
+
+    // Off by default
+    let ctx = SolarParser::new(&project)
+        .parse_function(&path, "CheckedMath", "mint")
+        .unwrap();
+    assert_eq!(ctx.branch_points.len(), 1);
+
+    let ctx = SolarParser::new(&project)
+        .with_arithmetic_overflow(true)
+        .parse_function(&path, "CheckedMath", "mint")
+        .unwrap();
+    let tree = TreeBuilder::build("mint", ctx.branch_points).unwrap();
+
+    // The addition inside `unchecked` wraps instead, so it gets no branch
+    let expected = r#"mint
+├── when totalSupply + amount overflows
+│   └── it should revert
+└── when totalSupply + amount does not overflow
+    ├── given newSupply is greater than cap
+    │   └── it should revert with "Over cap"
+    └── given newSupply is at most cap
+        └── it should succeed
+"#;
+
+    assert_eq!(render_to_string(&tree), expected);
+}
+
+#[test]
+fn test_internal_helper_guards_are_inlined() {
+    let project = test_project();
+    let path = testdata_dir().join("HelperGuard.sol");
+
+    // Without inlining, the guards in the helpers are invisible
+    let ctx = SolarParser::new(&project)
+        .parse_function(&path, "HelperGuard", "deposit")
+        .unwrap();
+    assert!(ctx.branch_points.is_empty());
+
+    let ctx = SolarParser::new(&project)
+        .with_inline_helpers(true)
+        .parse_function(&path, "HelperGuard", "deposit")
+        .unwrap();
+    let tree = TreeBuilder::build("deposit", ctx.branch_points).unwrap();
+
+    let expected = r#"deposit
+├── given the contract is paused
+│   └── it should revert with "Paused"
+└── given the contract is not paused
+    ├── when value is at most zero
+    │   └── it should revert with "Zero amount"
+    └── when value is greater than zero
+        └── it should succeed
+"#;
+
+    assert_eq!(render_to_string(&tree), expected);
+}
+
+#[test]
+fn test_helper_guard_clause_forks_the_caller() {
+    let project = test_project();
+    let ctx = SolarParser::new(&project)
+        .with_inline_helpers(true)
+        .parse_function(
+            &testdata_dir().join("HelperGuard.sol"),
+            "HelperGuard",
+            "withdraw",
+        )
+        .unwrap();
+    let tree = TreeBuilder::build("withdraw", ctx.branch_points).unwrap();
+
+    // The helper's `return` skips its own later check, not the caller's
+    let expected = r#"withdraw
+├── when value is zero
+│   ├── given balances[msg.sender] is less than value
+│   │   └── it should revert with "Insufficient balance"
+│   └── given balances[msg.sender] is at least value
+│       └── it should succeed
+└── when value is not zero
+    ├── when value is at most 10
+    │   └── it should revert with "Too small"
+    └── when value is greater than 10
+        ├── given balances[msg.sender] is less than value
+        │   └── it should revert with "Insufficient balance"
+        └── given balances[msg.sender] is at least value
+            └── it should succeed
+"#;
+
+    assert_eq!(render_to_string(&tree), expected);
+}
+
+#[test]
+fn test_contract_typed_members_are_external() {
+    let ctx = parse_with_library("ContractTypedState", "VaultRouter", "route");
+
+    let contexts: Vec<(String, ConditionContext)> = ctx
+        .branch_points
+        .iter()
+        .map(|bp| (bp.condition.to_string(), bp.context.clone()))
+        .collect();
+
+    // `this.paused()` is the router's own flag, `otherVault.paused()` the vault's,
+    // and a struct member is still this contract's storage
+    assert_eq!(
+        contexts,
+        vec![
+            ("!(this.paused())".to_string(), ConditionContext::Storage),
+            (
+                "!(otherVault.paused())".to_string(),
+                ConditionContext::External
+            ),
+            (
+                "lastRoute.amount < amount".to_string(),
+                ConditionContext::Storage
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_struct_fields_follow_their_base_variable() {
+    let contexts = |function: &str| -> Vec<(String, ConditionContext)> {
+        parse_with_library("StructFields", "OrderBook", function)
+            .branch_points
+            .iter()
+            .map(|bp| (bp.condition.to_string(), bp.context.clone()))
+            .collect()
+    };
+
+    // A field of a struct in a state mapping is storage
+    assert_eq!(
+        contexts("fill"),
+        vec![
+            (
+                "orders[id].filled == false".to_string(),
+                ConditionContext::Storage
+            ),
+            (
+                "orders[id].amount > 0".to_string(),
+                ConditionContext::Storage
+            ),
+        ]
+    );
+    // The memory parameter shadows the `order` state variable
+    assert_eq!(
+        contexts("submit"),
+        vec![
+            (
+                "order.filled == false".to_string(),
+                ConditionContext::External
+            ),
+            ("order.amount > 0".to_string(), ConditionContext::External),
+        ]
+    );
+}
+
+#[test]
+fn test_try_success_block_checks_and_rethrowing_catch() {
+    let tree = generate_tree_with_library("TryCatch", "TryCatch", "update");
+
+    let expected = r#"update
+├── when oracle.price() fails
+│   └── it should revert with "Oracle failed"
+└── when oracle.price() succeeds
+    ├── given p is less than minPrice
+    │   └── it should revert with "Price too low"
+    └── given p is at least minPrice
+        └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_try_with_handling_catch_continues_on_both_sides() {
+    let tree = generate_tree_with_library("TryCatch", "TryCatch", "updateOrKeep");
+
+    let expected = r#"updateOrKeep
+├── when oracle.price() fails
+│   ├── when fallbackPrice is at most zero
+│   │   └── it should revert with "No fallback"
+│   └── when fallbackPrice is greater than zero
+│       └── it should succeed
+└── when oracle.price() succeeds
+    └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_initializer_modifier_reads_as_one_shot_guard() {
+    let tree = generate_tree_with_library("InitializerGuard", "InitializerGuard", "initialize");
+
+    let expected = r#"initialize
+├── given the contract is already initialized
+│   └── it should revert with "Already initialized"
+└── given the contract is not initialized
+    ├── when owner_ is zero address
+    │   └── it should revert with "Zero owner"
+    └── when owner_ is not zero address
+        └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_revert_position_orders_sibling_branches() {
+    let render = |revert_last: bool| {
+        let ctx = parse_with_library("SimpleRequire", "SimpleRequire", "transfer");
+        let options = TreeOptions {
+            revert_last,
+            ..TreeOptions::default()
+        };
+        let tree =
+            TreeBuilder::build_with_options("transfer", ctx.branch_points, &options).unwrap();
+        render_to_string(&tree)
+    };
+
+    assert_eq!(
+        render(false),
+        r#"transfer
+├── when amount is at most zero
+│   └── it should revert with "Amount must be positive"
+└── when amount is greater than zero
+    └── it should succeed
+"#
+    );
+    assert_eq!(
+        render(true),
+        r#"transfer
+├── when amount is greater than zero
+│   └── it should succeed
+└── when amount is at most zero
+    └── it should revert with "Amount must be positive"
+"#
+    );
+}
+
+#[test]
+fn test_custom_btt_natspec_hints() {
+    let ctx = parse_with_library("BttHints", "BttHints", "deposit");
+
+    assert_eq!(
+        ctx.hints,
+        vec!["given the vault is empty", "given the vault has assets"]
+    );
+}
+
+#[test]
+fn test_custom_leaf_labels() {
+    let ctx = parse_with_library("EarlyReturn", "EarlyReturn", "accrue");
+
+    let options = TreeOptions {
+        labels: LabelConfig {
+            revert: "it reverts".to_string(),
+            success: "it works".to_string(),
+            early_return: "it does nothing".to_string(),
+        },
+        ..TreeOptions::default()
+    };
+    let tree = TreeBuilder::build_with_options("accrue", ctx.branch_points, &options).unwrap();
+
+    let expected = r#"accrue
+├── when newRate is at most zero
+│   └── it reverts with "Invalid rate"
+└── when newRate is greater than zero
+    ├── given lastUpdate is block.timestamp
+    │   └── it does nothing
+    └── given lastUpdate is not block.timestamp
+        └── it works
+"#;
+
+    assert_eq!(render_to_string(&tree), expected);
+}
+
+#[test]
+fn test_max_depth_summarizes_remaining_checks() {
+    let ctx = parse_with_library("ManyChecks", "ManyChecks", "configure");
+    assert_eq!(ctx.branch_points.len(), 5);
+
+    let options = TreeOptions {
+        max_depth: Some(2),
+        ..TreeOptions::default()
+    };
+    let tree = TreeBuilder::build_with_options("configure", ctx.branch_points, &options).unwrap();
+
+    let expected = r#"configure
+├── given the contract is paused
+│   └── it should revert with "Paused"
+└── given the contract is not paused
+    ├── when fee is greater than 1000
+    │   └── it should revert with "Fee too high"
+    └── when fee is at most 1000
+        └── it should handle remaining 3 conditions
+"#;
+
+    assert_eq!(render_to_string(&tree), expected);
+}
+
+#[test]
+fn test_if_else_arms_fork_the_tree() {
+    let tree = generate_tree_with_library("IfElseArms", "IfElseArms", "fund");
+
+    // The trailing require follows both arms
+    let expected = r#"fund
+├── when useVault is true
+│   ├── given vaultBalance is less than amount
+│   │   └── it should revert with "Vault too low"
+│   └── given vaultBalance is at least amount
+│       ├── given amount is greater than limit
+│       │   └── it should revert with "Over limit"
+│       └── given amount is at most limit
+│           └── it should succeed
+└── when useVault is false
+    ├── when the payment is below the amount
+    │   └── it should revert with "Not enough ETH"
+    └── when the payment is at least the amount
+        ├── given amount is greater than limit
+        │   └── it should revert with "Over limit"
+        └── given amount is at most limit
+            └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_stable_order_ignores_order_of_independent_checks() {
+    let render = |function: &str, stable_order: bool| {
+        let ctx = parse_with_library("StableOrder", "StableOrder", function);
+        let options = TreeOptions {
+            stable_order,
+            ..TreeOptions::default()
+        };
+        let tree = TreeBuilder::build_with_options("send", ctx.branch_points, &options).unwrap();
+        render_to_string(&tree)
+    };
+
+    assert_ne!(
+        render("recipientFirst", false),
+        render("amountFirst", false)
+    );
+    assert_eq!(render("recipientFirst", true), render("amountFirst", true));
+
+    // `balances[to]` reads `to`, so it stays after the check on `to`
+    assert_eq!(render("dependent", true), render("dependent", false));
+}
+
+#[test]
+fn test_split_logical_gives_each_part_a_branch() {
+    let ctx = parse_with_library("LogicalChecks", "LogicalChecks", "deposit");
+    let render = |options: &TreeOptions| {
+        let tree =
+            TreeBuilder::build_with_options("deposit", ctx.branch_points.clone(), options).unwrap();
+        render_to_string(&tree)
+    };
+
+    let combined = r#"deposit
+├── given locked is true or frozen is true
+│   └── it should revert with Halted
+└── given locked is false and frozen is false
+    ├── given amount is out of range
+    │   └── it should revert with "Invalid amount"
+    └── given amount is within (0, maxAmount]
+        └── it should succeed
+"#;
+    assert_eq!(render(&TreeOptions::default()), combined);
+
+    let split = r#"deposit
+├── given locked is true
+│   └── it should revert with Halted
+└── given locked is false
+    ├── given frozen is true
+    │   └── it should revert with Halted
+    └── given frozen is false
+        ├── given amount is at most zero
+        │   └── it should revert with "Invalid amount"
+        └── given amount is greater than zero
+            ├── given amount is greater than maxAmount
+            │   └── it should revert with "Invalid amount"
+            └── given amount is at most maxAmount
+                └── it should succeed
+"#;
+    let options = TreeOptions {
+        split_logical: true,
+        ..TreeOptions::default()
+    };
+    assert_eq!(render(&options), split);
+}
 
 #[test]
-fn test_simple_require() {
-    let tree = generate_tree_for_function("SimpleRequire", "transfer");
+fn test_else_revert_guards_then_branch() {
+    // The else-branch reverts, so `amount <= cap` reads like `require(amount <= cap)`
+    let tree = generate_tree_with_library("ElseRevert", "ElseRevert", "join");
 
-    // Simple require(amount > 0)
-    let expected = r#"transfer
-├── when amount is at most zero
-│   └── it should revert
-└── when amount is greater than zero
-    └── it should succeed
+    let expected = r#"join
+├── given amount is greater than cap
+│   └── it should revert with OverCap
+└── given amount is at most cap
+    ├── when amount is at most zero
+    │   └── it should revert with "Zero amount"
+    └── when amount is greater than zero
+        └── it should succeed
 "#;
 
     assert_eq!(tree, expected);
+
+    // When both arms revert, the condition is still a single check
+    let ctx = parse_with_library("ElseRevert", "ElseRevert", "settle");
+    assert_eq!(ctx.branch_points.len(), 1);
+    assert_eq!(
+        ctx.branch_points[0].revert_message.as_deref(),
+        Some("Too early")
+    );
 }
 
 #[test]
-fn test_with_modifier() {
-    let tree = generate_tree_for_function("WithModifier", "mint");
+fn test_audit_json_lists_lines_of_each_check() {
+    let ctx = parse_with_library("ElseRevert", "ElseRevert", "join");
 
-    // "owner" is a state variable, so msg.sender == owner uses "given"
-    let expected = r#"mint
-├── given msg.sender is not owner
-│   └── it should revert
-└── given msg.sender is owner
-    ├── when amount is at most zero
-    │   └── it should revert
-    └── when amount is greater than zero
+    // `if (amount <= cap)` is on line 12 and the `require` inside it on line 13
+    let lines: Vec<Option<usize>> = ctx
+        .branch_points
+        .iter()
+        .map(|bp| bp.span.map(|(line, _)| line))
+        .collect();
+    assert_eq!(lines, vec![Some(12), Some(13)]);
+
+    let json = acacia::output::render_audit_json(&[("join".to_string(), &ctx.branch_points)]);
+    let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let checks = parsed[0]["checks"].as_array().unwrap();
+    assert_eq!(parsed[0]["function"], "join");
+    assert_eq!(checks.len(), 2);
+    assert_eq!(checks[0]["condition"], "amount <= cap");
+    assert_eq!(checks[0]["context"], "storage");
+    assert_eq!(checks[0]["line"], 12);
+    assert_eq!(checks[1]["condition"], "amount > 0");
+    assert_eq!(checks[1]["context"], "external");
+    assert_eq!(checks[1]["is_if_revert"], false);
+    assert_eq!(checks[1]["line"], 13);
+}
+
+#[test]
+fn test_with_lines_annotates_each_condition() {
+    let ctx = parse_with_library("ElseRevert", "ElseRevert", "join");
+    let tree = TreeBuilder::build("join", ctx.branch_points).unwrap();
+
+    let expected = r#"join
+├── given amount is greater than cap      // L12
+│   └── it should revert with OverCap
+└── given amount is at most cap           // L12
+    ├── when amount is at most zero       // L13
+    │   └── it should revert with "Zero amount"
+    └── when amount is greater than zero  // L13
         └── it should succeed
 "#;
 
+    assert_eq!(render_with_lines(&tree, false), expected);
+}
+
+#[test]
+fn test_free_functions_get_trees_of_their_own() {
+    let project = test_project();
+    let functions = SolarParser::new(&project)
+        .parse_free_functions(&testdata_dir().join("FreeFunctions.sol"))
+        .unwrap();
+
+    // `sharesFor` belongs to the contract in the same file, not to the free functions
+    let names: Vec<&str> = functions.iter().map(|f| f.function_name.as_str()).collect();
+    assert_eq!(names, vec!["ceilDiv", "checkShares"]);
+
+    let ctx = functions.into_iter().next().unwrap();
+    let tree = TreeBuilder::build(&ctx.function_name, ctx.branch_points).unwrap();
+
+    let expected = r#"ceilDiv
+├── when b is at most zero
+│   └── it should revert with "Zero divisor"
+└── when b is greater than zero
+    └── it should succeed
+"#;
+
+    assert_eq!(render_to_string(&tree), expected);
+}
+
+#[test]
+fn test_assert_false_marks_unreachable_branch() {
+    // The last `else` can't be reached for valid input, so it isn't split on `false`
+    let tree = generate_tree_with_library("Unreachable", "Unreachable", "execute");
+
+    let expected = r#"execute
+├── when kind is zero
+│   └── it should succeed
+└── when kind is not zero
+    ├── when kind is 1
+    │   ├── given total is less than amount
+    │   │   └── it should revert with "Insufficient"
+    │   └── given total is at least amount
+    │       └── it should succeed
+    └── when kind is not 1
+        └── it should never reach here
+"#;
+
     assert_eq!(tree, expected);
 }
 
 #[test]
-fn test_multiple_requires() {
-    let tree = generate_tree_for_function("MultipleRequires", "withdraw");
+fn test_revert_unreachable_marks_unreachable_branch() {
+    let tree = generate_tree_with_library("Unreachable", "Unreachable", "settle");
 
-    // balances is a state variable, so uses "given"
-    // address(this).balance is an expression that gets simplified
-    let expected = r#"withdraw
-├── when amount is at most zero
-│   └── it should revert
-└── when amount is greater than zero
-    ├── given balances[...] is less than amount
-    │   └── it should revert
-    └── given balances[...] is at least amount
-        ├── when expr(...).balance is less than amount
-        │   └── it should revert
-        └── when expr(...).balance is at least amount
-            └── it should succeed
+    let expected = r#"settle
+├── when kind is zero
+│   └── it should succeed
+└── when kind is not zero
+    └── it should never reach here
 "#;
 
     assert_eq!(tree, expected);
 }
 
 #[test]
-fn test_if_revert() {
-    let tree = generate_tree_for_function("IfRevert", "mint");
+fn test_require_false_keeps_its_message() {
+    // Only `assert(false)` and `revert("unreachable")` mark code that can't run
+    let ctx = parse_with_library("Unreachable", "Unreachable", "retire");
 
-    // if (condition) revert pattern - condition being TRUE causes revert
-    // So for: if (amount == 0) revert -> "when amount is zero" causes revert
-    // totalSupply and maxSupply are state variables
-    let expected = r#"mint
-├── when amount is zero
-│   └── it should revert
-└── when amount is not zero
-    ├── given totalSupply Add amount is greater than maxSupply
-    │   └── it should revert
-    └── given totalSupply Add amount is at most maxSupply
-        └── it should succeed
+    assert_eq!(ctx.branch_points.len(), 1);
+    assert_eq!(ctx.branch_points[0].kind, CheckKind::Require);
+    assert_eq!(
+        ctx.branch_points[0].revert_message.as_deref(),
+        Some("Retired")
+    );
+}
+
+#[test]
+fn test_resolve_constants_labels_constant_values() {
+    let project = test_project();
+    let path = testdata_dir().join("ConstantLimits.sol");
+    let tree = |function, resolve| {
+        let ctx = SolarParser::new(&project)
+            .with_resolved_constants(resolve)
+            .parse_function(&path, "ConstantLimits", function)
+            .unwrap();
+        render_to_string(&TreeBuilder::build(function, ctx.branch_points).unwrap())
+    };
+
+    // By default the constant is named, and counts as contract state
+    let named = r#"deposit
+├── given amount is greater than MAX_DEPOSIT
+│   └── it should revert with "Too much"
+└── given amount is at most MAX_DEPOSIT
+    └── it should succeed
+"#;
+    assert_eq!(tree("deposit", false), named);
+
+    // Resolved, the guard only depends on the argument
+    let resolved = r#"deposit
+├── when amount is greater than 100
+│   └── it should revert with "Too much"
+└── when amount is at most 100
+    └── it should succeed
+"#;
+    assert_eq!(tree("deposit", true), resolved);
+
+    // Constants declared at file level or in a base contract resolve too
+    let resolved = r#"withdraw
+├── when amount is less than 10
+│   └── it should revert with "Too little"
+└── when amount is at least 10
+    └── it should succeed
+"#;
+    assert_eq!(tree("withdraw", true), resolved);
+
+    let resolved = r#"setFee
+├── when newFee is greater than 500
+│   └── it should revert with "Fee too high"
+└── when newFee is at most 500
+    └── it should succeed
+"#;
+    assert_eq!(tree("setFee", true), resolved);
+}
+
+#[test]
+fn test_mixed_conjunction_prefixes_each_side() {
+    // `paused` is storage and `amount` a parameter, so each side keeps its own prefix
+    let tree = generate_tree_with_library("MixedChecks", "MixedChecks", "deposit");
+
+    let expected = r#"deposit
+├── given the contract is paused or when amount is at most zero
+│   └── it should revert with "Unavailable"
+└── given the contract is not paused and when amount is greater than zero
+    └── it should succeed
 "#;
 
     assert_eq!(tree, expected);
 }
 
 #[test]
-fn test_with_loop() {
-    let tree = generate_tree_for_function("WithLoop", "batchTransfer");
+fn test_assert_leaf_panics_and_require_leaf_reverts() {
+    let tree = generate_tree_with_library("Invariants", "Invariants", "mint");
 
-    // Loop conditions should use "any" prefix
-    // address(0) gets parsed as an expression
-    let expected = r#"batchTransfer
-├── when recipients.length is not amounts.length
-│   └── it should revert
-└── when recipients.length is amounts.length
-    ├── when any recipients[...] is expr(...)
-    │   └── it should revert
-    └── when any recipients[...] is not expr(...)
-        ├── when any amounts[...] is at most zero
-        │   └── it should revert
-        └── when any amounts[...] is greater than zero
+    let expected = r#"mint
+├── when amount is at most zero
+│   └── it should revert with "Invalid amount"
+└── when amount is greater than zero
+    ├── given totalSupply is not sumOfBalances
+    │   └── it should panic
+    └── given totalSupply is sumOfBalances
+        ├── given totalSupply is less than amount
+        │   └── it should panic
+        └── given totalSupply is at least amount
             └── it should succeed
 "#;
 
@@ -102,17 +1173,26 @@ fn test_with_loop() {
 }
 
 #[test]
-fn test_storage_condition() {
-    let tree = generate_tree_for_function("StorageCondition", "doSomething");
+fn test_enum_members_drop_type_name() {
+    let tree = generate_tree_with_library("EnumStateMachine", "EnumStateMachine", "bid");
+    let expected = r#"bid
+├── given state is not Active
+│   └── it should revert with "Not active"
+└── given state is Active
+    ├── when the payment is at most the highestBid
+    │   └── it should revert with "Bid too low"
+    └── when the payment is above the highestBid
+        └── it should succeed
+"#;
+    assert_eq!(tree, expected);
 
-    // Storage-based condition should use "given" prefix
-    let expected = r#"doSomething
-├── given paused is true
-│   └── it should revert
-└── given paused is false
+    let tree = generate_tree_with_library("EnumStateMachine", "EnumStateMachine", "settle");
+    let expected = r#"settle
+├── given state is not Ended
+│   └── it should revert with "Not ended"
+└── given state is Ended
     └── it should succeed
 "#;
-
     assert_eq!(tree, expected);
 }
 
@@ -120,8 +1200,6 @@ fn test_storage_condition() {
 
 #[test]
 fn test_overload_specific_signature_one_param() {
-    use common::generate_tree_for_function_with_signature;
-
     // Test transfer(address) overload
     let tree =
         generate_tree_for_function_with_signature("FunctionOverloading", "transfer", "address");
@@ -138,8 +1216,6 @@ fn test_overload_specific_signature_one_param() {
 
 #[test]
 fn test_overload_specific_signature_two_params() {
-    use common::generate_tree_for_function_with_signature;
-
     // Test transfer(address, uint256) overload
     let tree = generate_tree_for_function_with_signature(
         "FunctionOverloading",
@@ -162,8 +1238,6 @@ fn test_overload_specific_signature_two_params() {
 
 #[test]
 fn test_overload_specific_signature_three_params() {
-    use common::generate_tree_for_function_with_signature;
-
     // Test transfer(address, uint256, bytes) overload
     let tree = generate_tree_for_function_with_signature(
         "FunctionOverloading",
@@ -189,8 +1263,6 @@ fn test_overload_specific_signature_three_params() {
 
 #[test]
 fn test_generate_all_overloads() {
-    use common::generate_trees_for_all_overloads;
-
     // Generate trees for all transfer overloads
     let trees = generate_trees_for_all_overloads("FunctionOverloading", "transfer");
 
@@ -209,12 +1281,31 @@ fn test_generate_all_overloads() {
     }
 }
 
+#[test]
+fn test_repeated_overload_parses_are_identical() {
+    let project = test_project();
+    let parser = SolarParser::new(&project);
+    let file = testdata_dir().join("FunctionOverloading.sol");
+
+    let first = parser
+        .parse_all_functions(&file, "FunctionOverloading", "transfer")
+        .unwrap();
+    let second = parser
+        .parse_all_functions(&file, "FunctionOverloading", "transfer")
+        .unwrap();
+    let by_signature = parser
+        .parse_function_by_signature(&file, "FunctionOverloading", "transfer", "address")
+        .unwrap();
+
+    assert_eq!(first, second);
+    assert!(first.len() > 1);
+    assert!(first.contains(&by_signature));
+}
+
 // ============= Contract-Wide Generation Tests =============
 
 #[test]
 fn test_generate_all_public_functions() {
-    use common::generate_trees_for_contract;
-
     // Generate trees for all public/external functions in AllFunctions contract
     let trees = generate_trees_for_contract("AllFunctions");
 
@@ -240,8 +1331,6 @@ fn test_generate_all_public_functions() {
 /// in separate files are NOT resolved.
 #[test]
 fn test_inheritance_modifier_from_parent_contract() {
-    use common::generate_tree_for_function_at_path;
-
     // ChildContract inherits from Ownable (in a separate file)
     // setValue uses onlyOwner modifier from Ownable
     let tree = generate_tree_for_function_at_path(
@@ -278,3 +1367,393 @@ fn test_inheritance_modifier_from_parent_contract() {
         "Current behavior: parent modifiers are not resolved"
     );
 }
+
+#[test]
+fn test_inheritance_through_named_and_aliased_imports() {
+    // Ownable comes from `import {Ownable} from ...`, Pausable from `import {Pausable as PausableBase} from ...`
+    let tree = generate_tree_with_library("inheritance/NamedImports", "NamedImports", "setValue");
+
+    let expected = r#"setValue
+├── given msg.sender is not owner
+│   └── it should revert with "Not owner"
+└── given msg.sender is owner
+    ├── given the contract is paused
+    │   └── it should revert with "Paused"
+    └── given the contract is not paused
+        ├── when newValue is at most zero
+        │   └── it should revert with "Value must be positive"
+        └── when newValue is greater than zero
+            └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_unresolved_modifier_is_reported_as_warning() {
+    let function_ctx = parse_with_library("UnresolvedModifier", "UnresolvedModifier", "setValue");
+
+    // The function body is still analyzed
+    assert_eq!(function_ctx.branch_points.len(), 1);
+    assert_eq!(function_ctx.warnings.len(), 1);
+
+    let warning = &function_ctx.warnings[0];
+    assert_eq!(
+        warning.kind,
+        WarningKind::ModifierUnresolved {
+            modifier: "onlyRole".to_string(),
+            function: "setValue".to_string(),
+        }
+    );
+    assert!(warning.file.ends_with("UnresolvedModifier.sol"));
+    assert_eq!(warning.line, Some(8));
+}
+
+#[test]
+fn test_contract_trees_carry_parser_warnings() {
+    let contract = acacia::generate_trees_for_contract(&test_project(), "UnresolvedModifier")
+        .expect("Failed to generate trees");
+
+    assert_eq!(contract.trees.len(), 1);
+    assert_eq!(contract.warnings.len(), 1);
+    assert!(matches!(
+        contract.warnings[0].kind,
+        WarningKind::ModifierUnresolved { .. }
+    ));
+}
+
+#[test]
+fn test_parse_function_inlines_modifier_from_base_file() {
+    let project = test_project();
+    let parser = SolarParser::new(&project);
+    let file_path = testdata_dir().join("inheritance/Child.sol");
+
+    // `onlyOwner` is defined in Base.sol, which Child.sol imports
+    let function_ctx = parser
+        .parse_function(&file_path, "Child", "setFee")
+        .expect("Failed to parse function");
+    let tree =
+        TreeBuilder::build("setFee", function_ctx.branch_points).expect("Failed to build tree");
+    let rendered = render_to_string(&tree);
+
+    assert!(rendered.starts_with(
+        "setFee\n├── given msg.sender is not owner\n│   └── it should revert with \"Not owner\"\n"
+    ));
+    assert!(function_ctx.warnings.is_empty());
+
+    // Every overload picks up the inherited modifier too
+    let overloads = parser
+        .parse_all_functions(&file_path, "Child", "setFee")
+        .expect("Failed to parse functions");
+    assert_eq!(overloads.len(), 2);
+    for ctx in overloads {
+        let tree = TreeBuilder::build("setFee", ctx.branch_points).expect("Failed to build tree");
+        assert!(render_to_string(&tree).contains("── given msg.sender is not owner\n"));
+    }
+}
+
+// ============= Project Discovery Tests =============
+
+#[test]
+fn test_foundry_profile_selects_src_dir() {
+    let root = common::testdata_dir().join("profiles");
+
+    let default = FoundryProject::load(root.clone(), None).unwrap();
+    assert_eq!(default.src_dir, root.join("src"));
+
+    // `ci` only overrides `src`; remappings still come from `default`
+    let ci = FoundryProject::load(root.clone(), Some("ci")).unwrap();
+    assert_eq!(ci.src_dir, root.join("contracts"));
+    assert_eq!(
+        ci.remappings,
+        vec![(
+            "@oz/".to_string(),
+            "lib/openzeppelin-contracts/".to_string()
+        )]
+    );
+
+    // Unknown profiles fall back to `default`
+    let unknown = FoundryProject::load(root.clone(), Some("missing")).unwrap();
+    assert_eq!(unknown.src_dir, root.join("src"));
+}
+
+#[test]
+fn test_remappings_txt_resolves_imports() {
+    let root = common::testdata_dir().join("remapped");
+    let project = FoundryProject::load(root.clone(), None).unwrap();
+    let from = root.join("src/Vault.sol");
+
+    assert_eq!(
+        project.resolve_import("@openzeppelin/access/Ownable.sol", &from),
+        Some(root.join("lib/openzeppelin-contracts/access/Ownable.sol"))
+    );
+    // foundry.toml's `solmate/` mapping wins over the one in remappings.txt
+    assert_eq!(
+        project.resolve_import("solmate/Auth.sol", &from),
+        Some(root.join("lib/solmate/Auth.sol"))
+    );
+}
+
+#[test]
+fn test_plain_directory_without_foundry_toml() {
+    let root = std::env::temp_dir().join(format!("acacia-plain-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(
+        root.join("Counter.sol"),
+        r#"// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+
+contract Counter {
+    uint256 public count;
+
+    function add(uint256 amount) external {
+        require(amount > 0, "Zero amount");
+        count += amount;
+    }
+}
+"#,
+    )
+    .unwrap();
+
+    // No src/ or contracts/, so sources are read from the root itself
+    let project = FoundryProject::plain(root.clone());
+    assert_eq!(project.src_dir, root);
+
+    let file = project
+        .find_contract("Counter", &SourceFilter::default())
+        .unwrap();
+    let ctx = SolarParser::new(&project)
+        .parse_function(&file, "Counter", "add")
+        .unwrap();
+    let tree = TreeBuilder::build("add", ctx.branch_points).unwrap();
+
+    let expected = r#"add
+├── when amount is at most zero
+│   └── it should revert with "Zero amount"
+└── when amount is greater than zero
+    └── it should succeed
+"#;
+    assert_eq!(render_to_string(&tree), expected);
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+fn ambiguous_project() -> acacia::foundry::FoundryProject {
+    let dir = common::testdata_dir().join("ambiguous");
+    acacia::foundry::FoundryProject::new(dir.clone(), dir, vec![], vec![])
+}
+
+#[test]
+fn test_find_contract_lists_every_definition_when_ambiguous() {
+    let project = ambiguous_project();
+
+    let err = project
+        .find_contract("Vault", &SourceFilter::default())
+        .unwrap_err();
+
+    // VaultFactory in Factory.sol is not a definition of Vault
+    let dir = common::testdata_dir().join("ambiguous");
+    match &err {
+        FoundryError::AmbiguousContract { name, candidates } => {
+            assert_eq!(name, "Vault");
+            assert_eq!(
+                candidates,
+                &vec![dir.join("legacy/Vaults.sol"), dir.join("v2/Upgraded.sol")]
+            );
+        }
+        other => panic!("expected an ambiguity error, got {:?}", other),
+    }
+
+    let message = err.to_string();
+    assert!(message.contains("legacy/Vaults.sol"));
+    assert!(message.contains("v2/Upgraded.sol"));
+}
+
+#[test]
+fn test_find_contract_prefers_file_named_after_contract() {
+    let project = ambiguous_project();
+
+    let path = project
+        .find_contract("Registry", &SourceFilter::default())
+        .unwrap();
+
+    assert_eq!(path, common::testdata_dir().join("ambiguous/Registry.sol"));
+}
+
+#[test]
+fn test_overridden_modifier_uses_most_derived_definition() {
+    // `onlyOwner` is `virtual` in Guarded and overridden in AdminGuarded
+    let tree = generate_tree_with_library("inheritance/OverrideGuard", "Treasury", "sweep");
+
+    let expected = r#"sweep
+├── given msg.sender is not admin
+│   └── it should revert with "Not admin"
+└── given msg.sender is admin
+    ├── when amount is zero
+    │   └── it should revert with "Zero amount"
+    └── when amount is not zero
+        └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_inherited_state_variable_uses_given() {
+    // `paused` is declared in Pausable, not in PausableVault itself
+    let tree = generate_tree_with_library("inheritance/PausableVault", "PausableVault", "deposit");
+
+    let expected = r#"deposit
+├── given the contract is paused
+│   └── it should revert with "Paused"
+└── given the contract is not paused
+    └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+#[test]
+fn test_inherited_when_not_paused_reads_as_paused_contract() {
+    // `whenNotPaused` comes from Pausable
+    let tree = generate_tree_with_library("inheritance/PausableVault", "PausableVault", "withdraw");
+
+    let expected = r#"withdraw
+├── given the contract is paused
+│   └── it should revert with "Paused"
+└── given the contract is not paused
+    └── it should succeed
+"#;
+
+    assert_eq!(tree, expected);
+}
+
+// ============= Coverage Tests =============
+
+#[test]
+fn test_coverage_reports_untested_leaves() {
+    let project = test_project();
+    let parser = SolarParser::new(&project);
+
+    let function_ctx = parser
+        .parse_function(&testdata_dir().join("IfRevert.sol"), "IfRevert", "mint")
+        .expect("Failed to parse function");
+    let tree =
+        TreeBuilder::build("mint", function_ctx.branch_points).expect("Failed to build tree");
+
+    let test_names = parser
+        .get_function_names(&testdata_dir().join("test/IfRevert.t.sol"))
+        .expect("Failed to parse test file");
+
+    let report = acacia::coverage::check_coverage(&[tree], &test_names);
+
+    assert_eq!(report.total, 3);
+    assert_eq!(report.covered(), 2);
+    assert_eq!(
+        report.gaps,
+        vec![
+            "mint: when amount is not zero, given totalSupply + amount is greater than maxSupply, it should revert with MaxSupplyReached"
+        ]
+    );
+}
+
+// ============= Round-Trip Tests =============
+
+/// The tree as `render_to_string` shows it, without the sources and lines it leaves out
+fn without_sources(node: acacia::tree::TreeNode) -> acacia::tree::TreeNode {
+    match node {
+        TreeNode::Root { name, children } => TreeNode::Root {
+            name,
+            children: children.into_iter().map(without_sources).collect(),
+        },
+        TreeNode::Branch {
+            label, children, ..
+        } => TreeNode::Branch {
+            label,
+            source: None,
+            line: None,
+            children: children.into_iter().map(without_sources).collect(),
+        },
+        leaf => leaf,
+    }
+}
+
+#[test]
+fn test_parse_tree_inverts_rendering_for_every_testdata_function() {
+    let project = test_project();
+    let parser = SolarParser::new(&project);
+
+    let mut checked = 0;
+    for entry in std::fs::read_dir(testdata_dir()).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|ext| ext != "sol") {
+            continue;
+        }
+
+        for (contract, _) in parser.get_contract_bases(&path).unwrap() {
+            for ctx in parser.parse_all_public_functions(&path, &contract).unwrap() {
+                let tree = TreeBuilder::build(&ctx.function_name, ctx.branch_points).unwrap();
+                let rendered = render_to_string(&tree);
+
+                assert_eq!(
+                    parse_tree(&rendered).unwrap(),
+                    without_sources(tree),
+                    "{}::{}",
+                    contract,
+                    ctx.function_name
+                );
+                checked += 1;
+            }
+        }
+    }
+
+    assert!(checked > 0);
+}
+
+// ============= Scaffold Tests =============
+
+#[test]
+fn test_scaffold_of_every_testdata_function_parses() {
+    let project = test_project();
+    let parser = SolarParser::new(&project);
+
+    let mut checked = 0;
+    for entry in std::fs::read_dir(testdata_dir()).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|ext| ext != "sol") {
+            continue;
+        }
+
+        for (contract, _) in parser.get_contract_bases(&path).unwrap() {
+            for ctx in parser.parse_all_public_functions(&path, &contract).unwrap() {
+                let tree = TreeBuilder::build(&ctx.function_name, ctx.branch_points).unwrap();
+                let scaffold = render_scaffold(&[tree], &LabelConfig::default());
+
+                // Parse the scaffold as if saved next to the fixture. Modifiers are
+                // listed with the functions
+                let scaffold_path = path.with_extension("t.sol");
+                let functions = SolarParser::new(&project)
+                    .with_source(&scaffold_path, scaffold.as_str())
+                    .get_function_names(&scaffold_path)
+                    .unwrap_or_else(|e| panic!("{}::{}: {}", contract, ctx.function_name, e));
+
+                let expected: Vec<String> = scaffold
+                    .lines()
+                    .map(str::trim)
+                    .filter_map(|line| {
+                        line.strip_prefix("modifier ")
+                            .or_else(|| line.strip_prefix("function "))
+                    })
+                    .filter_map(|rest| rest.split_once('('))
+                    .map(|(name, _)| name.to_string())
+                    .collect();
+                assert!(!functions.is_empty());
+                assert_eq!(functions, expected, "{}::{}", contract, ctx.function_name);
+                checked += 1;
+            }
+        }
+    }
+
+    assert!(checked > 0);
+}